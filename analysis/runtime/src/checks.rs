@@ -0,0 +1,33 @@
+//! Optional runtime null-pointer checking for instrumented pointer
+//! operations, enabled by setting `$C2RUST_INSTRUMENT_CHECK_NULL=1`.
+//!
+//! This is off by default since the instrumented program's own logic may
+//! already validate pointers before the instrumentation hook observes
+//! them (the checks here exist to catch violations the analysis missed,
+//! not to replace normal error handling), and because checking adds a
+//! branch to every instrumented load/store.
+//!
+//! Bounds checking is not implemented here: the instrumentation hooks only
+//! see a raw pointer value, not the allocation size it was derived from,
+//! so a true bounds check would need to be threaded through from the
+//! `malloc`/`calloc` hooks that do know the size; that's future work.
+
+use once_cell::sync::OnceCell;
+
+fn null_checks_enabled() -> bool {
+    static ENABLED: OnceCell<bool> = OnceCell::new();
+    *ENABLED.get_or_init(|| {
+        std::env::var("C2RUST_INSTRUMENT_CHECK_NULL")
+            .map(|v| v == "1" || v.eq_ignore_ascii_case("true"))
+            .unwrap_or(false)
+    })
+}
+
+/// Panic with the given operation name and MIR location if null checks are
+/// enabled and `ptr` is null. A no-op when checks are disabled (the common
+/// case), so this can be called unconditionally from every pointer hook.
+pub fn check_non_null(op: &str, mir_loc: crate::mir_loc::MirLocId, ptr: usize) {
+    if null_checks_enabled() && ptr == 0 {
+        panic!("c2rust instrumentation: null pointer dereferenced in `{op}` at mir_loc {mir_loc:?}");
+    }
+}
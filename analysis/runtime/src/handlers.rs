@@ -175,6 +175,7 @@ pub unsafe fn addr_of_sized<T: ?Sized>(mir_loc: MirLocId, ptr: *const T) {
 }
 
 pub fn load_value(mir_loc: MirLocId, ptr: usize) {
+    crate::checks::check_non_null("load_value", mir_loc, ptr);
     RUNTIME.send_event(Event {
         mir_loc,
         kind: EventKind::LoadValue(ptr),
@@ -182,6 +183,7 @@ pub fn load_value(mir_loc: MirLocId, ptr: usize) {
 }
 
 pub fn store_value(mir_loc: MirLocId, ptr: usize) {
+    crate::checks::check_non_null("store_value", mir_loc, ptr);
     RUNTIME.send_event(Event {
         mir_loc,
         kind: EventKind::StoreValue(ptr),
@@ -196,6 +198,7 @@ pub fn ptr_ret(mir_loc: MirLocId, ptr: usize) {
 }
 
 pub fn ptr_load(mir_loc: MirLocId, ptr: usize) {
+    crate::checks::check_non_null("ptr_load", mir_loc, ptr);
     RUNTIME.send_event(Event {
         mir_loc,
         kind: EventKind::LoadAddr(ptr),
@@ -203,6 +206,7 @@ pub fn ptr_load(mir_loc: MirLocId, ptr: usize) {
 }
 
 pub fn ptr_store(mir_loc: MirLocId, ptr: usize) {
+    crate::checks::check_non_null("ptr_store", mir_loc, ptr);
     RUNTIME.send_event(Event {
         mir_loc,
         kind: EventKind::StoreAddr(ptr),
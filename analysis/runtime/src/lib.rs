@@ -1,3 +1,4 @@
+pub mod checks;
 pub mod events;
 mod handlers;
 pub mod metadata;
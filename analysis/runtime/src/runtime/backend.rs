@@ -2,8 +2,10 @@ use crossbeam_queue::ArrayQueue;
 use crossbeam_utils::Backoff;
 use enum_dispatch::enum_dispatch;
 use fs_err::{File, OpenOptions};
+use once_cell::sync::OnceCell;
 use std::fmt::Debug;
 use std::io::{stderr, BufWriter, Write};
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::Arc;
 
 use bincode;
@@ -13,6 +15,36 @@ use crate::events::{Event, EventKind};
 use crate::metadata::Metadata;
 use crate::parse::{self, AsStr, GetChoices};
 
+/// Sampling rate read from `$INSTRUMENT_SAMPLE_RATE`: keep 1 out of every
+/// `N` events. Defaults to `1` (keep every event) if unset, empty, or not a
+/// positive integer, so sampling is opt-in.
+fn sample_rate() -> u64 {
+    static RATE: OnceCell<u64> = OnceCell::new();
+    *RATE.get_or_init(|| {
+        std::env::var("INSTRUMENT_SAMPLE_RATE")
+            .ok()
+            .and_then(|s| s.parse().ok())
+            .filter(|&n| n > 0)
+            .unwrap_or(1)
+    })
+}
+
+static EVENTS_SEEN: AtomicU64 = AtomicU64::new(0);
+
+/// Whether the current event should be kept under the configured sampling
+/// rate. [`EventKind::Done`] is never sampled out, so finalization always
+/// runs.
+fn should_keep(event: &Event) -> bool {
+    if matches!(event.kind, EventKind::Done) {
+        return true;
+    }
+    let rate = sample_rate();
+    if rate <= 1 {
+        return true;
+    }
+    EVENTS_SEEN.fetch_add(1, Ordering::Relaxed) % rate == 0
+}
+
 #[enum_dispatch]
 pub(super) trait WriteEvent {
     fn write(&mut self, event: Event);
@@ -98,7 +130,9 @@ impl Backend {
             };
 
             let done = matches!(event.kind, EventKind::Done);
-            self.write(event);
+            if should_keep(&event) {
+                self.write(event);
+            }
             if done {
                 break;
             }
@@ -1,5 +1,10 @@
 use crate::annotate::AnnotationBuffer;
+use crate::array_of_ptrs;
 use crate::borrowck;
+use crate::call_graph::CallGraph;
+use crate::callback_shims;
+use crate::cast_chain;
+use crate::const_fn_candidates;
 use crate::context::{
     self, AnalysisCtxt, AnalysisCtxtData, Assignment, DontRewriteFieldReason, DontRewriteFnReason,
     DontRewriteStaticReason, FlagSet, GlobalAnalysisCtxt, LFnSig, LTy, LTyCtxt, PermissionSet,
@@ -7,10 +12,15 @@ use crate::context::{
 };
 use crate::dataflow;
 use crate::dataflow::DataflowConstraints;
+use crate::derive_needs;
+use crate::double_free_guard;
 use crate::equiv::GlobalEquivSet;
 use crate::equiv::LocalEquivSet;
 use crate::labeled_ty::LabeledTyCtxt;
 use crate::last_use::{self, LastUse};
+use crate::mir_dump;
+use crate::null_guard;
+use crate::offset_expr;
 use crate::panic_detail;
 use crate::panic_detail::PanicDetail;
 use crate::pointee_type;
@@ -18,13 +28,19 @@ use crate::pointee_type::PointeeTypes;
 use crate::pointer_id::GlobalPointerTable;
 use crate::pointer_id::LocalPointerTable;
 use crate::pointer_id::PointerTable;
+use crate::provenance;
 use crate::recent_writes::RecentWrites;
+use crate::refcount;
 use crate::rewrite;
+use crate::static_locals;
+use crate::tokenize_loop;
 use crate::type_desc;
 use crate::type_desc::Ownership;
+use crate::unsafe_helper_dedup;
 use crate::util;
 use crate::util::Callee;
 use crate::util::TestAttr;
+use crate::vtable_struct;
 use c2rust_pdg::graph::Graphs;
 use c2rust_pdg::info::NodeInfo;
 use log::{debug, info, warn};
@@ -35,16 +51,19 @@ use rustc_hir::def_id::DefIndex;
 use rustc_hir::def_id::LocalDefId;
 use rustc_hir::definitions::DefPathData;
 use rustc_index::vec::IndexVec;
+use rustc_middle::middle::codegen_fn_attrs::InlineAttr;
 use rustc_middle::mir::visit::{PlaceContext, Visitor};
 use rustc_middle::mir::{
-    AggregateKind, BindingForm, Body, Constant, Local, LocalDecl, LocalInfo, LocalKind, Location,
-    Operand, Place, PlaceElem, PlaceRef, Rvalue, StatementKind,
+    AggregateKind, BasicBlock, BindingForm, Body, CastKind, Constant, Local, LocalDecl, LocalInfo,
+    LocalKind, Location, Operand, Place, PlaceElem, PlaceRef, Rvalue, StatementKind,
+    TerminatorKind,
 };
 use rustc_middle::ty::GenericArgKind;
 use rustc_middle::ty::Ty;
 use rustc_middle::ty::TyCtxt;
 use rustc_middle::ty::TyKind;
 use rustc_middle::ty::WithOptConstParam;
+use rustc_span::source_map::SourceMap;
 use rustc_span::{Span, Symbol};
 use std::collections::HashMap;
 use std::collections::HashSet;
@@ -62,6 +81,7 @@ use std::ops::DerefMut;
 use std::ops::Index;
 use std::panic::AssertUnwindSafe;
 use std::path::Path;
+use std::path::PathBuf;
 use std::str::FromStr;
 
 /// A wrapper around `T` that dynamically tracks whether it's initialized or not.
@@ -399,6 +419,91 @@ fn mark_all_structs_fixed<'tcx>(
     }
 }
 
+/// Functions whose first argument is read as a NUL-terminated byte string, such as `strlen`.
+const NUL_TERMINATED_ARG0_FNS: &[&str] = &["strlen"];
+
+/// `printf`-family functions whose first argument is the format string, which `printf` itself
+/// always reads as a NUL-terminated byte string (regardless of what its `%s` conversions, if
+/// any, refer to).  `c2rust-analyze` doesn't model variadic calls at all (see the commented-out
+/// `printf` entry in `known_fn.rs`), so this is the only part of the "printf %s" signal from the
+/// request that we can pick up without a variadic-argument-aware call model: recognizing which
+/// variadic argument corresponds to a particular `%s` in the format string would additionally
+/// require reading the literal bytes of the format-string constant out of its MIR allocation,
+/// and there's no existing, verified helper for that in this crate.
+const NUL_TERMINATED_FORMAT_ARG0_FNS: &[&str] = &[
+    "printf",
+    "fprintf",
+    "sprintf",
+    "snprintf",
+    "vprintf",
+    "vfprintf",
+    "vsprintf",
+    "vsnprintf",
+];
+
+/// Infer [`FlagSet::NUL_TERMINATED`] for pointers whose pointee is used as a NUL-terminated byte
+/// string, based on direct uses (passing the pointer to `strlen` or a `printf`-family format
+/// parameter).  This is a candidate flag, not a permission: once set, it never forces a
+/// particular rewrite, and no rewrite consults it; it is only recorded in `Assignment::flags`
+/// (see the note in `type_desc::perms_to_ptr_desc`).
+///
+/// Note this doesn't separately special-case "indexing by strlen result" (`p[strlen(p)]`): by the
+/// time this runs, equivalence-class remapping has already unified `p`'s `PointerId` with the
+/// `PointerId` of any `p.offset(...)`/`p.add(...)` derived from it, so marking `p` itself already
+/// covers that case.
+fn mark_string_flags<'tcx>(
+    gacx: &mut GlobalAnalysisCtxt<'tcx>,
+    asn: &mut Assignment,
+    tcx: TyCtxt<'tcx>,
+    func_info: &mut HashMap<LocalDefId, FuncInfo<'tcx>>,
+    all_fn_ldids: &[LocalDefId],
+) {
+    for &ldid in all_fn_ldids {
+        if gacx.fn_analysis_invalid(ldid.to_def_id()) {
+            continue;
+        }
+
+        let info = func_info.get_mut(&ldid).unwrap();
+        let ldid_const = WithOptConstParam::unknown(ldid);
+        let mir = tcx.mir_built(ldid_const);
+        let mir = mir.borrow();
+        let acx = gacx.function_context_with_data(&mir, info.acx_data.take());
+
+        for bb_data in mir.basic_blocks().iter() {
+            let (func, args) = match &bb_data.terminator().kind {
+                TerminatorKind::Call { func, args, .. } => (func, args),
+                _ => continue,
+            };
+            let func_ty = func.ty(&mir, tcx);
+            let def_id = match util::ty_callee(tcx, func_ty) {
+                Callee::UnknownDef(util::UnknownDefCallee::Direct {
+                    def_id,
+                    is_foreign: true,
+                    ..
+                }) => def_id,
+                _ => continue,
+            };
+            let name = tcx.item_name(def_id);
+            let name = name.as_str();
+            if !NUL_TERMINATED_ARG0_FNS.contains(&name)
+                && !NUL_TERMINATED_FORMAT_ARG0_FNS.contains(&name)
+            {
+                continue;
+            }
+            let arg0 = match args.get(0) {
+                Some(x) => x,
+                None => continue,
+            };
+            let ptr = acx.type_of(arg0).label;
+            if !ptr.is_none() {
+                asn.flags[ptr].insert(FlagSet::NUL_TERMINATED);
+            }
+        }
+
+        info.acx_data.set(acx.into_data());
+    }
+}
+
 fn parse_def_id(s: &str) -> Result<DefId, String> {
     // DefId debug output looks like `DefId(0:1 ~ alias1[0dc4]::{use#0})`.  The ` ~ name` part may
     // be omitted if the name/DefPath info is not available at the point in the compiler where the
@@ -521,6 +626,14 @@ fn get_fixed_defs(tcx: TyCtxt) -> io::Result<HashSet<DefId>> {
     Ok(fixed_defs)
 }
 
+fn get_dump_mir_annotated_defs() -> io::Result<HashSet<DefId>> {
+    let mut defs = HashSet::new();
+    if let Ok(path) = env::var("C2RUST_ANALYZE_DUMP_MIR_ANNOTATED_LIST") {
+        read_defs_list(&mut defs, &path)?;
+    }
+    Ok(defs)
+}
+
 fn get_force_rewrite_defs() -> io::Result<HashSet<DefId>> {
     let mut force_rewrite = HashSet::new();
     if let Ok(path) = env::var("C2RUST_ANALYZE_FORCE_REWRITE_LIST") {
@@ -529,6 +642,24 @@ fn get_force_rewrite_defs() -> io::Result<HashSet<DefId>> {
     Ok(force_rewrite)
 }
 
+fn get_impact_analysis_roots() -> io::Result<HashSet<DefId>> {
+    let mut roots = HashSet::new();
+    if let Ok(path) = env::var("C2RUST_ANALYZE_IMPACT_ANALYSIS_LIST") {
+        read_defs_list(&mut roots, &path)?;
+    }
+    Ok(roots)
+}
+
+/// Functions to rewrite in "dual implementation" mode (see [`rewrite::dual_impl`]) instead of the
+/// usual in-place rewrite.
+fn get_dual_impl_defs() -> io::Result<HashSet<DefId>> {
+    let mut dual_impl_defs = HashSet::new();
+    if let Ok(path) = env::var("C2RUST_ANALYZE_DUAL_IMPL_LIST") {
+        read_defs_list(&mut dual_impl_defs, &path)?;
+    }
+    Ok(dual_impl_defs)
+}
+
 fn get_skip_pointee_defs() -> io::Result<HashSet<DefId>> {
     let mut skip_pointee = HashSet::new();
     if let Ok(path) = env::var("C2RUST_ANALYZE_SKIP_POINTEE_LIST") {
@@ -537,7 +668,121 @@ fn get_skip_pointee_defs() -> io::Result<HashSet<DefId>> {
     Ok(skip_pointee)
 }
 
+fn get_offset_bounds_mode() -> util::OffsetBoundsMode {
+    match env::var("C2RUST_ANALYZE_OFFSET_BOUNDS_MODE") {
+        Ok(val) => util::OffsetBoundsMode::from_str(&val)
+            .unwrap_or_else(|| panic!("bad value {:?} for C2RUST_ANALYZE_OFFSET_BOUNDS_MODE", val)),
+        Err(_) => util::OffsetBoundsMode::default(),
+    }
+}
+
+fn get_offset_bounds_overrides() -> io::Result<HashMap<DefId, util::OffsetBoundsMode>> {
+    let mut overrides = HashMap::new();
+    let mut option_defs = HashSet::new();
+    if let Ok(path) = env::var("C2RUST_ANALYZE_OFFSET_OPTION_LIST") {
+        read_defs_list(&mut option_defs, &path)?;
+    }
+    for def_id in option_defs {
+        overrides.insert(def_id, util::OffsetBoundsMode::Option);
+    }
+    let mut unchecked_defs = HashSet::new();
+    if let Ok(path) = env::var("C2RUST_ANALYZE_OFFSET_UNCHECKED_LIST") {
+        read_defs_list(&mut unchecked_defs, &path)?;
+    }
+    for def_id in unchecked_defs {
+        overrides.insert(def_id, util::OffsetBoundsMode::Unchecked);
+    }
+    Ok(overrides)
+}
+
+fn get_rewrite_strategy_overrides(
+    tcx: TyCtxt,
+    all_fn_ldids: &[LocalDefId],
+) -> io::Result<HashMap<DefId, util::RewriteStrategy>> {
+    let mut overrides = HashMap::new();
+    let mut performance_first_defs = HashSet::new();
+    if let Ok(path) = env::var("C2RUST_ANALYZE_REWRITE_STRATEGY_PERFORMANCE_FIRST_LIST") {
+        read_defs_list(&mut performance_first_defs, &path)?;
+    }
+    // `#[c2rust::hot]` is the in-source equivalent of listing a function in
+    // `C2RUST_ANALYZE_REWRITE_STRATEGY_PERFORMANCE_FIRST_LIST`, for projects that would rather
+    // annotate performance-sensitive functions at the definition site than maintain an external
+    // list.
+    for &ldid in all_fn_ldids {
+        if provenance::has_c2rust_attr(tcx, ldid.to_def_id(), "hot") {
+            performance_first_defs.insert(ldid.to_def_id());
+        }
+    }
+    for def_id in performance_first_defs {
+        overrides.insert(def_id, util::RewriteStrategy::PerformanceFirst);
+    }
+    let mut minimal_churn_defs = HashSet::new();
+    if let Ok(path) = env::var("C2RUST_ANALYZE_REWRITE_STRATEGY_MINIMAL_CHURN_LIST") {
+        read_defs_list(&mut minimal_churn_defs, &path)?;
+    }
+    for def_id in minimal_churn_defs {
+        overrides.insert(def_id, util::RewriteStrategy::MinimalChurn);
+    }
+    Ok(overrides)
+}
+
+/// Crate-wide set of `Ownership` variants to never introduce, from the comma-separated
+/// `C2RUST_ANALYZE_DISABLED_OWNERSHIP` env var (e.g. `rc,box` to forbid both `Rc` and `Box`).
+fn get_disabled_ownerships() -> HashSet<Ownership> {
+    let val = match env::var("C2RUST_ANALYZE_DISABLED_OWNERSHIP") {
+        Ok(val) => val,
+        Err(_) => return HashSet::new(),
+    };
+    val.split(',')
+        .filter(|s| !s.is_empty())
+        .map(|s| {
+            Ownership::from_str(s).unwrap_or_else(|| {
+                panic!("bad value {:?} for C2RUST_ANALYZE_DISABLED_OWNERSHIP", s)
+            })
+        })
+        .collect()
+}
+
+/// Per-function overrides of [`get_disabled_ownerships`], one defs-list env var per `Ownership`
+/// variant, mirroring [`get_offset_bounds_overrides`]'s one-list-per-mode convention.  A function
+/// appearing in any of these lists uses only the variants named by the lists it appears in,
+/// replacing (not adding to) the crate-wide default -- e.g. a `no_alloc` module would list its
+/// functions in `C2RUST_ANALYZE_DISABLE_BOX_LIST` alone, even if `rc` is also disabled
+/// crate-wide, since that module's `Rc` use (if any) is unaffected by its `no_alloc` constraint.
+fn get_disabled_ownership_overrides() -> io::Result<HashMap<DefId, HashSet<Ownership>>> {
+    let lists = [
+        ("C2RUST_ANALYZE_DISABLE_RAW_LIST", Ownership::Raw),
+        ("C2RUST_ANALYZE_DISABLE_RAW_MUT_LIST", Ownership::RawMut),
+        ("C2RUST_ANALYZE_DISABLE_IMM_LIST", Ownership::Imm),
+        ("C2RUST_ANALYZE_DISABLE_CELL_LIST", Ownership::Cell),
+        ("C2RUST_ANALYZE_DISABLE_MUT_LIST", Ownership::Mut),
+        ("C2RUST_ANALYZE_DISABLE_RC_LIST", Ownership::Rc),
+        ("C2RUST_ANALYZE_DISABLE_BOX_LIST", Ownership::Box),
+    ];
+    let mut overrides: HashMap<DefId, HashSet<Ownership>> = HashMap::new();
+    for (env_var, ownership) in lists {
+        let mut defs = HashSet::new();
+        if let Ok(path) = env::var(env_var) {
+            read_defs_list(&mut defs, &path)?;
+        }
+        for def_id in defs {
+            overrides.entry(def_id).or_default().insert(ownership);
+        }
+    }
+    Ok(overrides)
+}
+
 fn get_rewrite_mode(tcx: TyCtxt, pointwise_fn_ldid: Option<LocalDefId>) -> rewrite::UpdateFiles {
+    if let Some(output_dir) = env::var_os("C2RUST_ANALYZE_OUTPUT_DIR") {
+        // `--output-dir` is annotated as conflicting with `--rewrite-mode`, so if both env vars
+        // ended up set, something upstream of us (not a user passing both flags) is to blame.
+        assert!(
+            env::var_os("C2RUST_ANALYZE_REWRITE_MODE").is_none(),
+            "C2RUST_ANALYZE_OUTPUT_DIR and C2RUST_ANALYZE_REWRITE_MODE are both set"
+        );
+        return rewrite::UpdateFiles::OutputDir(output_dir.into());
+    }
+
     let mut update_files = rewrite::UpdateFiles::No;
     if let Ok(val) = env::var("C2RUST_ANALYZE_REWRITE_MODE") {
         match val.as_str() {
@@ -562,6 +807,31 @@ fn get_rewrite_mode(tcx: TyCtxt, pointwise_fn_ldid: Option<LocalDefId>) -> rewri
     update_files
 }
 
+/// Path to write machine-applicable suggestions to, in the style of `rustc`'s own JSON
+/// suggestion output, for tools like `cargo fix` that apply rewrites from that format rather than
+/// from this tool's own `--rewrite-mode`.  Set via `--emit-suggestions-json` / the
+/// `C2RUST_ANALYZE_SUGGESTIONS_JSON_PATH` env var it's forwarded through as.
+fn get_suggestions_json_path() -> Option<PathBuf> {
+    env::var_os("C2RUST_ANALYZE_SUGGESTIONS_JSON_PATH").map(PathBuf::from)
+}
+
+/// Path to write every individual rewrite to as newline-delimited JSON, for editor tooling or
+/// review scripts that want structured rewrite data rather than the debug output `apply_rewrites`
+/// prints.  Set via `--emit-rewrites-json` / the `C2RUST_ANALYZE_REWRITES_JSON_PATH` env var it's
+/// forwarded through as.
+fn get_rewrites_json_path() -> Option<PathBuf> {
+    env::var_os("C2RUST_ANALYZE_REWRITES_JSON_PATH").map(PathBuf::from)
+}
+
+/// Path to write rewrites to as full `rustc`-diagnostic-shaped JSON (see
+/// [`rewrite::emit_rustc_diagnostics_json`]), for tools that consume `rustc --error-format=json`
+/// diagnostics directly rather than the bare per-span suggestion fragments
+/// `--emit-suggestions-json` writes.  Set via `--emit-rustc-diagnostics-json` / the
+/// `C2RUST_ANALYZE_RUSTC_DIAGNOSTICS_JSON_PATH` env var it's forwarded through as.
+fn get_rustc_diagnostics_json_path() -> Option<PathBuf> {
+    env::var_os("C2RUST_ANALYZE_RUSTC_DIAGNOSTICS_JSON_PATH").map(PathBuf::from)
+}
+
 /// Local information, specific to a single function.  Many of the data structures we use for
 /// the pointer analysis have a "global" part that's shared between all functions and a "local"
 /// part that's specific to the function being analyzed; this struct contains only the local
@@ -590,6 +860,11 @@ struct FuncInfo<'tcx> {
 }
 
 fn run(tcx: TyCtxt) {
+    if env::var("C2RUST_ANALYZE_MEMORY_REPORT").as_deref() == Ok("1") {
+        crate::mem_report::enable();
+    }
+    crate::mem_report::checkpoint("start of analysis");
+
     debug!("all defs:");
     for ldid in tcx.hir_crate_items(()).definitions() {
         //debug!("{:?} @ {:?}", ldid, tcx.source_span(ldid));
@@ -604,6 +879,8 @@ fn run(tcx: TyCtxt) {
 
     // Load the list of fixed defs early, so any errors are reported immediately.
     let fixed_defs = get_fixed_defs(tcx).unwrap();
+    let dump_mir_annotated_defs = get_dump_mir_annotated_defs().unwrap();
+    let dual_impl_defs = get_dual_impl_defs().unwrap();
 
     let rewrite_pointwise = env::var("C2RUST_ANALYZE_REWRITE_MODE")
         .ok()
@@ -620,6 +897,15 @@ fn run(tcx: TyCtxt) {
         debug!("  {:?}", ldid);
     }
 
+    let impact_analysis_roots = get_impact_analysis_roots().unwrap();
+    if !impact_analysis_roots.is_empty() {
+        let roots = impact_analysis_roots
+            .iter()
+            .filter_map(|did| did.as_local())
+            .collect::<Vec<_>>();
+        CallGraph::build(tcx, &all_fn_ldids).report_impact(tcx, &roots);
+    }
+
     gacx.force_rewrite = get_force_rewrite_defs().unwrap();
     eprintln!("{} force_rewrite defs", gacx.force_rewrite.len());
     let mut xs = gacx.force_rewrite.iter().copied().collect::<Vec<_>>();
@@ -628,6 +914,19 @@ fn run(tcx: TyCtxt) {
         eprintln!("{:?}", x);
     }
 
+    gacx.offset_bounds_mode = get_offset_bounds_mode();
+    gacx.offset_bounds_overrides = get_offset_bounds_overrides().unwrap();
+    gacx.rewrite_strategy_overrides = get_rewrite_strategy_overrides(tcx, &all_fn_ldids).unwrap();
+    gacx.normalize_byte_pointee_types =
+        env::var("C2RUST_ANALYZE_NORMALIZE_BYTE_POINTEES").as_deref() == Ok("1");
+    gacx.disabled_ownerships = get_disabled_ownerships();
+    gacx.disabled_ownership_overrides = get_disabled_ownership_overrides().unwrap();
+    let annotate_must_use = env::var("C2RUST_ANALYZE_ANNOTATE_MUST_USE").as_deref() == Ok("1");
+    let annotate_derive_needs =
+        env::var("C2RUST_ANALYZE_ANNOTATE_DERIVE_NEEDS").as_deref() == Ok("1");
+    let emit_pub_api_compat_shims =
+        env::var("C2RUST_ANALYZE_EMIT_PUB_API_COMPAT_SHIMS").as_deref() == Ok("1");
+
     populate_field_users(&mut gacx, &all_fn_ldids);
 
     // ----------------------------------
@@ -757,6 +1056,15 @@ fn run(tcx: TyCtxt) {
     }
 
     mark_foreign_fixed(&mut gacx, &mut asn, tcx);
+    mark_mismatched_cast_ptrs_fixed(&mut gacx, &mut asn, &mut func_info, &all_fn_ldids);
+    mark_keep_raw_fixed(&mut gacx, &mut asn, &mut func_info, &all_fn_ldids);
+    mark_repeat_elems_fixed(&mut gacx, &mut asn, &mut func_info, &all_fn_ldids);
+    mark_transmute_ptrs_fixed(&mut gacx, &mut asn, &mut func_info, &all_fn_ldids);
+    mark_volatile_ptrs_fixed(&mut gacx, &mut asn, &mut func_info, &all_fn_ldids);
+    mark_union_ptrs_fixed(&mut gacx, &mut asn, &mut func_info, &all_fn_ldids);
+    mark_hot_fn_ptrs_fixed(&mut gacx, &mut asn, &mut func_info, &all_fn_ldids);
+    mark_callback_ptrs_fixed(&mut gacx, &mut asn, &mut func_info, &all_fn_ldids);
+    mark_disabled_ownership_fixed(&mut gacx, &mut asn, &mut func_info, &all_fn_ldids);
 
     if rewrite_pointwise {
         // In pointwise mode, we restrict rewriting to a single fn at a time.  All statics and
@@ -880,6 +1188,135 @@ fn run(tcx: TyCtxt) {
     debug!("=== ADT Metadata ===");
     debug!("{:?}", gacx.adt_metadata);
 
+    if env::var("C2RUST_ANALYZE_DETECT_VTABLE_STRUCTS").as_deref() == Ok("1") {
+        debug!("=== vtable struct candidates ===");
+        for candidate in vtable_struct::find_vtable_struct_candidates(tcx) {
+            debug!("{:?}", candidate);
+        }
+    }
+
+    if env::var("C2RUST_ANALYZE_DETECT_MANUAL_REFCOUNT").as_deref() == Ok("1") {
+        debug!("=== manual refcount candidates ===");
+        for &ldid in &all_fn_ldids {
+            let ldid_const = WithOptConstParam::unknown(ldid);
+            let mir = tcx.mir_built(ldid_const);
+            for candidate in refcount::find_refcount_field_candidates(tcx, &mir.borrow()) {
+                debug!("{:?}: {:?}", tcx.item_name(ldid.to_def_id()), candidate);
+            }
+        }
+    }
+
+    if env::var("C2RUST_ANALYZE_DETECT_TOKENIZE_LOOPS").as_deref() == Ok("1") {
+        debug!("=== strtok/strsep tokenization loop candidates ===");
+        for &ldid in &all_fn_ldids {
+            let ldid_const = WithOptConstParam::unknown(ldid);
+            let mir = tcx.mir_built(ldid_const);
+            for bb in tokenize_loop::find_tokenize_loop_calls(tcx, &mir.borrow()) {
+                debug!("{:?}: {:?}", tcx.item_name(ldid.to_def_id()), bb);
+            }
+        }
+    }
+
+    if env::var("C2RUST_ANALYZE_DETECT_NULL_GUARDS").as_deref() == Ok("1") {
+        debug!("=== null guard candidates ===");
+        for &ldid in &all_fn_ldids {
+            let ldid_const = WithOptConstParam::unknown(ldid);
+            let mir = tcx.mir_built(ldid_const);
+            for candidate in null_guard::find_null_guard_candidates(tcx, &mir.borrow()) {
+                debug!("{:?}: {:?}", tcx.item_name(ldid.to_def_id()), candidate);
+            }
+        }
+    }
+
+    if env::var("C2RUST_ANALYZE_DETECT_DOUBLE_FREE_GUARDS").as_deref() == Ok("1") {
+        debug!("=== double-free guard candidates ===");
+        for &ldid in &all_fn_ldids {
+            let ldid_const = WithOptConstParam::unknown(ldid);
+            let mir = tcx.mir_built(ldid_const);
+            for candidate in
+                double_free_guard::find_double_free_guard_candidates(tcx, &mir.borrow())
+            {
+                debug!("{:?}: {:?}", tcx.item_name(ldid.to_def_id()), candidate);
+            }
+        }
+    }
+
+    if env::var("C2RUST_ANALYZE_DETECT_CAST_CHAINS").as_deref() == Ok("1") {
+        debug!("=== cast chain candidates ===");
+        for &ldid in &all_fn_ldids {
+            let ldid_const = WithOptConstParam::unknown(ldid);
+            let mir = tcx.mir_built(ldid_const);
+            for chain in cast_chain::find_cast_chains(tcx, &mir.borrow()) {
+                debug!("{:?}: {:?}", tcx.item_name(ldid.to_def_id()), chain);
+            }
+        }
+    }
+
+    if env::var("C2RUST_ANALYZE_DETECT_CONST_FN_CANDIDATES").as_deref() == Ok("1") {
+        debug!("=== const fn candidates ===");
+        for &ldid in &all_fn_ldids {
+            let ldid_const = WithOptConstParam::unknown(ldid);
+            let mir = tcx.mir_built(ldid_const);
+            if const_fn_candidates::is_const_fn_candidate(tcx, &mir.borrow()) {
+                debug!("{:?}", tcx.item_name(ldid.to_def_id()));
+            }
+        }
+    }
+
+    if env::var("C2RUST_ANALYZE_DETECT_SINGLE_FN_STATICS").as_deref() == Ok("1") {
+        debug!("=== single-function-access static candidates ===");
+        for (did, owner_ldid) in static_locals::find_single_fn_statics(tcx, &all_fn_ldids) {
+            debug!(
+                "{:?}: only accessed by {:?}",
+                tcx.item_name(did),
+                tcx.item_name(owner_ldid.to_def_id())
+            );
+        }
+    }
+
+    if env::var("C2RUST_ANALYZE_DETECT_SYMBOLIC_INDEX_EXPRS").as_deref() == Ok("1") {
+        debug!("=== symbolic index expression folding ===");
+        for &ldid in &all_fn_ldids {
+            let ldid_const = WithOptConstParam::unknown(ldid);
+            let mir = tcx.mir_built(ldid_const);
+            let mir = mir.borrow();
+            let recent_writes = RecentWrites::new(&mir);
+            let mut finder = IndexOperandFinder::default();
+            finder.visit_body(&mir);
+            for (loc, index_local) in finder.index_locals {
+                let op = Operand::Copy(index_local.into());
+                match offset_expr::symbolic_offset_of(&mir, &recent_writes, loc, &op) {
+                    Some(symbolic) => debug!(
+                        "{:?} at {:?}: folded index {:?} to {:?}",
+                        tcx.item_name(ldid.to_def_id()),
+                        loc,
+                        index_local,
+                        symbolic
+                    ),
+                    None => debug!(
+                        "{:?} at {:?}: could not fold index {:?}",
+                        tcx.item_name(ldid.to_def_id()),
+                        loc,
+                        index_local
+                    ),
+                }
+            }
+        }
+    }
+
+    if env::var("C2RUST_ANALYZE_DETECT_ARRAY_OF_PTRS").as_deref() == Ok("1") {
+        debug!("=== array-of-pointers candidates ===");
+        for &ldid in &all_fn_ldids {
+            let ldid_const = WithOptConstParam::unknown(ldid);
+            let mir = tcx.mir_built(ldid_const);
+            for candidate in array_of_ptrs::find_array_of_ptrs_candidates(tcx, &mir.borrow()) {
+                debug!("{:?}: {:?}", tcx.item_name(ldid.to_def_id()), candidate);
+            }
+        }
+    }
+
+    mark_string_flags(&mut gacx, &mut asn, tcx, &mut func_info, &all_fn_ldids);
+
     let mut loop_count = 0;
     loop {
         // Loop until the global assignment reaches a fixpoint.  The inner loop also runs until a
@@ -906,6 +1343,8 @@ fn run(tcx: TyCtxt) {
             let field_ltys = gacx.field_ltys.clone();
             let acx = gacx.function_context_with_data(&mir, info.acx_data.take());
 
+            let _breadcrumb =
+                panic_detail::push_breadcrumb(format!("dataflow/borrowck for function {}", name));
             let r = panic_detail::catch_unwind(AssertUnwindSafe(|| {
                 // `dataflow.propagate` and `borrowck_mir` both run until the assignment converges
                 // on a fixpoint, so there's no need to do multiple iterations here.
@@ -1150,6 +1589,8 @@ fn run(tcx: TyCtxt) {
         return;
     }
 
+    crate::mem_report::checkpoint("after per-function analysis setup, before rewriting");
+
     if !rewrite_pointwise {
         run2(
             None,
@@ -1160,6 +1601,7 @@ fn run(tcx: TyCtxt) {
             func_info,
             &all_fn_ldids,
             &fixed_defs,
+            &dual_impl_defs,
             &known_perm_error_fns,
         );
     } else {
@@ -1173,6 +1615,7 @@ fn run(tcx: TyCtxt) {
                 func_info.clone(),
                 &all_fn_ldids,
                 &fixed_defs,
+                &dual_impl_defs,
                 &known_perm_error_fns,
             );
         }
@@ -1189,6 +1632,7 @@ fn run2<'tcx>(
     mut func_info: HashMap<LocalDefId, FuncInfo<'tcx>>,
     all_fn_ldids: &Vec<LocalDefId>,
     fixed_defs: &HashSet<DefId>,
+    dual_impl_defs: &HashSet<DefId>,
     known_perm_error_fns: &HashSet<DefId>,
 ) {
     // ----------------------------------
@@ -1255,11 +1699,27 @@ fn run2<'tcx>(
     }
     let manual_shim_casts = manual_shim_casts;
 
+    let log_pointer_decisions =
+        env::var("C2RUST_ANALYZE_POINTER_DECISION_LOG").as_deref() == Ok("1");
+
+    let dedup_unsafe_helpers = unsafe_helper_dedup::enabled();
+    // Span right before the first rewritten function we see; a valid place to insert the
+    // `macro_rules!` item `unsafe_helper_dedup` generates, if it ends up generating one.
+    let mut dedup_insert_span: Option<Span> = None;
+    // Set when a block's rewrites failed to generate and its locals got pinned `FIXED` (see
+    // `make_block_locals_fixed`), so other rewrites in the same function stop assuming a
+    // representation the failed block can't provide. Like `dont_rewrite_fns`'s `new_keys()`,
+    // this forces another trip around the fixed-point loop below to regenerate rewrites against
+    // the newly-`FIXED` pointers.
+    let mut any_new_block_fixed = false;
+
     // It may take multiple tries to reach a state where all rewrites succeed.
     for i in 0.. {
         assert!(i < 100);
         func_reports.clear();
         all_rewrites.clear();
+        dedup_insert_span = None;
+        any_new_block_fixed = false;
         info!("--- start rewriting ---");
 
         // Update non-rewritten items first.  This has two purposes.  First, it clears the
@@ -1268,6 +1728,11 @@ fn run2<'tcx>(
         // rewrite, such as pointers in the signatures of non-rewritten functions.
         process_new_dont_rewrite_items(&mut gacx, &mut asn);
 
+        // Both passes below independently derive each pointer's target representation from
+        // `asn.perms()`/`asn.flags()`; this plan lets them assert they agree instead of silently
+        // producing rewrites that disagree with each other.
+        let plan = rewrite::RewritePlan::build(asn.perms(), asn.flags());
+
         for &ldid in all_fn_ldids {
             if gacx.dont_rewrite_fn(ldid.to_def_id()) {
                 continue;
@@ -1290,16 +1755,24 @@ fn run2<'tcx>(
                 }
 
                 let hir_body_id = tcx.hir().body_owned_by(ldid);
-                let expr_rewrites = rewrite::gen_expr_rewrites(
+                let (expr_rewrites, block_failures) = rewrite::gen_expr_rewrites(
                     &mut acx,
                     &asn,
+                    &plan,
                     pointee_types,
                     &info.last_use,
                     ldid.to_def_id(),
                     &mir,
                     hir_body_id,
                 );
-                let ty_rewrites = rewrite::gen_ty_rewrites(&acx, &asn, pointee_types, &mir, ldid);
+                let mut ty_rewrites =
+                    rewrite::gen_ty_rewrites(&acx, &asn, &plan, pointee_types, &mir, ldid);
+                if annotate_must_use {
+                    if let Some(span) = must_use_attr_span(&acx, &asn, ldid) {
+                        ty_rewrites
+                            .push((span, rewrite::Rewrite::Print("#[must_use] ".to_string())));
+                    }
+                }
                 // Print rewrites
                 let report = func_reports.entry(ldid).or_default();
                 writeln!(
@@ -1313,9 +1786,48 @@ fn run2<'tcx>(
                 for &(span, ref rw) in expr_rewrites.iter().chain(ty_rewrites.iter()) {
                     writeln!(report, "  {}: {}", describe_span(tcx, span), rw).unwrap();
                 }
+                // A block that panicked while generating its rewrites is left with no rewrites of
+                // its own; note it in the report so it's visible to a reviewer even though it
+                // didn't fail the whole function. Its locals get pinned `FIXED` below, so the
+                // rest of the function's rewrites (including its own signature, if any of these
+                // locals flow into it) don't end up assuming a representation this block can't
+                // provide.
+                for (bb, pd) in &block_failures {
+                    writeln!(
+                        report,
+                        "  {:?}: panicked while generating rewrites, skipping this block: {}",
+                        bb,
+                        pd.to_string_short()
+                    )
+                    .unwrap();
+                    make_block_locals_fixed(&acx, &mut asn, &mir, *bb);
+                    any_new_block_fixed = true;
+                }
+                if log_pointer_decisions {
+                    write_pointer_decision_log(
+                        report,
+                        &acx,
+                        &asn,
+                        &mir,
+                        INITIAL_PERMS,
+                        INITIAL_FLAGS,
+                    );
+                }
                 writeln!(report).unwrap();
-                all_rewrites.extend(expr_rewrites);
-                all_rewrites.extend(ty_rewrites);
+                let hir_id = tcx.hir().local_def_id_to_hir_id(ldid);
+                let fn_span = tcx.hir().span(hir_id);
+                if dedup_unsafe_helpers && dedup_insert_span.is_none() {
+                    dedup_insert_span = Some(fn_span.shrink_to_lo());
+                }
+                if dual_impl_defs.contains(&ldid.to_def_id()) {
+                    let rws = expr_rewrites.into_iter().chain(ty_rewrites).collect();
+                    let dual_rw =
+                        rewrite::build_dual_impl_rewrite(tcx.sess.source_map(), fn_span, rws);
+                    all_rewrites.push((fn_span, dual_rw));
+                } else {
+                    all_rewrites.extend(expr_rewrites);
+                    all_rewrites.extend(ty_rewrites);
+                }
             }));
 
             info.acx_data.set(acx.into_data());
@@ -1353,11 +1865,49 @@ fn run2<'tcx>(
             }
         }
 
+        // Generate `#[deprecated]` compatibility shims, with the old raw signature, for public
+        // functions whose signature rewriting changed, so that crates downstream of this one don't
+        // hard-break when they're recompiled against the rewritten signature.
+        if emit_pub_api_compat_shims {
+            let mut api_change_report = String::new();
+            for &ldid in &all_fn_ldids {
+                let def_id = ldid.to_def_id();
+                if !tcx.visibility(def_id).is_public() {
+                    continue;
+                }
+                let desc = match rewrite::describe_api_change(&gacx, &asn, def_id) {
+                    Some(x) => x,
+                    None => continue,
+                };
+                writeln!(api_change_report, "    {}", desc).unwrap();
+
+                let r = panic_detail::catch_unwind(AssertUnwindSafe(|| {
+                    rewrite::gen_pub_api_compat_shim_rewrites(
+                        &gacx,
+                        &asn,
+                        def_id,
+                        manual_shim_casts,
+                    )
+                }));
+                match r {
+                    Ok(rws) => all_rewrites.extend(rws),
+                    Err(pd) => {
+                        gacx.mark_fn_failed(
+                            def_id,
+                            DontRewriteFnReason::SHIM_GENERATION_FAILED,
+                            pd,
+                        );
+                    }
+                }
+            }
+            debug!("public API changes:\n{}", api_change_report);
+        }
+
         // Exit the loop upon reaching a fixpoint.
         let any_new_dont_rewrite_keys = !gacx.dont_rewrite_fns.new_keys().is_empty()
             || !gacx.dont_rewrite_statics.new_keys().is_empty()
             || !gacx.dont_rewrite_fields.new_keys().is_empty();
-        if !any_new_dont_rewrite_keys {
+        if !any_new_dont_rewrite_keys && !any_new_block_fixed {
             break;
         }
     }
@@ -1399,7 +1949,16 @@ fn run2<'tcx>(
             continue;
         }
 
-        let adt_rewrites = rewrite::gen_adt_ty_rewrites(&gacx, &asn, global_pointee_types, def_id);
+        let mut adt_rewrites =
+            rewrite::gen_adt_ty_rewrites(&gacx, &asn, global_pointee_types, def_id);
+        if annotate_derive_needs {
+            if let Some(span) = derive_needs::derive_attr_span(&gacx, &asn, def_id) {
+                adt_rewrites.push((
+                    span,
+                    rewrite::Rewrite::Print("#[derive(Default, Clone)] ".to_string()),
+                ));
+            }
+        }
         let report = adt_reports.entry(def_id).or_default();
         writeln!(
             report,
@@ -1460,6 +2019,11 @@ fn run2<'tcx>(
         debug!("\ntype assignment for {:?}:", name);
         rewrite::dump_rewritten_local_tys(&acx, &asn, pointee_types, &mir, describe_local);
 
+        if dump_mir_annotated_defs.contains(&ldid.to_def_id()) {
+            debug!("\nannotated MIR for {:?}:", name);
+            mir_dump::dump_annotated_mir(&acx, &asn, &mir);
+        }
+
         if let Some(report) = func_reports.remove(&ldid) {
             debug!("{}", report);
         }
@@ -1610,12 +2174,27 @@ fn run2<'tcx>(
     // Apply rewrites
     // ----------------------------------
 
+    if let Some(insert_span) = dedup_insert_span {
+        unsafe_helper_dedup::dedup_offset_raw_unsafe(&mut all_rewrites, insert_span);
+    }
+
     let annotations = ann.finish();
 
     // Apply rewrite to all functions at once.
+    if let Some(path) = get_suggestions_json_path() {
+        rewrite::emit_machine_applicable_suggestions(tcx, all_rewrites.clone(), &path);
+    }
+    if let Some(path) = get_rewrites_json_path() {
+        rewrite::emit_rewrites_json(tcx, &all_rewrites, &path);
+    }
+    if let Some(path) = get_rustc_diagnostics_json_path() {
+        rewrite::emit_rustc_diagnostics_json(tcx, all_rewrites.clone(), &path);
+    }
     let update_files = get_rewrite_mode(tcx, pointwise_fn_ldid);
     rewrite::apply_rewrites(tcx, all_rewrites, annotations, update_files);
 
+    crate::mem_report::checkpoint("after applying rewrites");
+
     // ----------------------------------
     // Report caught panics
     // ----------------------------------
@@ -1623,11 +2202,13 @@ fn run2<'tcx>(
     // Report errors that were caught previously
     debug!("\nerror details:");
     for ldid in tcx.hir().body_owners() {
-        if let Some(detail) = gacx.fns_failed.get(&ldid.to_def_id()) {
-            if !detail.has_backtrace() {
-                continue;
+        if let Some(details) = gacx.fns_failed.get(&ldid.to_def_id()) {
+            for detail in details {
+                if !detail.has_backtrace() {
+                    continue;
+                }
+                debug!("\nerror in {:?}:{}", ldid, detail.to_string_full());
             }
-            debug!("\nerror in {:?}:{}", ldid, detail.to_string_full());
         }
     }
 
@@ -1638,24 +2219,31 @@ fn run2<'tcx>(
         v
     }
     for def_id in sorted_def_ids(gacx.dont_rewrite_fns.keys()) {
-        let opt_detail = gacx.fns_failed.get(&def_id);
+        let details = gacx.fns_failed.get(&def_id);
         let flags = gacx.dont_rewrite_fns.get(def_id);
-        assert!(opt_detail.is_some() || !flags.is_empty());
-        let detail_str = match opt_detail {
-            Some(detail) => detail.to_string_short(),
+        assert!(details.is_some() || !flags.is_empty());
+        let detail_str = match details {
+            Some(details) => details
+                .iter()
+                .map(|detail| detail.to_string_short())
+                .collect::<Vec<_>>()
+                .join("; "),
             None => "(no panic)".into(),
         };
-        debug!("analysis of {def_id:?} failed: {flags:?}, {detail_str}");
+        let c_loc = provenance::describe(tcx, def_id);
+        debug!("analysis of {def_id:?}{c_loc} failed: {flags:?}, {detail_str}");
     }
 
     for def_id in sorted_def_ids(gacx.dont_rewrite_statics.keys()) {
         let flags = gacx.dont_rewrite_statics.get(def_id);
-        debug!("analysis of {def_id:?} failed: {flags:?}");
+        let c_loc = provenance::describe(tcx, def_id);
+        debug!("analysis of {def_id:?}{c_loc} failed: {flags:?}");
     }
 
     for def_id in sorted_def_ids(gacx.dont_rewrite_fields.keys()) {
         let flags = gacx.dont_rewrite_fields.get(def_id);
-        debug!("analysis of {def_id:?} failed: {flags:?}");
+        let c_loc = provenance::describe(tcx, def_id);
+        debug!("analysis of {def_id:?}{c_loc} failed: {flags:?}");
     }
 
     info!(
@@ -1730,6 +2318,67 @@ fn assign_pointer_ids<'tcx>(
         gacx.fn_sigs.insert(did, lsig);
     }
 
+    // Cross-crate `#[inline(always)]` function signatures.
+    //
+    // Transpiled workspaces commonly split `#[inline(always)]` helpers out into a separate
+    // "common" crate; MIR for such functions is embedded in that crate's metadata (codegen needs
+    // it to inline the call), so a call to one of them doesn't have to fall back to the
+    // unhandled/pessimistic `Callee::UnknownDef` treatment the way a call to an arbitrary
+    // dynamically-linked external function does. For now we only use the declared signature here
+    // (the same `PointerInfo::ANNOTATED` treatment as the `extern "C"` items above), which is
+    // already strictly better than giving up on the call entirely; re-running this crate's own
+    // dataflow pass over the borrowed MIR to derive a flow-sensitive summary is future work.
+    let mut seen_inline_fns = HashSet::new();
+    for &ldid in all_fn_ldids {
+        let ldid_const = WithOptConstParam::unknown(ldid);
+        let mir = tcx.mir_built(ldid_const);
+        let mir = mir.borrow();
+        for bb_data in mir.basic_blocks().iter() {
+            let term = match &bb_data.terminator {
+                Some(term) => term,
+                None => continue,
+            };
+            let func = match &term.kind {
+                TerminatorKind::Call { func, .. } => func,
+                _ => continue,
+            };
+            let did = match *func.ty(&mir, tcx).kind() {
+                TyKind::FnDef(did, _) => did,
+                _ => continue,
+            };
+            if did.is_local() || seen_inline_fns.contains(&did) {
+                continue;
+            }
+            if tcx.def_kind(tcx.parent(did)) == DefKind::ForeignMod {
+                continue;
+            }
+            if !matches!(tcx.codegen_fn_attrs(did).inline, InlineAttr::Always) {
+                continue;
+            }
+            if !tcx.is_mir_available(did) {
+                continue;
+            }
+            seen_inline_fns.insert(did);
+
+            let sig = tcx.erase_late_bound_regions(tcx.fn_sig(did));
+            let inputs = sig
+                .inputs()
+                .iter()
+                .map(|&ty| gacx.assign_pointer_ids_with_info(ty, PointerInfo::ANNOTATED))
+                .collect::<Vec<_>>();
+            let inputs = gacx.lcx.mk_slice(&inputs);
+            let output = gacx.assign_pointer_ids_with_info(sig.output(), PointerInfo::ANNOTATED);
+            let c_variadic = sig.c_variadic;
+
+            let lsig = LFnSig {
+                inputs,
+                output,
+                c_variadic,
+            };
+            gacx.fn_sigs.insert(did, lsig);
+        }
+    }
+
     // Global items: statics
 
     // Collect all `static` items.
@@ -2177,47 +2826,719 @@ fn build_dataflow_constraints<'tcx>(
     }
 }
 
-fn make_ty_fixed(asn: &mut Assignment, lty: LTy) {
-    for lty in lty.iter() {
-        let ptr = lty.label;
-        if !ptr.is_none() {
-            asn.flags[ptr].insert(FlagSet::FIXED);
-        }
-    }
-}
-
-fn make_sig_fixed(asn: &mut Assignment, lsig: &LFnSig) {
-    for lty in lsig.inputs.iter().copied().chain(iter::once(lsig.output)) {
-        make_ty_fixed(asn, lty);
-    }
-}
-
-/// For testing, putting #[c2rust_analyze_test::fail_before_analysis] on a function marks it as
-/// failed at this point.
-fn apply_test_attr_fail_before_analysis(
-    gacx: &mut GlobalAnalysisCtxt,
+/// Scan every function for `Rvalue::Cast`s that reinterpret a pointer's pointee as an unrelated,
+/// non-transmutable type (the `Some(false)` case handled by [`type_check::visit_cast`]'s
+/// `CastKind::Misc` arm).  Since those two pointers are deliberately left unconnected in the
+/// dataflow graph (unifying them would let the rewriter convert one side to a safe reference typed
+/// as the other side's pointee), we additionally pin both sides to `FIXED` here so neither one
+/// gets rewritten away from its original raw pointer type; the cast is left in the output as an
+/// explicit raw-pointer reinterpretation that a human needs to audit for `transmute`-like
+/// soundness.
+fn mark_mismatched_cast_ptrs_fixed<'tcx>(
+    gacx: &mut GlobalAnalysisCtxt<'tcx>,
+    asn: &mut Assignment,
+    func_info: &mut HashMap<LocalDefId, FuncInfo<'tcx>>,
     all_fn_ldids: &[LocalDefId],
 ) {
     let tcx = gacx.tcx;
     for &ldid in all_fn_ldids {
-        if !util::has_test_attr(tcx, ldid, TestAttr::FailBeforeAnalysis) {
+        if gacx.fn_analysis_invalid(ldid.to_def_id()) {
             continue;
         }
-        gacx.mark_fn_failed(
-            ldid.to_def_id(),
-            DontRewriteFnReason::FAKE_INVALID_FOR_TESTING,
-            PanicDetail::new("explicit fail_before_analysis for testing".to_owned()),
-        );
-    }
-}
 
-/// For testing, putting #[c2rust_analyze_test::force_non_null_args] on a function marks its
-/// arguments as `NON_NULL` and also adds `NON_NULL` to the `updates_forbidden` mask.
-fn apply_test_attr_force_non_null_args(
-    gacx: &mut GlobalAnalysisCtxt,
-    all_fn_ldids: &[LocalDefId],
-    asn: &mut Assignment,
-    updates_forbidden: &mut GlobalPointerTable<PermissionSet>,
+        let ldid_const = WithOptConstParam::unknown(ldid);
+        let info = func_info.get_mut(&ldid).unwrap();
+        let mir = tcx.mir_built(ldid_const);
+        let mir = mir.borrow();
+
+        let acx = gacx.function_context_with_data(&mir, info.acx_data.take());
+
+        for (bb, bb_data) in mir.basic_blocks().iter_enumerated() {
+            for (i, stmt) in bb_data.statements.iter().enumerate() {
+                let (_pl, rv) = match &stmt.kind {
+                    StatementKind::Assign(x) => &**x,
+                    _ => continue,
+                };
+                let (op, to_ty) = match *rv {
+                    Rvalue::Cast(CastKind::Misc, ref op, ty) => (op, ty),
+                    _ => continue,
+                };
+                let from_ty = op.ty(&mir.local_decls, tcx);
+                if util::is_transmutable_ptr_cast(from_ty, to_ty) != Some(false) {
+                    continue;
+                }
+                if util::is_benign_void_ptr_cast(tcx, from_ty, to_ty) {
+                    continue;
+                }
+
+                let loc = Location {
+                    block: bb,
+                    statement_index: i,
+                };
+                let rv_lty = match acx.rvalue_tys.get(&loc) {
+                    Some(&lty) => lty,
+                    None => continue,
+                };
+                debug!(
+                    "pinning mismatched-pointee cast at {:?} ({:?} as {:?}) to FIXED on both sides",
+                    loc, from_ty, to_ty
+                );
+                make_ty_fixed(asn, acx.type_of(op));
+                make_ty_fixed(asn, rv_lty);
+            }
+        }
+
+        info.acx_data.set(acx.into_data());
+    }
+}
+
+/// Collects every distinct `Local` a basic block mentions, discarding `Location`s -- used by
+/// [`make_block_locals_fixed`] to pin a whole failed block's locals `FIXED` at once.
+#[derive(Default)]
+struct BlockLocalFinder {
+    locals: HashSet<Local>,
+}
+
+impl<'tcx> Visitor<'tcx> for BlockLocalFinder {
+    fn visit_local(&mut self, local: Local, _context: PlaceContext, _location: Location) {
+        self.locals.insert(local);
+    }
+}
+
+/// Pin `FIXED` the type of every local mentioned in `bb`'s statements/terminator.
+///
+/// This is for a block whose rewrites couldn't be generated (see
+/// `rewrite::expr::mir_op::gen_mir_rewrites`'s doc comment): left alone, the block keeps its original
+/// raw-pointer representation while its neighboring blocks, or a `ty_rewrite` to the function's
+/// own signature, may go ahead assuming a rewritten (non-`FIXED`) representation for the same
+/// pointers, producing code that doesn't type-check. Pinning the block's locals here, then
+/// rejoining the fixed-point loop in `run2` so every other rewrite for this function is
+/// regenerated against the now-`FIXED` pointers, keeps the function's rewrites internally
+/// consistent instead of emitting a partially-applied, non-compiling mix.
+fn make_block_locals_fixed(acx: &AnalysisCtxt, asn: &mut Assignment, mir: &Body, bb: BasicBlock) {
+    let mut finder = BlockLocalFinder::default();
+    let bb_data = &mir.basic_blocks()[bb];
+    for (i, stmt) in bb_data.statements.iter().enumerate() {
+        let loc = Location {
+            block: bb,
+            statement_index: i,
+        };
+        finder.visit_statement(stmt, loc);
+    }
+    if let Some(ref term) = bb_data.terminator {
+        let loc = Location {
+            block: bb,
+            statement_index: bb_data.statements.len(),
+        };
+        finder.visit_terminator(term, loc);
+    }
+    for local in finder.locals {
+        make_ty_fixed(asn, acx.type_of(local));
+    }
+}
+
+fn make_ty_fixed(asn: &mut Assignment, lty: LTy) {
+    for lty in lty.iter() {
+        let ptr = lty.label;
+        if !ptr.is_none() {
+            asn.flags[ptr].insert(FlagSet::FIXED);
+        }
+    }
+}
+
+fn make_sig_fixed(asn: &mut Assignment, lsig: &LFnSig) {
+    for lty in lsig.inputs.iter().copied().chain(iter::once(lsig.output)) {
+        make_ty_fixed(asn, lty);
+    }
+}
+
+/// Marker comment that, written on the same line as an assignment statement, pins every pointer
+/// that statement's place and rvalue mention to `FIXED` -- see `mark_keep_raw_fixed` below.
+const KEEP_RAW_COMMENT: &str = "c2rust: keep-raw";
+
+/// Returns `true` if the source line containing `span` has a trailing [`KEEP_RAW_COMMENT`]
+/// comment. Plain comments are discarded before the HIR/MIR are built, so there's no AST node to
+/// check here; this reads the line straight out of the `SourceMap` instead.
+fn line_has_keep_raw_comment(sm: &SourceMap, span: Span) -> bool {
+    let pos = span.lo();
+    let file_idx = sm.lookup_source_file_idx(pos);
+    let sf = &sm.files()[file_idx];
+    let line = match sf.lookup_line(pos) {
+        Some(line) => line,
+        None => return false,
+    };
+    let text = match sf.get_line(line) {
+        Some(text) => text,
+        None => return false,
+    };
+    text.contains(KEEP_RAW_COMMENT)
+}
+
+/// Pin the pointers mentioned by an assignment statement to `FIXED` when that statement is
+/// marked with a trailing `// c2rust: keep-raw` comment. This is the per-statement counterpart
+/// to `#[c2rust_analyze_test::fixed_signature]` (see `util::TestAttr`), which today is the only
+/// way to opt out of rewriting and only works at whole-function granularity.
+///
+/// The granularity here is still the *pointer*, not the *statement*: `FlagSet::FIXED` is
+/// recorded per `PointerId`, shared by every place that pointer's value flows through, so pinning
+/// a pointer used in a `keep-raw`-marked statement also keeps it raw everywhere else in the
+/// function, not just at the marked statement. Genuinely per-occurrence suppression -- rewriting
+/// the same pointer differently at two different expressions -- isn't possible without teaching
+/// `type_desc`/`rewrite::ty` to track more than one representation per pointer, which is out of
+/// scope here.
+fn mark_keep_raw_fixed<'tcx>(
+    gacx: &mut GlobalAnalysisCtxt<'tcx>,
+    asn: &mut Assignment,
+    func_info: &mut HashMap<LocalDefId, FuncInfo<'tcx>>,
+    all_fn_ldids: &[LocalDefId],
+) {
+    let tcx = gacx.tcx;
+    let sm = tcx.sess.source_map();
+    for &ldid in all_fn_ldids {
+        if gacx.fn_analysis_invalid(ldid.to_def_id()) {
+            continue;
+        }
+
+        let ldid_const = WithOptConstParam::unknown(ldid);
+        let info = func_info.get_mut(&ldid).unwrap();
+        let mir = tcx.mir_built(ldid_const);
+        let mir = mir.borrow();
+
+        let acx = gacx.function_context_with_data(&mir, info.acx_data.take());
+
+        for (bb, bb_data) in mir.basic_blocks().iter_enumerated() {
+            for (i, stmt) in bb_data.statements.iter().enumerate() {
+                if !line_has_keep_raw_comment(sm, stmt.source_info.span) {
+                    continue;
+                }
+                let pl = match &stmt.kind {
+                    StatementKind::Assign(x) => x.0,
+                    _ => continue,
+                };
+                let loc = Location {
+                    block: bb,
+                    statement_index: i,
+                };
+                debug!("pinning keep-raw statement at {:?} to FIXED", loc);
+                make_ty_fixed(asn, acx.type_of(pl));
+                if let Some(&rv_lty) = acx.rvalue_tys.get(&loc) {
+                    make_ty_fixed(asn, rv_lty);
+                }
+            }
+        }
+
+        info.acx_data.set(acx.into_data());
+    }
+}
+
+/// Collects the `(Location, Local)` of every `PlaceElem::Index` projection in a MIR body, i.e.
+/// every place of the form `arr[i]`, paired with `i`.
+#[derive(Default)]
+struct IndexOperandFinder {
+    index_locals: Vec<(Location, Local)>,
+}
+
+impl<'tcx> Visitor<'tcx> for IndexOperandFinder {
+    fn visit_place(&mut self, place: &Place<'tcx>, _context: PlaceContext, location: Location) {
+        for elem in place.projection.iter() {
+            if let PlaceElem::Index(local) = elem {
+                self.index_locals.push((location, local));
+            }
+        }
+    }
+}
+
+/// Scan every function for `Rvalue::Repeat`s (`[op; N]`) whose operand is a pointer, and pin that
+/// pointer's element type to `FIXED`.
+///
+/// `[op; N]` requires `op: Copy`, so if the rewriter ever turned the repeated pointer into a
+/// non-`Copy` representation (`Box<T>`, a `DynOwned` slot, ...), the output wouldn't compile --
+/// and even if it did, N boxes all wrapping the same pointer value would double-free on drop.
+/// Dataflow currently always unifies the operand's `PointerId` with the array's element
+/// `PointerId` (see the long-standing `TODO` in `dataflow::type_check`'s `Rvalue::Repeat` arm), so
+/// there's no way yet to give the repeated operand a different (non-`Copy`) representation than
+/// the array it produces; pinning here just makes that existing restriction explicit and keeps
+/// the repeated pointer a plain raw pointer rather than relying on the coincidence.
+fn mark_repeat_elems_fixed<'tcx>(
+    gacx: &mut GlobalAnalysisCtxt<'tcx>,
+    asn: &mut Assignment,
+    func_info: &mut HashMap<LocalDefId, FuncInfo<'tcx>>,
+    all_fn_ldids: &[LocalDefId],
+) {
+    let tcx = gacx.tcx;
+    for &ldid in all_fn_ldids {
+        if gacx.fn_analysis_invalid(ldid.to_def_id()) {
+            continue;
+        }
+
+        let ldid_const = WithOptConstParam::unknown(ldid);
+        let info = func_info.get_mut(&ldid).unwrap();
+        let mir = tcx.mir_built(ldid_const);
+        let mir = mir.borrow();
+
+        let acx = gacx.function_context_with_data(&mir, info.acx_data.take());
+
+        for bb_data in mir.basic_blocks().iter() {
+            for stmt in &bb_data.statements {
+                let (_pl, rv) = match &stmt.kind {
+                    StatementKind::Assign(x) => &**x,
+                    _ => continue,
+                };
+                let op = match rv {
+                    Rvalue::Repeat(op, _) => op,
+                    _ => continue,
+                };
+                let op_lty = acx.type_of(op);
+                if op_lty.label.is_none() {
+                    continue;
+                }
+                debug!("pinning repeat operand {:?} to FIXED", op);
+                make_ty_fixed(asn, op_lty);
+            }
+        }
+
+        info.acx_data.set(acx.into_data());
+    }
+}
+
+/// Scan every pointer for a use of an [`Ownership`] variant disabled (crate-wide, or for that
+/// specific function) via [`GlobalAnalysisCtxt::disabled_ownerships`], and pin any such pointer
+/// to `FIXED` instead.
+///
+/// This doesn't attempt to substitute a different, still-allowed representation: most of the
+/// `Ownership` lattice's rules exist because no other representation is sound for that pointer.
+/// A pointer with `PermissionSet::FREE` set genuinely owns an allocation it must free, so if
+/// `box` is disabled there's no sound fallback short of leaving it a raw pointer; likewise a
+/// `WRITE`-without-`UNIQUE` pointer needs `Cell`'s aliased mutability, and disabling `cell` can't
+/// be patched by silently picking `Imm` (which would drop the write) or `Mut` (which would be
+/// unsound under aliasing). So disabling an `Ownership` variant doesn't pick the "next-best"
+/// representation from the lattice; it reports the pointer as unconvertible and leaves its
+/// original raw-pointer representation in place, exactly like every other `mark_*_fixed` pass in
+/// this module.
+fn mark_disabled_ownership_fixed<'tcx>(
+    gacx: &mut GlobalAnalysisCtxt<'tcx>,
+    asn: &mut Assignment,
+    func_info: &mut HashMap<LocalDefId, FuncInfo<'tcx>>,
+    all_fn_ldids: &[LocalDefId],
+) {
+    let tcx = gacx.tcx;
+    for &ldid in all_fn_ldids {
+        let def_id = ldid.to_def_id();
+        if gacx.fn_analysis_invalid(def_id) {
+            continue;
+        }
+        if gacx.disabled_ownerships(def_id).is_empty() {
+            continue;
+        }
+
+        let ldid_const = WithOptConstParam::unknown(ldid);
+        let info = func_info.get_mut(&ldid).unwrap();
+        let mir = tcx.mir_built(ldid_const);
+        let mir = mir.borrow();
+
+        let acx = gacx.function_context_with_data(&mir, info.acx_data.take());
+
+        for local in mir.local_decls.indices() {
+            let lty = acx.type_of(local);
+            for lty in lty.iter() {
+                let ptr = lty.label;
+                if ptr.is_none() {
+                    continue;
+                }
+                if asn.flags[ptr].contains(FlagSet::FIXED) {
+                    continue;
+                }
+                let own = type_desc::perms_to_ptr_desc(asn.perms[ptr], asn.flags[ptr]).own;
+                if !gacx.disabled_ownerships(def_id).contains(&own) {
+                    continue;
+                }
+                warn!(
+                    "function {:?}, local {:?}: pointer {:?} would be rewritten to a disabled \
+                     ownership {:?}; pinning to its original raw representation instead",
+                    def_id, local, ptr, own
+                );
+                asn.flags[ptr].insert(FlagSet::FIXED);
+            }
+        }
+
+        info.acx_data.set(acx.into_data());
+    }
+}
+
+/// Scan every function whose [`RewriteStrategy`](util::RewriteStrategy) is
+/// [`PerformanceFirst`](util::RewriteStrategy::PerformanceFirst) (see `#[c2rust::hot]` in
+/// [`get_rewrite_strategy_overrides`]) for pointers that would otherwise be rewritten into
+/// `Box<T>`, and pin them to `FIXED` instead.
+///
+/// `OffsetBoundsMode` (consulted in `rewrite::expr::mir_op`) already keeps indexing on a hot
+/// function's pointers unchecked, so the one remaining "no longer zero-cost" rewrite this crate
+/// can produce is adding an owning heap allocation: a pointer with `PermissionSet::FREE` gets
+/// rewritten to `Box<T>`, trading a plain pointer for a heap pointer plus a drop glue call. For a
+/// hot inner loop that's exactly the kind of added indirection the caller asked to avoid, so when
+/// a pointer on a hot function would take that path, leave it as a raw pointer and `warn!` about
+/// it instead -- a human can still convert it by hand if they decide the safety is worth it here.
+fn mark_hot_fn_ptrs_fixed<'tcx>(
+    gacx: &mut GlobalAnalysisCtxt<'tcx>,
+    asn: &mut Assignment,
+    func_info: &mut HashMap<LocalDefId, FuncInfo<'tcx>>,
+    all_fn_ldids: &[LocalDefId],
+) {
+    let tcx = gacx.tcx;
+    for &ldid in all_fn_ldids {
+        let def_id = ldid.to_def_id();
+        if gacx.fn_analysis_invalid(def_id) {
+            continue;
+        }
+        if gacx.rewrite_strategy(def_id) != util::RewriteStrategy::PerformanceFirst {
+            continue;
+        }
+
+        let ldid_const = WithOptConstParam::unknown(ldid);
+        let info = func_info.get_mut(&ldid).unwrap();
+        let mir = tcx.mir_built(ldid_const);
+        let mir = mir.borrow();
+
+        let acx = gacx.function_context_with_data(&mir, info.acx_data.take());
+
+        for local in mir.local_decls.indices() {
+            let lty = acx.type_of(local);
+            for lty in lty.iter() {
+                let ptr = lty.label;
+                if ptr.is_none() {
+                    continue;
+                }
+                if !asn.perms[ptr].contains(PermissionSet::FREE) {
+                    continue;
+                }
+                warn!(
+                    "hot function {:?}, local {:?}: pointer {:?} would be rewritten to `Box`; \
+                     pinning to its original raw representation instead",
+                    def_id, local, ptr
+                );
+                asn.flags[ptr].insert(FlagSet::FIXED);
+            }
+        }
+
+        info.acx_data.set(acx.into_data());
+    }
+}
+
+/// Pin every pointer in the signature of a function registered as a callback with a known libc
+/// API (see [`callback_shims::find_libc_callbacks`]) to its original raw representation.
+///
+/// Such a function's signature can't be rewritten: there's no call site in the rewritten crate
+/// for the rewriter to update to match, since the call happens on the C side via the bare
+/// function pointer handed to e.g. `qsort`. Leaving the body otherwise untouched (rather than
+/// attempting the raw-to-safe conversion at entry that a real "callback shim" would need) is the
+/// same honest scoping [`callback_shims`] documents.
+fn mark_callback_ptrs_fixed<'tcx>(
+    gacx: &mut GlobalAnalysisCtxt<'tcx>,
+    asn: &mut Assignment,
+    func_info: &mut HashMap<LocalDefId, FuncInfo<'tcx>>,
+    all_fn_ldids: &[LocalDefId],
+) {
+    let tcx = gacx.tcx;
+    let callbacks = callback_shims::find_libc_callbacks(tcx, all_fn_ldids);
+    for (&def_id, &api_name) in &callbacks {
+        let ldid = match def_id.as_local() {
+            Some(x) => x,
+            None => continue,
+        };
+        if gacx.fn_analysis_invalid(def_id) {
+            continue;
+        }
+
+        let ldid_const = WithOptConstParam::unknown(ldid);
+        let info = func_info.get_mut(&ldid).unwrap();
+        let mir = tcx.mir_built(ldid_const);
+        let mir = mir.borrow();
+
+        let acx = gacx.function_context_with_data(&mir, info.acx_data.take());
+
+        // Locals `0..=arg_count` are the return place and the arguments, i.e. the signature.
+        for local in mir.local_decls.indices().take(mir.arg_count + 1) {
+            let lty = acx.type_of(local);
+            for lty in lty.iter() {
+                let ptr = lty.label;
+                if ptr.is_none() {
+                    continue;
+                }
+                warn!(
+                    "{:?} is registered as a callback with `{}`; pinning its signature to its \
+                     original raw representation",
+                    def_id, api_name
+                );
+                asn.flags[ptr].insert(FlagSet::FIXED);
+            }
+        }
+
+        info.acx_data.set(acx.into_data());
+    }
+}
+
+/// If `ldid`'s return type has a top-level pointer that will be rewritten to an owning `Box<T>`
+/// (i.e. it has [`PermissionSet::FREE`] and isn't [`FlagSet::FIXED`]), return the span at which a
+/// `#[must_use]` attribute should be inserted.
+///
+/// Before the rewrite, discarding the return value of a function returning a raw pointer was
+/// ordinarily harmless -- the caller never owned anything to begin with. After the rewrite, the
+/// same call returns a `Box<T>`, so discarding it silently frees the allocation; that's exactly
+/// the mistake `#[must_use]` exists to catch, so we add it wherever a rewrite introduces that new
+/// failure mode (gated behind `C2RUST_ANALYZE_ANNOTATE_MUST_USE`, since, like the `dual_impl`
+/// rewrite, it changes the function item's attributes rather than just its signature/body, and
+/// some projects may prefer to add such annotations by hand instead).
+fn must_use_attr_span(acx: &AnalysisCtxt, asn: &Assignment, ldid: LocalDefId) -> Option<Span> {
+    let lty_sig = acx.gacx.fn_sigs.get(&ldid.to_def_id())?;
+    let ptr = lty_sig.output.label;
+    if ptr.is_none() {
+        return None;
+    }
+    if !asn.perms()[ptr].contains(PermissionSet::FREE) {
+        return None;
+    }
+    if asn.flags()[ptr].contains(FlagSet::FIXED) {
+        return None;
+    }
+    let hir_id = acx.tcx().hir().local_def_id_to_hir_id(ldid);
+    Some(acx.tcx().hir().span(hir_id).shrink_to_lo())
+}
+
+/// Scan every function for calls to `core::mem::transmute`, and pin both the argument and the
+/// destination to `FIXED`.
+///
+/// A `transmute` is explicitly allowed to reinterpret its argument as an unrelated type, so unlike
+/// an ordinary assignment there's no reason to expect the two sides to agree on pointee type or
+/// representation -- `dataflow::type_check`'s `Callee::Transmute` arm deliberately leaves their
+/// `PointerId`s unconstrained rather than unifying them. But the rewriter has no rule for a
+/// `transmute` call either (it's just left alone, raw pointers in and out), so if the solver were
+/// free to rewrite one side to, say, a safe reference while leaving the other raw, the call would
+/// silently start reinterpreting bytes that don't mean what the new type claims. Pinning both
+/// sides here keeps them as plain raw pointers, matching what the unrewritten call site actually
+/// does, and the `warn!` gives a human a span to go audit for real transmute-level soundness.
+fn mark_transmute_ptrs_fixed<'tcx>(
+    gacx: &mut GlobalAnalysisCtxt<'tcx>,
+    asn: &mut Assignment,
+    func_info: &mut HashMap<LocalDefId, FuncInfo<'tcx>>,
+    all_fn_ldids: &[LocalDefId],
+) {
+    let tcx = gacx.tcx;
+    for &ldid in all_fn_ldids {
+        if gacx.fn_analysis_invalid(ldid.to_def_id()) {
+            continue;
+        }
+
+        let ldid_const = WithOptConstParam::unknown(ldid);
+        let info = func_info.get_mut(&ldid).unwrap();
+        let mir = tcx.mir_built(ldid_const);
+        let mir = mir.borrow();
+
+        let acx = gacx.function_context_with_data(&mir, info.acx_data.take());
+
+        for bb_data in mir.basic_blocks().iter() {
+            let term = match &bb_data.terminator {
+                Some(term) => term,
+                None => continue,
+            };
+            let (func, args, destination) = match &term.kind {
+                TerminatorKind::Call {
+                    func,
+                    args,
+                    destination,
+                    ..
+                } => (func, args, destination),
+                _ => continue,
+            };
+            let func_ty = func.ty(&mir.local_decls, tcx);
+            let (from_ty, to_ty) = match util::ty_callee(tcx, func_ty) {
+                util::Callee::Transmute { from_ty, to_ty } => (from_ty, to_ty),
+                _ => continue,
+            };
+            warn!(
+                "pinning both sides of `transmute::<{:?}, {:?}>` at {:?} to FIXED",
+                from_ty, to_ty, term.source_info.span
+            );
+            make_ty_fixed(asn, acx.type_of(&args[0]));
+            make_ty_fixed(asn, acx.type_of(*destination));
+        }
+
+        info.acx_data.set(acx.into_data());
+    }
+}
+
+/// Scan every function for calls to `core::ptr::read_volatile`/`write_volatile`, and pin the
+/// accessed pointer to [`FlagSet::VOLATILE`] and [`FlagSet::FIXED`].
+///
+/// A volatile access exists specifically to defeat the optimizer's (and, here, our own) usual
+/// assumption that a load/store can be reordered or elided based on what else touches the same
+/// memory -- C code uses it for device/memory-mapped registers, where a plain load or store would
+/// be an observable correctness bug, not just a missed optimization. There's no rewrite rule that
+/// understands that distinction (the existing `Cell`/reference conversions all assume ordinary
+/// memory), so the safest thing to do is pin the pointer raw, leaving the `read_volatile`/
+/// `write_volatile` call exactly as transpiled. Teaching the rewriter to instead wrap such a
+/// pointer in a `VolatileCell`-style accessor -- letting it still become a safe reference, while
+/// keeping volatile semantics -- would need a new [`Ownership`] variant and is future
+/// work; [`FlagSet::VOLATILE`] is recorded now so that work has something to key off of later.
+fn mark_volatile_ptrs_fixed<'tcx>(
+    gacx: &mut GlobalAnalysisCtxt<'tcx>,
+    asn: &mut Assignment,
+    func_info: &mut HashMap<LocalDefId, FuncInfo<'tcx>>,
+    all_fn_ldids: &[LocalDefId],
+) {
+    let tcx = gacx.tcx;
+    for &ldid in all_fn_ldids {
+        if gacx.fn_analysis_invalid(ldid.to_def_id()) {
+            continue;
+        }
+
+        let ldid_const = WithOptConstParam::unknown(ldid);
+        let info = func_info.get_mut(&ldid).unwrap();
+        let mir = tcx.mir_built(ldid_const);
+        let mir = mir.borrow();
+
+        let acx = gacx.function_context_with_data(&mir, info.acx_data.take());
+
+        for bb_data in mir.basic_blocks().iter() {
+            let term = match &bb_data.terminator {
+                Some(term) => term,
+                None => continue,
+            };
+            let (func, args) = match &term.kind {
+                TerminatorKind::Call { func, args, .. } => (func, args),
+                _ => continue,
+            };
+            let func_ty = func.ty(&mir.local_decls, tcx);
+            let op = match util::ty_callee(tcx, func_ty) {
+                util::Callee::Volatile { op, .. } => op,
+                _ => continue,
+            };
+            let ptr_lty = acx.type_of(&args[0]);
+            let ptr = ptr_lty.label;
+            if ptr.is_none() {
+                continue;
+            }
+            warn!(
+                "pinning volatile pointer accessed by `{:?}` at {:?} to FIXED",
+                op, term.source_info.span
+            );
+            asn.flags[ptr].insert(FlagSet::VOLATILE | FlagSet::FIXED);
+        }
+
+        info.acx_data.set(acx.into_data());
+    }
+}
+
+/// Scan every function for accesses to more than one field of the same local `union`, where at
+/// least one of the accessed fields is a pointer, and pin every such field to `FIXED`.
+///
+/// C's classic type-punning idiom -- write through one union member, read back through another --
+/// gets transpiled into ordinary field-projection places on a Rust `union`, but nothing in this
+/// crate's dataflow understands unions: each field access is just a place like any other, so the
+/// two fields' `PointerId`s (if both are pointers) are never connected, and the solver could pick
+/// unrelated representations for each one even though they alias the same bytes. Detecting and
+/// pinning here is a conservative stand-in for real union support; it doesn't attempt to relate
+/// the fields to each other, just keeps every field that's part of a pun raw so the existing
+/// per-field rewrite rules can't disagree with each other about what's stored there.
+fn mark_union_ptrs_fixed<'tcx>(
+    gacx: &mut GlobalAnalysisCtxt<'tcx>,
+    asn: &mut Assignment,
+    func_info: &mut HashMap<LocalDefId, FuncInfo<'tcx>>,
+    all_fn_ldids: &[LocalDefId],
+) {
+    let tcx = gacx.tcx;
+    for &ldid in all_fn_ldids {
+        if gacx.fn_analysis_invalid(ldid.to_def_id()) {
+            continue;
+        }
+
+        let ldid_const = WithOptConstParam::unknown(ldid);
+        let info = func_info.get_mut(&ldid).unwrap();
+        let mir = tcx.mir_built(ldid_const);
+        let mir = mir.borrow();
+
+        let acx = gacx.function_context_with_data(&mir, info.acx_data.take());
+
+        // Local -> (field index -> (first access site, that field's `LTy`)).
+        let mut union_fields: HashMap<Local, HashMap<u32, (Location, LTy<'tcx>)>> = HashMap::new();
+        let record = |pl: Place<'tcx>, loc: Location, out: &mut HashMap<_, _>| {
+            let mut lty = acx.type_of(pl.local);
+            for proj in pl.projection {
+                let next_lty = acx.projection_lty(lty, &proj);
+                if let PlaceElem::Field(field, _) = proj {
+                    if matches!(lty.ty.kind(), TyKind::Adt(adt_def, _) if adt_def.is_union()) {
+                        out.entry(pl.local)
+                            .or_insert_with(HashMap::new)
+                            .entry(field.index() as u32)
+                            .or_insert((loc, next_lty));
+                    }
+                }
+                lty = next_lty;
+            }
+        };
+        for (bb, bb_data) in mir.basic_blocks().iter_enumerated() {
+            for (i, stmt) in bb_data.statements.iter().enumerate() {
+                let (pl, rv) = match &stmt.kind {
+                    StatementKind::Assign(x) => (&x.0, &x.1),
+                    _ => continue,
+                };
+                let loc = Location {
+                    block: bb,
+                    statement_index: i,
+                };
+                record(*pl, loc, &mut union_fields);
+                if let Rvalue::Use(Operand::Copy(rv_pl) | Operand::Move(rv_pl)) = rv {
+                    record(*rv_pl, loc, &mut union_fields);
+                }
+            }
+        }
+
+        for (local, fields) in union_fields {
+            if fields.len() < 2 {
+                continue;
+            }
+            if !fields.values().any(|(_, lty)| lty.label.is_some()) {
+                continue;
+            }
+            let sites: Vec<Location> = fields.values().map(|&(loc, _)| loc).collect();
+            warn!(
+                "pinning punned union local {:?} to FIXED on every accessed field (access sites: \
+                 {:?})",
+                local, sites
+            );
+            for (_, lty) in fields.into_values() {
+                make_ty_fixed(asn, lty);
+            }
+        }
+
+        info.acx_data.set(acx.into_data());
+    }
+}
+
+/// For testing, putting #[c2rust_analyze_test::fail_before_analysis] on a function marks it as
+/// failed at this point.
+fn apply_test_attr_fail_before_analysis(
+    gacx: &mut GlobalAnalysisCtxt,
+    all_fn_ldids: &[LocalDefId],
+) {
+    let tcx = gacx.tcx;
+    for &ldid in all_fn_ldids {
+        if !util::has_test_attr(tcx, ldid, TestAttr::FailBeforeAnalysis) {
+            continue;
+        }
+        gacx.mark_fn_failed(
+            ldid.to_def_id(),
+            DontRewriteFnReason::FAKE_INVALID_FOR_TESTING,
+            PanicDetail::new("explicit fail_before_analysis for testing".to_owned()),
+        );
+    }
+}
+
+/// For testing, putting #[c2rust_analyze_test::force_non_null_args] on a function marks its
+/// arguments as `NON_NULL` and also adds `NON_NULL` to the `updates_forbidden` mask.
+fn apply_test_attr_force_non_null_args(
+    gacx: &mut GlobalAnalysisCtxt,
+    all_fn_ldids: &[LocalDefId],
+    asn: &mut Assignment,
+    updates_forbidden: &mut GlobalPointerTable<PermissionSet>,
 ) {
     let tcx = gacx.tcx;
     for &ldid in all_fn_ldids {
@@ -2439,6 +3760,52 @@ fn local_span(decl: &LocalDecl) -> Span {
     span
 }
 
+/// Append a per-pointer decision-log section to `report`, enabled by
+/// `C2RUST_ANALYZE_POINTER_DECISION_LOG=1`: for every pointer appearing in one of `mir`'s locals,
+/// record its initial permissions/flags (the fixed point's starting hypothesis, the same for
+/// every pointer in the crate), its final permissions/flags, and its final [`TypeDesc`], to help
+/// an auditor spot-check why a specific pointer ended up with the representation it did.
+///
+/// This doesn't (yet) record *which* constraint added or removed each bit, nor which rewrite(s)
+/// in the rest of the report came from this pointer: [`DataflowConstraints`] doesn't track spans
+/// for its constraints, and rewrites are recorded by `Span` rather than `PointerId`, so neither
+/// kind of provenance is available without deeper changes to those two passes. This logs the
+/// fixed point's input and output for each pointer, which is already useful on its own.
+///
+/// [`TypeDesc`]: crate::type_desc::TypeDesc
+/// [`DataflowConstraints`]: crate::dataflow::DataflowConstraints
+fn write_pointer_decision_log(
+    report: &mut String,
+    acx: &AnalysisCtxt,
+    asn: &Assignment,
+    mir: &Body,
+    initial_perms: PermissionSet,
+    initial_flags: FlagSet,
+) {
+    writeln!(report, "  pointer decision log:").unwrap();
+    for local in mir.local_decls.indices() {
+        let lty = acx.type_of(local);
+        for lty in lty.iter() {
+            let ptr = lty.label;
+            if ptr.is_none() {
+                continue;
+            }
+            let final_perms = asn.perms[ptr];
+            let final_flags = asn.flags[ptr];
+            // `perms_to_desc` asserts on some invalid bit combinations, which a `FIXED` pointer
+            // (kept in its original raw representation) isn't guaranteed to satisfy.
+            let desc = (!final_flags.contains(FlagSet::FIXED))
+                .then(|| type_desc::perms_to_desc(lty.ty, final_perms, final_flags));
+            writeln!(
+                report,
+                "    {:?} (local {:?}): initial {:?}/{:?} -> final {:?}/{:?}, desc = {:?}",
+                ptr, local, initial_perms, initial_flags, final_perms, final_flags, desc
+            )
+            .unwrap();
+        }
+    }
+}
+
 fn describe_local(tcx: TyCtxt, decl: &LocalDecl) -> String {
     let span = local_span(decl);
     describe_span(tcx, span)
@@ -2622,7 +3989,7 @@ pub(super) fn fn_body_owners_postorder(tcx: TyCtxt) -> Vec<LocalDefId> {
     order
 }
 
-fn for_each_callee(tcx: TyCtxt, ldid: LocalDefId, f: impl FnMut(LocalDefId)) {
+pub(super) fn for_each_callee(tcx: TyCtxt, ldid: LocalDefId, f: impl FnMut(LocalDefId)) {
     let ldid_const = WithOptConstParam::unknown(ldid);
     let mir = tcx.mir_built(ldid_const);
     let mir = mir.borrow();
@@ -0,0 +1,116 @@
+//! Opt-in detection of "array of pointers used as a lookup table" patterns, e.g. a local
+//! `T *table[N]`-shaped array built from several distinct pointer values and later indexed with a
+//! value computed at runtime (`table[i]`, not a constant `table[3]`).
+//!
+//! The dataflow pass in [`crate::dataflow::type_check`] already *unifies* every element of an
+//! array into one shared element `PointerId` (see its handling of `AggregateKind::Array`, and of
+//! indexing through [`crate::context::AnalysisCtxt::projection_lty`]), so loads and stores through
+//! `table[i]` are soundly accounted for -- the summarized permission set is always a superset of
+//! what any individual slot needs. What that summarization can't do is give two different slots
+//! of the same array two different rewritten representations: if even one literal entry in the
+//! table needs a non-`Copy` representation like `Box<T>`, every other slot is forced to use that
+//! same representation too, even if most entries are `NULL` sentinels that would otherwise become
+//! a plain `Option<&T>`. Teaching the rewrite pipeline to track a per-index representation (or to
+//! reject just the unsound slots) is future work; for now this module only detects and logs
+//! candidate tables for a human to look at, the same way [`crate::null_guard`] and
+//! [`crate::tokenize_loop`] do for their own patterns.
+use log::debug;
+use rustc_middle::mir::visit::{PlaceContext, Visitor};
+use rustc_middle::mir::{
+    AggregateKind, Body, Local, Location, Place, PlaceElem, Rvalue, StatementKind,
+};
+use rustc_middle::ty::{TyCtxt, TyKind};
+use std::collections::HashSet;
+
+/// A local array-of-pointers built from more than one operand at `build_loc`, and later
+/// indexed with a non-constant index at each of `index_locs`.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct ArrayOfPtrsCandidate {
+    pub array_local: Local,
+    pub build_loc: Location,
+    pub index_locs: Vec<Location>,
+}
+
+/// Collects every `Location` at which `array_local` is accessed through a (non-constant) index
+/// projection.
+struct IndexFinder {
+    array_local: Local,
+    index_locs: Vec<Location>,
+}
+
+impl<'tcx> Visitor<'tcx> for IndexFinder {
+    fn visit_place(&mut self, place: &Place<'tcx>, _context: PlaceContext, location: Location) {
+        if place.local != self.array_local {
+            return;
+        }
+        let is_runtime_index = place
+            .projection
+            .iter()
+            .any(|elem| matches!(elem, PlaceElem::Index(_)));
+        if is_runtime_index {
+            self.index_locs.push(location);
+        }
+    }
+}
+
+/// Find array-of-pointers lookup tables in `mir`. Run only when
+/// `C2RUST_ANALYZE_DETECT_ARRAY_OF_PTRS=1` is set, since (like the other opt-in detectors in this
+/// crate) this is a heuristic, not a conclusion backed by the dataflow analysis.
+pub fn find_array_of_ptrs_candidates<'tcx>(
+    tcx: TyCtxt<'tcx>,
+    mir: &Body<'tcx>,
+) -> HashSet<ArrayOfPtrsCandidate> {
+    let mut out = HashSet::new();
+    for (bb, bb_data) in mir.basic_blocks.iter_enumerated() {
+        for (i, stmt) in bb_data.statements.iter().enumerate() {
+            let (pl, rv) = match &stmt.kind {
+                StatementKind::Assign(x) => (&x.0, &x.1),
+                _ => continue,
+            };
+            let ops = match rv {
+                Rvalue::Aggregate(kind, ops) if matches!(**kind, AggregateKind::Array(_)) => ops,
+                _ => continue,
+            };
+            // A single-element/`Repeat`-shaped build only ever has one representation to agree
+            // with itself, so there's nothing for this pass to flag.
+            if ops.len() < 2 {
+                continue;
+            }
+            let array_local = match pl.as_local() {
+                Some(local) => local,
+                None => continue,
+            };
+            let elem_ty = match rv.ty(&mir.local_decls, tcx).kind() {
+                TyKind::Array(elem_ty, _) => *elem_ty,
+                _ => continue,
+            };
+            if !elem_ty.is_unsafe_ptr() {
+                continue;
+            }
+
+            let mut finder = IndexFinder {
+                array_local,
+                index_locs: Vec::new(),
+            };
+            finder.visit_body(mir);
+            if finder.index_locs.is_empty() {
+                continue;
+            }
+
+            let build_loc = Location {
+                block: bb,
+                statement_index: i,
+            };
+            debug!(
+                "found array-of-pointers table {:?} built at {:?}, indexed at {:?}",
+                array_local, build_loc, finder.index_locs
+            );
+            out.insert(ArrayOfPtrsCandidate {
+                array_local,
+                build_loc,
+                index_locs: finder.index_locs,
+            });
+        }
+    }
+    out
+}
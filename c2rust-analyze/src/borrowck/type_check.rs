@@ -565,10 +565,7 @@ impl<'tcx> TypeChecker<'tcx, '_> {
                     Callee::SliceAsPtr { .. } => {
                         // TODO: handle this like a cast
                     }
-                    Callee::Malloc => {
-                        // TODO
-                    }
-                    Callee::Calloc => {
+                    Callee::Malloc | Callee::Calloc | Callee::CustomMalloc { .. } => {
                         // TODO
                     }
                     Callee::Realloc => {
@@ -580,13 +577,13 @@ impl<'tcx> TypeChecker<'tcx, '_> {
 
                         self.do_assign(pl_lty, rv_lty);
                     }
-                    Callee::Free => {
+                    Callee::Free | Callee::CustomFree => {
                         let _pl_lty = self.visit_place(destination);
                         let _rv_lty = assert_matches!(&args[..], [p] => {
                             self.visit_operand(p)
                         });
                     }
-                    Callee::Memcpy => {
+                    Callee::Memcpy | Callee::Memmove => {
                         let _pl_lty = self.visit_place(destination);
                         assert_matches!(&args[..], [dest, src, _] => {
                             self.visit_operand(dest);
@@ -0,0 +1,86 @@
+//! Call graph construction and rewrite "blast radius" reporting, enabled via
+//! `C2RUST_ANALYZE_IMPACT_ANALYSIS_LIST`.
+//!
+//! [`analyze::for_each_callee`] already walks a function's body looking for direct calls to
+//! other local functions (it's how [`analyze::fn_body_owners_postorder`] orders the main
+//! analysis so callees are visited before their callers). This module reuses it to build the
+//! reverse edge -- for each function, the set of functions that call it -- and then reports, for
+//! a chosen set of "roots" (functions whose signature a caller is considering rewriting), the
+//! full transitive set of functions that would need to be re-checked as a result.
+//!
+//! Like [`analyze::for_each_callee`] itself, this only follows calls where the callee can be
+//! named directly at the call site (`Callee::LocalDef`). A call through a function-pointer-typed
+//! local or field isn't resolved to the set of functions whose address might flow there, since
+//! nothing in this crate currently tracks which concrete functions a function-pointer value may
+//! hold (the closest existing mechanism, [`pointee_type`](crate::pointee_type), tracks pointee
+//! *types* for data pointers, not possible callees for function pointers). Such call sites are
+//! simply not added as edges, so the reported impact set is a lower bound on the true blast
+//! radius whenever function pointers are involved.
+use crate::analyze::for_each_callee;
+use log::debug;
+use rustc_hir::def_id::LocalDefId;
+use rustc_middle::ty::TyCtxt;
+use std::collections::{HashMap, HashSet, VecDeque};
+
+/// The reverse call graph: for each function, the set of functions with a direct call to it.
+pub struct CallGraph {
+    callers: HashMap<LocalDefId, HashSet<LocalDefId>>,
+}
+
+impl CallGraph {
+    /// Build the reverse call graph over `all_fn_ldids` by scanning each function's body for
+    /// direct calls to other functions in the set.
+    pub fn build(tcx: TyCtxt, all_fn_ldids: &[LocalDefId]) -> CallGraph {
+        let mut callers: HashMap<LocalDefId, HashSet<LocalDefId>> = HashMap::new();
+        for &caller in all_fn_ldids {
+            for_each_callee(tcx, caller, |callee| {
+                callers.entry(callee).or_default().insert(caller);
+            });
+        }
+        CallGraph { callers }
+    }
+
+    /// Compute the transitive impact set of rewriting `root`'s signature: every function that
+    /// calls `root`, plus every function that calls one of those, and so on. `root` itself is
+    /// not included.
+    pub fn impact_set(&self, root: LocalDefId) -> HashSet<LocalDefId> {
+        let mut seen = HashSet::new();
+        let mut queue = VecDeque::new();
+        queue.push_back(root);
+        while let Some(ldid) = queue.pop_front() {
+            for &caller in self.callers.get(&ldid).into_iter().flatten() {
+                if seen.insert(caller) {
+                    queue.push_back(caller);
+                }
+            }
+        }
+        // A recursive `root` is its own (transitive) caller; keep it out of its own impact set.
+        seen.remove(&root);
+        seen
+    }
+
+    /// Log the impact set of each of `roots`, sorted from largest blast radius to smallest.
+    pub fn report_impact(&self, tcx: TyCtxt, roots: &[LocalDefId]) {
+        let mut reports: Vec<(LocalDefId, HashSet<LocalDefId>)> = roots
+            .iter()
+            .map(|&root| (root, self.impact_set(root)))
+            .collect();
+        reports.sort_by_key(|(_, impact)| std::cmp::Reverse(impact.len()));
+
+        for (root, impact) in &reports {
+            debug!(
+                "impact analysis: rewriting {:?} affects {} function(s)",
+                tcx.item_name(root.to_def_id()),
+                impact.len(),
+            );
+            let mut affected = impact
+                .iter()
+                .map(|&ldid| tcx.item_name(ldid.to_def_id()))
+                .collect::<Vec<_>>();
+            affected.sort();
+            for name in affected {
+                debug!("  - {:?}", name);
+            }
+        }
+    }
+}
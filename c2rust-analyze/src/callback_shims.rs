@@ -0,0 +1,99 @@
+//! Detection of local functions registered as callbacks with known libc APIs (`qsort`, `bsearch`,
+//! `pthread_create`, `atexit`) that must keep their exact `extern "C"` signature.
+//!
+//! A callback like `qsort`'s `compar` isn't called from anywhere in the rewritten crate's own
+//! source -- it's handed to `qsort` as a bare function pointer and called back through the C ABI
+//! -- so its signature can't be changed the way an ordinary function's can: there's no call site
+//! here for [`rewrite::expr`](crate::rewrite::expr) to update, and the libc side has no idea a
+//! `Box<T>` or `&mut T` even exists. [`find_libc_callbacks`] finds such functions so
+//! [`crate::analyze::mark_callback_ptrs_fixed`] can pin their parameter/return pointers to
+//! [`crate::context::FlagSet::FIXED`], the same way [`crate::analyze::mark_foreign_fixed`] does
+//! for the parameters of `extern` declarations -- this keeps the function's raw ABI shim intact.
+//!
+//! Actually rewriting the *body* of such a callback to convert its raw parameters to safe values
+//! at entry (mirroring [`crate::rewrite::shim::gen_shim_definition_rewrite`], but inline in the
+//! same function instead of a separate wrapper, since the signature can't move) is future work;
+//! for now the body is left untouched, same as any other `FIXED`-pinned function.
+use rustc_hir::def_id::{DefId, LocalDefId};
+use rustc_middle::mir::{Operand, TerminatorKind};
+use rustc_middle::ty::{TyCtxt, TyKind, WithOptConstParam};
+use std::collections::HashMap;
+
+/// A libc API that takes a callback function pointer, and the argument position of that
+/// callback, named for the `debug!`/`warn!` output in
+/// [`crate::analyze::mark_callback_ptrs_fixed`].
+struct CallbackApi {
+    name: &'static str,
+    callback_arg: usize,
+}
+
+const CALLBACK_APIS: &[CallbackApi] = &[
+    // `qsort(base, nmemb, size, compar)`
+    CallbackApi {
+        name: "qsort",
+        callback_arg: 3,
+    },
+    // `bsearch(key, base, nmemb, size, compar)`
+    CallbackApi {
+        name: "bsearch",
+        callback_arg: 4,
+    },
+    // `pthread_create(thread, attr, start_routine, arg)`
+    CallbackApi {
+        name: "pthread_create",
+        callback_arg: 2,
+    },
+    // `atexit(function)`
+    CallbackApi {
+        name: "atexit",
+        callback_arg: 0,
+    },
+];
+
+/// Find every local function passed as a callback argument to one of [`CALLBACK_APIS`], mapped
+/// to the name of the API it was registered with.
+pub fn find_libc_callbacks(
+    tcx: TyCtxt,
+    all_fn_ldids: &[LocalDefId],
+) -> HashMap<DefId, &'static str> {
+    let mut callbacks = HashMap::new();
+    for &ldid in all_fn_ldids {
+        let ldid_const = WithOptConstParam::unknown(ldid);
+        let mir = tcx.mir_built(ldid_const);
+        let mir = mir.borrow();
+        for bb_data in mir.basic_blocks().iter() {
+            let term = match &bb_data.terminator {
+                Some(term) => term,
+                None => continue,
+            };
+            let (func, args) = match &term.kind {
+                TerminatorKind::Call { func, args, .. } => (func, args),
+                _ => continue,
+            };
+            let callee_did = match func.ty(&mir, tcx).kind() {
+                TyKind::FnDef(did, _) => *did,
+                _ => continue,
+            };
+            let name = tcx.item_name(callee_did);
+            let api = match CALLBACK_APIS.iter().find(|api| name.as_str() == api.name) {
+                Some(x) => x,
+                None => continue,
+            };
+            let arg = match args.get(api.callback_arg) {
+                Some(x) => x,
+                None => continue,
+            };
+            let arg_did = match arg {
+                Operand::Constant(c) => match c.ty().kind() {
+                    TyKind::FnDef(did, _) => *did,
+                    _ => continue,
+                },
+                _ => continue,
+            };
+            if arg_did.is_local() {
+                callbacks.insert(arg_did, api.name);
+            }
+        }
+    }
+    callbacks
+}
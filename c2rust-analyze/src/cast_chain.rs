@@ -0,0 +1,97 @@
+//! Opt-in detection of long chains of `as` casts, as commonly produced by the C-to-Rust
+//! transpiler (e.g. `p as *mut libc::c_void as *mut u8 as usize`).
+//!
+//! MIR building splits a nested cast expression like `x as A as B` into one assignment statement
+//! per `as`, each casting the previous statement's temporary. [`crate::rewrite::expr::mir_op`]'s
+//! `Rvalue::Cast` handling decides, independently for each of those statements, whether that one
+//! hop's cast becomes removable (via `RewriteKind::RemoveCast`) by comparing the hop's input and
+//! output [`TypeDesc`](crate::type_desc::TypeDesc)s; it relies entirely on
+//! [`crate::pointee_type`]'s constraint-based pointee-type inference to see a consistent pointee
+//! through an opaque hop like `*mut c_void`. When that inference doesn't unify the whole chain
+//! (for instance, because one endpoint's pointee is only discovered through a later use this
+//! module doesn't also trace), some hops are left un-rewritten, and the intermediate cast blocks
+//! the rewritten pointer from propagating further -- the dangling-`as`-cast noise the originating
+//! request describes.
+//!
+//! Extending the `SubLoc`/unlowering scheme to see each hop's place in the full chain, and to
+//! decide chain-wide (rather than hop-by-hop) whether the whole thing collapses to one cast from
+//! the original rewritten type to the final one, is future work. This module only detects and
+//! logs the chains themselves, so a human can check whether the rewriter fully collapsed each one.
+use log::debug;
+use rustc_middle::mir::{Body, CastKind, Local, Rvalue, StatementKind};
+use rustc_middle::ty::{Ty, TyCtxt};
+
+/// A chain of 2 or more consecutive `as` casts, each one recast from the previous: `locals[0] as
+/// tys[1]` assigned into `locals[1]`, then `locals[1] as tys[2]` assigned into `locals[2]`, etc.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CastChain<'tcx> {
+    pub locals: Vec<Local>,
+    pub tys: Vec<Ty<'tcx>>,
+}
+
+/// Find chains of `as` casts in `mir`. Run only when `C2RUST_ANALYZE_DETECT_CAST_CHAINS=1` is
+/// set, since (like the other opt-in detectors in this crate) this only reports a heuristically
+/// recognized MIR shape, not a conclusion backed by the dataflow analysis.
+pub fn find_cast_chains<'tcx>(tcx: TyCtxt<'tcx>, mir: &Body<'tcx>) -> Vec<CastChain<'tcx>> {
+    let mut out = Vec::new();
+    for bb_data in mir.basic_blocks.iter() {
+        let mut chain_locals = Vec::new();
+        let mut chain_tys = Vec::new();
+        let mut flush = |locals: &mut Vec<Local>, tys: &mut Vec<Ty<'tcx>>| {
+            // `locals` holds the source plus one destination per cast, so 2 casts means 3 locals.
+            if locals.len() >= 3 {
+                debug!("found cast chain: {:?}", tys);
+                out.push(CastChain {
+                    locals: std::mem::take(locals),
+                    tys: std::mem::take(tys),
+                });
+            } else {
+                locals.clear();
+                tys.clear();
+            }
+        };
+
+        for stmt in &bb_data.statements {
+            let (pl, rv) = match &stmt.kind {
+                StatementKind::Assign(x) => (&x.0, &x.1),
+                _ => {
+                    flush(&mut chain_locals, &mut chain_tys);
+                    continue;
+                }
+            };
+            let (op, to_ty) = match *rv {
+                Rvalue::Cast(CastKind::Misc, ref op, ty) if ty.is_unsafe_ptr() => (op, ty),
+                _ => {
+                    flush(&mut chain_locals, &mut chain_tys);
+                    continue;
+                }
+            };
+            let op_local = op.place().and_then(|pl| pl.as_local());
+            let continues_chain = match (op_local, chain_locals.last()) {
+                (Some(op_local), Some(&prev_local)) => op_local == prev_local,
+                _ => false,
+            };
+            if !continues_chain {
+                flush(&mut chain_locals, &mut chain_tys);
+                if let Some(op_local) = op_local {
+                    chain_locals.push(op_local);
+                    chain_tys.push(op.ty(&mir.local_decls, tcx));
+                }
+            }
+            let dest_local = match pl.as_local() {
+                Some(x) => x,
+                None => {
+                    flush(&mut chain_locals, &mut chain_tys);
+                    continue;
+                }
+            };
+            if chain_locals.is_empty() {
+                continue;
+            }
+            chain_locals.push(dest_local);
+            chain_tys.push(to_ty);
+        }
+        flush(&mut chain_locals, &mut chain_tys);
+    }
+    out
+}
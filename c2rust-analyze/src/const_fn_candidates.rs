@@ -0,0 +1,37 @@
+//! Opt-in detection of functions that look like they could become `const fn` once their pointer
+//! parameters are rewritten to safe references.  Proving that a function is truly const-evaluable
+//! is rustc's job (it has the full, ever-growing list of what's allowed in a `const fn` body);
+//! this module only flags the narrow, easy-to-verify-by-eye subset -- no raw pointers anywhere in
+//! the signature or locals (so the rewritten signature won't contain one either), and no calls or
+//! drops in the body, both of which are disallowed or heavily restricted in a `const fn` -- and,
+//! like [`crate::refcount`] and [`crate::tokenize_loop`], only logs candidates for a human to
+//! double check and annotate by hand, rather than emitting `const fn` rewrites itself.
+use crate::trivial::IsTrivial;
+use rustc_middle::mir::{Body, TerminatorKind};
+use rustc_middle::ty::TyCtxt;
+
+/// Returns `true` if every local in `mir` is [`IsTrivial`] (no raw pointers) and the body contains
+/// no function calls and no drops.
+pub fn is_const_fn_candidate<'tcx>(tcx: TyCtxt<'tcx>, mir: &Body<'tcx>) -> bool {
+    for local_decl in mir.local_decls.iter() {
+        if !local_decl.ty.is_trivial(tcx) {
+            return false;
+        }
+    }
+
+    for bb_data in mir.basic_blocks().iter() {
+        let term = match &bb_data.terminator {
+            Some(term) => term,
+            None => continue,
+        };
+        let is_call_or_drop = matches!(
+            term.kind,
+            TerminatorKind::Call { .. } | TerminatorKind::Drop { .. }
+        );
+        if is_call_or_drop {
+            return false;
+        }
+    }
+
+    true
+}
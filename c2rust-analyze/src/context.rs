@@ -5,6 +5,7 @@ use crate::known_fn::{all_known_fns, KnownFn};
 use crate::labeled_ty::{LabeledTy, LabeledTyCtxt};
 use crate::panic_detail::PanicDetail;
 use crate::pointer_id::{GlobalPointerTable, LocalPointerTable, PointerTable, PointerTableMut};
+use crate::type_desc::Ownership;
 use crate::util::{self, describe_rvalue, PhantomLifetime, RvalueDesc};
 use assert_matches::assert_matches;
 use bitflags::bitflags;
@@ -201,6 +202,28 @@ bitflags! {
         /// cross an FFI boundary, and for arguments and return values of functions we can't
         /// rewrite.
         const FIXED = 0x0002;
+
+        /// The pointee is used as a NUL-terminated byte string: it's passed to `strlen`-like
+        /// functions, or indexed using the result of such a call.  This is a candidate flag for
+        /// picking a string-flavored target type (`&CStr`, `&str`, ...) over a plain `&[T]` when
+        /// rewriting `*mut c_char`-style pointers; see [`rewrite::ty`](crate::rewrite::ty).
+        const NUL_TERMINATED = 0x0004;
+
+        /// The pointee is used in a way that additionally relies on its contents being valid
+        /// UTF-8 (for example, passed to a function that only accepts `&str`/`CStr::to_str`).
+        /// Currently nothing infers this flag -- C code gives us no reliable, generically
+        /// checkable signal that a buffer's contents are valid UTF-8, so this is defined for
+        /// forward compatibility with [`NUL_TERMINATED`](Self::NUL_TERMINATED) but left unset by
+        /// the analysis.
+        const UTF8 = 0x0008;
+
+        /// The pointee is accessed through `core::ptr::read_volatile`/`write_volatile` (see
+        /// `util::Callee::Volatile`). A volatile access can't be replaced with an ordinary load or
+        /// store -- doing so would be a correctness bug for the device/memory-mapped registers
+        /// transpiled C typically uses this for -- so `analyze::mark_volatile_ptrs_fixed` also sets
+        /// [`FIXED`](Self::FIXED) on every pointer with this flag, pinning it against the
+        /// `Cell`/reference conversions that would otherwise replace the intrinsic calls.
+        const VOLATILE = 0x0010;
     }
 }
 
@@ -226,6 +249,15 @@ bitflags! {
         /// Calling this function from non-rewritten code requires a shim, but shim generation
         /// failed.
         const SHIM_GENERATION_FAILED = 1 << 7;
+        /// The function contains a statement or terminator kind with no rewrite rule (such as
+        /// `SetDiscriminant`, `CopyNonOverlapping`, or inline asm).  This is recorded instead of
+        /// panicking so that the rest of the function's analysis can still run to completion.
+        const UNSUPPORTED_CONSTRUCT = 1 << 8;
+        /// The function calls `<*const T>::offset`/`<*mut T>::offset` with a statically-known
+        /// negative count.  Rewriting this soundly requires retaining a wider "base" slice that
+        /// extends before the current pointer position, which isn't tracked yet, so we leave the
+        /// call unrewritten rather than emit a slice index that wraps around.
+        const UNSUPPORTED_NEGATIVE_OFFSET = 1 << 9;
 
         /// Pointee analysis results for this function are invalid.
         const POINTEE_INVALID = 1 << 10;
@@ -426,9 +458,43 @@ pub struct GlobalAnalysisCtxt<'tcx> {
     /// acquire.
     pub force_rewrite: HashSet<DefId>,
 
-    /// `DefId`s of functions where analysis failed, and a [`PanicDetail`] explaining the reason
-    /// for each failure.
-    pub fns_failed: HashMap<DefId, PanicDetail>,
+    /// Crate-wide default for the bounds-check form used by `OffsetSlice` rewrites.  See
+    /// [`offset_bounds_mode`](Self::offset_bounds_mode).
+    pub offset_bounds_mode: util::OffsetBoundsMode,
+    /// Per-function overrides of [`offset_bounds_mode`](Self::offset_bounds_mode), for crates
+    /// that want most functions to use the default but a few hot paths or fallible paths to use
+    /// a different bounds-check form.
+    pub offset_bounds_overrides: HashMap<DefId, util::OffsetBoundsMode>,
+
+    /// Per-function [`RewriteStrategy`](util::RewriteStrategy) profile, for crates that want a
+    /// few hot paths to prefer performance (or a few risky paths to prefer a minimal diff) while
+    /// everything else uses the safety-first default.  Functions with no entry here use
+    /// [`RewriteStrategy::default`](util::RewriteStrategy::default).  See
+    /// [`rewrite_strategy`](Self::rewrite_strategy).
+    pub rewrite_strategy_overrides: HashMap<DefId, util::RewriteStrategy>,
+
+    /// When set, casts between raw pointers whose pointee types differ only by being byte-like
+    /// integer types (`i8`/`u8`, which is all `c_char` ever resolves to) are treated as
+    /// compatible instead of failing the cast, inserting an `as`-cast on the pointee (see the
+    /// `mir_op::CastBuilder::normalize_byte_pointees` cast-builder option). Off by default, since
+    /// silently reinterpreting bytes between signed and unsigned isn't always what a given crate
+    /// wants.
+    pub normalize_byte_pointee_types: bool,
+
+    /// Crate-wide set of [`Ownership`] variants that the rewriter is forbidden from introducing,
+    /// for projects that can't tolerate one of them (e.g. no `Rc` allowed, or no `Box` in a
+    /// `no_alloc` context).  See [`disabled_ownerships`](Self::disabled_ownerships).
+    pub disabled_ownerships: HashSet<Ownership>,
+    /// Per-function overrides of [`disabled_ownerships`](Self::disabled_ownerships); a function
+    /// listed here uses its own set in place of the crate-wide default, rather than adding to it.
+    pub disabled_ownership_overrides: HashMap<DefId, HashSet<Ownership>>,
+
+    /// `DefId`s of functions where analysis failed, and the [`PanicDetail`]s explaining the
+    /// reason for each failure.  A function can accumulate more than one entry here if it panics
+    /// in more than one `catch_unwind` scope (e.g. once during dataflow, then again during
+    /// rewriting), so that cascading failures don't hide the original root cause behind whichever
+    /// panic happened to be caught last.
+    pub fns_failed: HashMap<DefId, Vec<PanicDetail>>,
 
     pub field_ltys: HashMap<DefId, LTy<'tcx>>,
     pub field_users: MultiMap<LocalDefId, LocalDefId>,
@@ -811,6 +877,12 @@ impl<'tcx> GlobalAnalysisCtxt<'tcx> {
             dont_rewrite_statics: FlagMap::new(),
             dont_rewrite_fields: FlagMap::new(),
             force_rewrite: HashSet::new(),
+            offset_bounds_mode: util::OffsetBoundsMode::default(),
+            offset_bounds_overrides: HashMap::new(),
+            rewrite_strategy_overrides: HashMap::new(),
+            normalize_byte_pointee_types: false,
+            disabled_ownerships: HashSet::new(),
+            disabled_ownership_overrides: HashMap::new(),
             fns_failed: HashMap::new(),
             field_ltys: HashMap::new(),
             field_users: MultiMap::new(),
@@ -822,6 +894,41 @@ impl<'tcx> GlobalAnalysisCtxt<'tcx> {
         }
     }
 
+    /// Get the [`RewriteStrategy`](util::RewriteStrategy) profile for `def_id`, applying any
+    /// per-function override on top of the default
+    /// ([`RewriteStrategy::SafetyFirst`](util::RewriteStrategy::SafetyFirst)).
+    pub fn rewrite_strategy(&self, def_id: DefId) -> util::RewriteStrategy {
+        self.rewrite_strategy_overrides
+            .get(&def_id)
+            .copied()
+            .unwrap_or_default()
+    }
+
+    /// Get the [`OffsetBoundsMode`](util::OffsetBoundsMode) to use for `OffsetSlice` rewrites in
+    /// `def_id`.  An explicit per-function override on [`offset_bounds_overrides`] takes priority
+    /// over the mode implied by [`rewrite_strategy`](Self::rewrite_strategy), which in turn takes
+    /// priority over the crate-wide default.
+    pub fn offset_bounds_mode(&self, def_id: DefId) -> util::OffsetBoundsMode {
+        if let Some(&mode) = self.offset_bounds_overrides.get(&def_id) {
+            return mode;
+        }
+        if let Some(&strategy) = self.rewrite_strategy_overrides.get(&def_id) {
+            return strategy.default_offset_bounds_mode();
+        }
+        self.offset_bounds_mode
+    }
+
+    /// Get the set of [`Ownership`] variants that `def_id` is forbidden from being rewritten to.
+    /// An explicit per-function override in [`disabled_ownership_overrides`] replaces the
+    /// crate-wide [`disabled_ownerships`] default entirely, rather than adding to it -- the same
+    /// "override wins outright" relationship [`offset_bounds_mode`](Self::offset_bounds_mode) has
+    /// with [`offset_bounds_overrides`].
+    pub fn disabled_ownerships(&self, def_id: DefId) -> &HashSet<Ownership> {
+        self.disabled_ownership_overrides
+            .get(&def_id)
+            .unwrap_or(&self.disabled_ownerships)
+    }
+
     /// Initialize `self.adt_metadata` and `self.fn_origins`.  This requires that all field types
     /// in the crate have already been labeled in `field_ltys`.
     pub fn construct_region_metadata(&mut self) {
@@ -951,8 +1058,10 @@ impl<'tcx> GlobalAnalysisCtxt<'tcx> {
 
     pub fn mark_fn_failed(&mut self, did: DefId, reason: DontRewriteFnReason, detail: PanicDetail) {
         self.dont_rewrite_fns.add(did, reason);
-        // Insert `detail` if there isn't yet an entry for this `DefId`.
-        self.fns_failed.entry(did).or_insert(detail);
+        // Record every failure for this `DefId`, tagged with the phase it happened in, instead of
+        // keeping only the first (or last) one.
+        let detail = detail.with_phase(format!("{:?}", reason));
+        self.fns_failed.entry(did).or_default().push(detail);
     }
 
     /// Iterate over the `DefId`s of all functions that should skip rewriting.
@@ -194,6 +194,22 @@ impl DataflowConstraints {
     {
         let mut xs = TrackedPointerTable::new(xs);
 
+        // Index constraints by the pointers they mention, so each round only visits the
+        // constraints incident to pointers that actually changed in the previous round instead
+        // of rescanning every constraint.
+        let mut constraints_by_ptr: Vec<Vec<usize>> = vec![Vec::new(); xs.len()];
+        for (i, c) in self.constraints.iter().enumerate() {
+            match *c {
+                Constraint::Subset(a, b) | Constraint::SubsetExcept(a, b, _) => {
+                    constraints_by_ptr[a.index() as usize].push(i);
+                    constraints_by_ptr[b.index() as usize].push(i);
+                }
+                Constraint::AllPerms(ptr, _) | Constraint::NoPerms(ptr, _) => {
+                    constraints_by_ptr[ptr.index() as usize].push(i);
+                }
+            }
+        }
+
         let restrict_updates = |rules: &mut R, ptr, old: &T, new: T| {
             if let Some(updates_forbidden) = updates_forbidden {
                 rules.restrict_updates(old, &new, &updates_forbidden[ptr])
@@ -203,20 +219,30 @@ impl DataflowConstraints {
         };
 
         let mut changed = false;
+        // Initially, every pointer is dirty, so the whole constraint set is on the worklist.
+        let mut worklist: Vec<PointerId> = (0..xs.len() as u32).map(PointerId::global).collect();
+        let mut queued = vec![false; self.constraints.len()];
         let mut i = 0;
-        loop {
+        while !worklist.is_empty() {
             if i > xs.len() + self.constraints.len() {
                 return Err("infinite loop in dataflow edges".to_string());
             }
             i += 1;
 
-            for c in &self.constraints {
-                match *c {
-                    Constraint::Subset(a, b) => {
-                        if !xs.dirty(a) && !xs.dirty(b) {
-                            continue;
-                        }
+            let mut todo = Vec::new();
+            for ptr in worklist.drain(..) {
+                for &ci in &constraints_by_ptr[ptr.index() as usize] {
+                    if !queued[ci] {
+                        queued[ci] = true;
+                        todo.push(ci);
+                    }
+                }
+            }
 
+            for ci in todo {
+                queued[ci] = false;
+                match self.constraints[ci] {
+                    Constraint::Subset(a, b) => {
                         let old_a = xs.get(a);
                         let old_b = xs.get(b);
                         let (new_a, new_b) = rules.subset(a, old_a, b, old_b);
@@ -227,10 +253,6 @@ impl DataflowConstraints {
                     }
 
                     Constraint::SubsetExcept(a, b, except) => {
-                        if !xs.dirty(a) && !xs.dirty(b) {
-                            continue;
-                        }
-
                         let old_a = xs.get(a);
                         let old_b = xs.get(b);
                         let (new_a, new_b) = rules.subset_except(a, old_a, b, old_b, except);
@@ -241,10 +263,6 @@ impl DataflowConstraints {
                     }
 
                     Constraint::AllPerms(ptr, perms) => {
-                        if !xs.dirty(ptr) {
-                            continue;
-                        }
-
                         let old = xs.get(ptr);
                         let new = rules.all_perms(ptr, perms, old);
                         let new = restrict_updates(rules, ptr, old, new);
@@ -252,10 +270,6 @@ impl DataflowConstraints {
                     }
 
                     Constraint::NoPerms(ptr, perms) => {
-                        if !xs.dirty(ptr) {
-                            continue;
-                        }
-
                         let old = xs.get(ptr);
                         let new = rules.no_perms(ptr, perms, old);
                         let new = restrict_updates(rules, ptr, old, new);
@@ -264,11 +278,8 @@ impl DataflowConstraints {
                 }
             }
 
-            if !xs.any_new_dirty() {
-                break;
-            }
-            xs.swap_dirty();
-            changed = true;
+            worklist = xs.take_new_dirty();
+            changed = changed || !worklist.is_empty();
         }
 
         Ok(changed)
@@ -388,24 +399,24 @@ impl DataflowConstraints {
     }
 }
 
+/// A [`GlobalPointerTable`] wrapper that records which entries have been modified since the last
+/// call to [`take_new_dirty`][Self::take_new_dirty], so callers can drive a worklist instead of
+/// rescanning every entry on each iteration.
 struct TrackedPointerTable<'a, T> {
     xs: &'a mut GlobalPointerTable<T>,
-    dirty: GlobalPointerTable<bool>,
+    /// Whether each pointer is already in `new_dirty_list`, to avoid duplicate entries.
     new_dirty: GlobalPointerTable<bool>,
-    any_new_dirty: bool,
+    new_dirty_list: Vec<PointerId>,
 }
 
 impl<'a, T: PartialEq> TrackedPointerTable<'a, T> {
     pub fn new(xs: &'a mut GlobalPointerTable<T>) -> TrackedPointerTable<'a, T> {
-        let mut dirty = GlobalPointerTable::with_len_of(xs);
         let mut new_dirty = GlobalPointerTable::with_len_of(xs);
-        dirty.fill(true);
         new_dirty.fill(false);
         TrackedPointerTable {
             xs,
-            dirty,
             new_dirty,
-            any_new_dirty: false,
+            new_dirty_list: Vec::new(),
         }
     }
 
@@ -417,26 +428,22 @@ impl<'a, T: PartialEq> TrackedPointerTable<'a, T> {
         &self.xs[id]
     }
 
-    pub fn dirty(&self, id: PointerId) -> bool {
-        self.dirty[id]
-    }
-
-    pub fn any_new_dirty(&self) -> bool {
-        self.any_new_dirty
-    }
-
     pub fn set(&mut self, id: PointerId, x: T) {
         if x != self.xs[id] {
             self.xs[id] = x;
-            self.new_dirty[id] = true;
-            self.any_new_dirty = true;
+            if !self.new_dirty[id] {
+                self.new_dirty[id] = true;
+                self.new_dirty_list.push(id);
+            }
         }
     }
 
-    pub fn swap_dirty(&mut self) {
-        mem::swap(&mut self.dirty, &mut self.new_dirty);
-        self.new_dirty.fill(false);
-        self.any_new_dirty = false;
+    /// Take the list of pointers that were modified since the last call, clearing it.
+    pub fn take_new_dirty(&mut self) -> Vec<PointerId> {
+        for &id in &self.new_dirty_list {
+            self.new_dirty[id] = false;
+        }
+        mem::take(&mut self.new_dirty_list)
     }
 }
 
@@ -6,7 +6,7 @@ use crate::pointer_id::PointerTable;
 use crate::recent_writes::RecentWrites;
 use crate::util::{
     self, describe_rvalue, is_transmutable_ptr_cast, ty_callee, Callee, RvalueDesc,
-    UnknownDefCallee,
+    UnknownDefCallee, VolatileOp,
 };
 use assert_matches::assert_matches;
 use either::Either;
@@ -198,9 +198,25 @@ impl<'tcx> TypeChecker<'tcx, '_> {
                         self.do_assign_pointer_ids(to_lty.label, from_lty.label);
                         // TODO add other dataflow constraints
                     }
-                    Some(false) => {
+                    Some(false)
+                        if util::is_benign_void_ptr_cast(self.acx.tcx(), from_ty, to_ty) =>
+                    {
+                        // `void *` is the C idiom for an untyped pointer; once the concrete
+                        // pointee type is known on the other side, there's no real type-punning
+                        // here, so it's safe to unify the two pointers as usual.
                         self.do_assign_pointer_ids(to_lty.label, from_lty.label);
-                        ::log::warn!("TODO: unsupported ptr-to-ptr cast between pointee types not yet supported as safely transmutable: `{from_ty:?} as {to_ty:?}`");
+                    }
+                    Some(false) => {
+                        // This cast genuinely reinterprets the pointee as a different type.
+                        // Unifying `from` and `to` here would be unsound: it could make the
+                        // rewriter convert one side to a safe reference/`Box` typed as the other
+                        // side's pointee, silently changing what the pointer points to.  Instead,
+                        // leave the two pointers unconnected in the dataflow graph; a separate
+                        // pass (`analyze::mark_mismatched_cast_ptrs_fixed`) pins both sides to
+                        // `FIXED` so they keep their original raw pointer types and the cast is
+                        // left as an explicit, unsafe reinterpretation requiring a manual review
+                        // for `transmute`-like soundness.
+                        ::log::warn!("cast `{from_ty:?} as {to_ty:?}` reinterprets the pointee type; this pointer pair requires a manual transmute and won't be converted to a safe reference");
                     }
 
                     None => {} // not a ptr cast (no dataflow constraints needed); let rustc typeck this
@@ -497,6 +513,16 @@ impl<'tcx> TypeChecker<'tcx, '_> {
                 // As this is actually a known `fn`, we can treat it as a normal local call.
                 self.visit_local_call(def_id, substs, args, destination);
             }
+            Callee::UnknownDef(UnknownDefCallee::Direct {
+                ty: _,
+                def_id,
+                substs,
+                is_foreign: false,
+            }) if self.acx.gacx.fn_sigs.contains_key(&def_id) => {
+                // A cross-crate `#[inline(always)]` callee whose signature `assign_pointer_ids`
+                // was able to gather (see the comment there); treat it like a normal local call.
+                self.visit_local_call(def_id, substs, args, destination);
+            }
             Callee::UnknownDef(_) => {
                 error!("TODO: visit Callee::{callee:?}");
             }
@@ -536,7 +562,7 @@ impl<'tcx> TypeChecker<'tcx, '_> {
                 self.do_assign_pointer_ids(pl_lty.label, rv_lty.label);
             }
 
-            Callee::Malloc | Callee::Calloc => {
+            Callee::Malloc | Callee::Calloc | Callee::CustomMalloc { .. } => {
                 self.visit_place(destination, Mutability::Mut);
 
                 // The output of `malloc` is known not to be a stack pointer.
@@ -564,7 +590,7 @@ impl<'tcx> TypeChecker<'tcx, '_> {
                 // unify inner-most pointer types
                 self.do_equivalence_nested(pl_lty, rv_lty);
             }
-            Callee::Free => {
+            Callee::Free | Callee::CustomFree => {
                 let in_ptr = args[0]
                     .place()
                     .expect("Casts to/from null pointer are not yet supported");
@@ -575,7 +601,7 @@ impl<'tcx> TypeChecker<'tcx, '_> {
                 let perms = PermissionSet::FREE;
                 self.add_all_perms(rv_lty.label, perms);
             }
-            Callee::Memcpy => {
+            Callee::Memcpy | Callee::Memmove => {
                 let out_ptr = destination;
 
                 let dest_ptr = args[0]
@@ -663,6 +689,45 @@ impl<'tcx> TypeChecker<'tcx, '_> {
                 // `NON_NULL` flag.
                 self.add_no_perms(pl_lty.label, PermissionSet::NON_NULL);
             }
+            Callee::Transmute { from_ty, to_ty } => {
+                // Deliberately skip `do_assign`/`do_unify` between the argument and the
+                // destination: a `transmute` is exactly the case where the two pointers (if either
+                // side is a pointer at all) aren't expected to share a representation, so
+                // unifying them would let the solver "prove" a safe-reference conversion across an
+                // arbitrary reinterpretation. We still visit both sides so their accesses and
+                // last-use info are recorded like any other use of the underlying locals; the
+                // `analyze::mark_transmute_ptrs_fixed` pass pins both to `FIXED` afterward so
+                // neither one gets rewritten on the assumption that it matches the other.
+                ::log::warn!(
+                    "transmute from `{from_ty:?}` to `{to_ty:?}` at {loc:?}; both sides (if \
+                     pointers) are pinned to their original raw representation"
+                );
+                assert!(args.len() == 1);
+                self.visit_operand(&args[0]);
+                self.visit_place(destination, Mutability::Mut);
+            }
+            Callee::Volatile { op, .. } => match op {
+                VolatileOp::Read => {
+                    assert!(args.len() == 1);
+                    let ptr = args[0]
+                        .place()
+                        .expect("Casts to/from null pointer are not yet supported");
+                    self.visit_place(ptr, Mutability::Not);
+                    self.visit_place(destination, Mutability::Mut);
+                    let ptr_lty = self.acx.type_of(ptr);
+                    self.add_all_perms(ptr_lty.label, PermissionSet::READ);
+                }
+                VolatileOp::Write => {
+                    assert!(args.len() == 2);
+                    let ptr = args[0]
+                        .place()
+                        .expect("Casts to/from null pointer are not yet supported");
+                    self.visit_place(ptr, Mutability::Mut);
+                    self.visit_operand(&args[1]);
+                    let ptr_lty = self.acx.type_of(ptr);
+                    self.add_all_perms(ptr_lty.label, PermissionSet::WRITE);
+                }
+            },
         }
     }
 
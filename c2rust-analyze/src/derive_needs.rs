@@ -0,0 +1,80 @@
+//! Opt-in detection of local structs that will need a `Default`/`Clone` impl after rewriting,
+//! plus (when it's safe to do so) insertion of a `#[derive(Default, Clone)]` to provide one.
+//!
+//! A field whose raw pointer gets rewritten to an owning `Box<T>` (see
+//! [`crate::type_desc::Ownership::Box`]) changes what "copying" that field means: the original
+//! raw pointer could be copied freely, but a `Box<T>` can only be duplicated by actually cloning
+//! the allocation, via `T: Clone`. Once even one field moves from a `PermissionSet::FREE` raw
+//! pointer to `Box<T>`, the containing struct generally needs its own `Clone` impl (and, if some
+//! other rewrite constructs a default-initialized instance of it, e.g. `vec![S::default(); n]`,
+//! a `Default` impl too) that it didn't need before.
+//!
+//! When every field is itself derivable -- no field is left as a raw pointer, and no field is
+//! rewritten to a `&mut T` reference, which implements neither trait -- both impls can be
+//! provided mechanically by inserting `#[derive(Default, Clone)]` above the item, the same
+//! [`crate::rewrite::Rewrite::Print`] mechanism `analyze::must_use_attr_span` uses for
+//! `#[must_use]`. When a blocking field remains, this module only `debug!`-logs that a
+//! hand-written impl is needed: like [`crate::refcount`] and friends, synthesizing a correct
+//! hand-written `Clone`/`Default` for an arbitrary raw-pointer or `&mut T` field is left for a
+//! human to do, rather than guessed at here.
+use crate::context::{Assignment, FlagSet, GlobalAnalysisCtxt};
+use crate::type_desc::{perms_to_ptr_desc, Ownership};
+use log::debug;
+use rustc_hir::def_id::DefId;
+use rustc_span::Span;
+
+/// If `did` (a local struct or union) has a field that will be rewritten to an owning `Box<T>`,
+/// and every field can be mechanically derived, return the span at which `#[derive(Default,
+/// Clone)]` should be inserted. Returns `None` (after `debug!`-logging why, if relevant) if `did`
+/// doesn't need these impls, or needs them but isn't safe to derive automatically.
+pub fn derive_attr_span<'tcx>(
+    gacx: &GlobalAnalysisCtxt<'tcx>,
+    asn: &Assignment,
+    did: DefId,
+) -> Option<Span> {
+    let tcx = gacx.tcx;
+    let adt_def = tcx.adt_def(did);
+    if adt_def.variants().len() != 1 {
+        // Enums with more than one variant aren't populated in `adt_metadata.table` today;
+        // bail out rather than assume how they'd be handled.
+        return None;
+    }
+
+    let mut needs_impls = false;
+    let mut blocked_by = None;
+    for field in adt_def.all_fields() {
+        let f_lty = match gacx.field_ltys.get(&field.did) {
+            Some(x) => x,
+            None => continue,
+        };
+        let ptr = f_lty.label;
+        if ptr.is_none() {
+            continue;
+        }
+        let perms = asn.perms()[ptr];
+        let flags = asn.flags()[ptr];
+        let desc = perms_to_ptr_desc(perms, flags);
+        match desc.own {
+            Ownership::Box => needs_impls = true,
+            Ownership::Mut => blocked_by = Some(field.did),
+            Ownership::Raw | Ownership::RawMut if flags.contains(FlagSet::FIXED) => {
+                blocked_by = Some(field.did);
+            }
+            _ => {}
+        }
+    }
+
+    if !needs_impls {
+        return None;
+    }
+    if let Some(fdid) = blocked_by {
+        debug!(
+            "{:?} needs a hand-written Default/Clone impl ({:?} can't be derived)",
+            did, fdid
+        );
+        return None;
+    }
+
+    let hir_id = tcx.hir().local_def_id_to_hir_id(did.expect_local());
+    Some(tcx.hir().span(hir_id).shrink_to_lo())
+}
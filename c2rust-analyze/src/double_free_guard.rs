@@ -0,0 +1,172 @@
+//! Opt-in detection of "double-free guard" patterns like `if (p) { free(p); p = NULL; }`.
+//!
+//! Once `p`'s pointer is rewritten to `Option<Box<T>>`, assigning `None` to `p` already drops
+//! whatever `p` used to hold (or does nothing, if it was already `None`), so the whole guarded
+//! statement is equivalent to the single assignment `p = None;` -- the `if` and the explicit
+//! `free` call both become redundant "dangling checks" once that assignment exists.
+//!
+//! As with [`crate::null_guard`], the existing per-statement rewrite passes already handle each
+//! piece of this pattern correctly in isolation (the `free` call becomes a checked
+//! [`mir_op::RewriteKind::FreeSafe`](crate::rewrite::expr::mir_op::RewriteKind::FreeSafe), and the
+//! `p = NULL` store becomes `p = None`), so the output is sound but, per the originating request,
+//! not idiomatic: a redundant `is_some()`-shaped guard remains around an assignment that no longer
+//! needs one. Collapsing the guard away requires deleting the whole `if` statement and replacing
+//! it with a single assignment, which -- like the `expect()`-collapsing case in
+//! [`crate::null_guard`] -- the expression-at-a-time rewrite pipeline in
+//! [`crate::rewrite::expr`] has no mechanism for today. This module only detects and logs
+//! candidates for a human to collapse by hand.
+use crate::util::{self, ty_callee, Callee};
+use log::debug;
+use rustc_middle::mir::{BasicBlock, BinOp, Body, Local, Operand, StatementKind, TerminatorKind};
+use rustc_middle::ty::TyCtxt;
+use std::collections::HashSet;
+
+/// A candidate double-free-guard pattern: `local` is compared against `NULL` in `guard_block`,
+/// and the arm taken when `local` is non-null calls `free(local)` and then stores `NULL` back
+/// into `local`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct DoubleFreeGuardCandidate {
+    pub guard_block: BasicBlock,
+    pub local: Local,
+}
+
+/// Find `if (p) { free(p); p = NULL; }`-shaped guards in `mir`. Run only when
+/// `C2RUST_ANALYZE_DETECT_DOUBLE_FREE_GUARDS=1` is set, since (like the other opt-in detectors in
+/// this crate) this is a heuristic, not a conclusion backed by the dataflow analysis.
+pub fn find_double_free_guard_candidates<'tcx>(
+    tcx: TyCtxt<'tcx>,
+    mir: &Body<'tcx>,
+) -> HashSet<DoubleFreeGuardCandidate> {
+    let mut out = HashSet::new();
+    for (bb, bb_data) in mir.basic_blocks.iter_enumerated() {
+        let targets = match bb_data.terminator().kind {
+            TerminatorKind::SwitchInt { ref targets, .. } => targets,
+            _ => continue,
+        };
+        let local = match find_null_check_local(bb_data) {
+            Some(x) => x,
+            None => continue,
+        };
+        // The other arm (the one taken when `local` is null) must rejoin the free-and-nullify
+        // arm's successor directly, with no other code in between, for the two arms to be
+        // equivalent to a single unconditional assignment.
+        let all_targets = targets.all_targets();
+        if all_targets.len() != 2 {
+            continue;
+        }
+        let join_via_other_arm = |free_target: BasicBlock| {
+            all_targets
+                .iter()
+                .copied()
+                .find(|&t| t != free_target)
+                .map(|other| (other, &mir.basic_blocks[other]))
+        };
+        let frees_and_nullifies = all_targets.iter().copied().find(|&target| {
+            let target_data = &mir.basic_blocks[target];
+            frees_then_nullifies(tcx, mir, target_data, local)
+                .map(|join| {
+                    matches!(
+                        join_via_other_arm(target),
+                        Some((other, other_data))
+                            if other == join || matches!(
+                                other_data.terminator().kind,
+                                TerminatorKind::Goto { target: t } if t == join
+                            )
+                    )
+                })
+                .unwrap_or(false)
+        });
+        if frees_and_nullifies.is_none() {
+            continue;
+        }
+        debug!("found double-free guard on {local:?} at {bb:?}");
+        out.insert(DoubleFreeGuardCandidate {
+            guard_block: bb,
+            local,
+        });
+    }
+    out
+}
+
+/// If `bb_data` is an empty block that unconditionally calls `free(local)` and then, in the block
+/// it branches to, stores `NULL` into `local` and branches onward, return that onward block. Only
+/// the common case of a block with no other statements calling `free` once, followed by a block
+/// with exactly one null-store, is recognized; a guard arm that does anything else is not handled.
+fn frees_then_nullifies<'tcx>(
+    tcx: TyCtxt<'tcx>,
+    mir: &Body<'tcx>,
+    bb_data: &rustc_middle::mir::BasicBlockData<'tcx>,
+    local: Local,
+) -> Option<BasicBlock> {
+    if !bb_data.statements.is_empty() {
+        return None;
+    }
+    let (func, args, next) = match bb_data.terminator().kind {
+        TerminatorKind::Call {
+            ref func,
+            ref args,
+            target: Some(next),
+            ..
+        } => (func, args, next),
+        _ => return None,
+    };
+    let func_ty = func.ty(mir, tcx);
+    if !matches!(ty_callee(tcx, func_ty), Callee::Free | Callee::CustomFree) {
+        return None;
+    }
+    if args.len() != 1 || args[0].place().and_then(|pl| pl.as_local()) != Some(local) {
+        return None;
+    }
+
+    let next_data = &mir.basic_blocks[next];
+    if next_data.statements.len() != 1 {
+        return None;
+    }
+    let stmt = &next_data.statements[0];
+    let (place, rv) = match &stmt.kind {
+        StatementKind::Assign(x) => (&x.0, &x.1),
+        _ => return None,
+    };
+    if place.as_local() != Some(local) {
+        return None;
+    }
+    let null_op = match rv {
+        rustc_middle::mir::Rvalue::Use(op) => op,
+        rustc_middle::mir::Rvalue::Cast(_, op, _) => op,
+        _ => return None,
+    };
+    if !util::is_null_const_operand(null_op) {
+        return None;
+    }
+
+    let join = match next_data.terminator().kind {
+        TerminatorKind::Goto { target } => target,
+        _ => return None,
+    };
+    Some(join)
+}
+
+/// If the last statement in `bb_data` assigns the switch discriminant from `local == NULL` or
+/// `local != NULL`, return `local`.
+fn find_null_check_local<'tcx>(bb_data: &rustc_middle::mir::BasicBlockData<'tcx>) -> Option<Local> {
+    let stmt = bb_data.statements.last()?;
+    let (_, rv) = match &stmt.kind {
+        StatementKind::Assign(x) => (&x.0, &x.1),
+        _ => return None,
+    };
+    let (op, ops) = match rv {
+        rustc_middle::mir::Rvalue::BinaryOp(op, ops) => (*op, ops),
+        _ => return None,
+    };
+    if !matches!(op, BinOp::Eq | BinOp::Ne) {
+        return None;
+    }
+    let (ref a, ref b) = **ops;
+    let place_local_if_null_cmp = |ptr_op: &Operand<'tcx>, null_op: &Operand<'tcx>| {
+        if !util::is_null_const_operand(null_op) {
+            return None;
+        }
+        ptr_op.place().and_then(|pl| pl.as_local())
+    };
+    place_local_if_null_cmp(a, b).or_else(|| place_local_if_null_cmp(b, a))
+}
@@ -0,0 +1,72 @@
+#![feature(rustc_private)]
+extern crate either;
+extern crate rustc_arena;
+extern crate rustc_ast;
+extern crate rustc_const_eval;
+extern crate rustc_data_structures;
+extern crate rustc_driver;
+extern crate rustc_hir;
+extern crate rustc_index;
+extern crate rustc_interface;
+extern crate rustc_middle;
+extern crate rustc_mir_build;
+extern crate rustc_session;
+extern crate rustc_span;
+extern crate rustc_target;
+extern crate rustc_type_ir;
+
+pub mod analyze;
+pub mod annotate;
+pub mod array_of_ptrs;
+pub mod borrowck;
+pub mod call_graph;
+pub mod callback_shims;
+pub mod cast_chain;
+pub mod const_fn_candidates;
+pub mod context;
+pub mod dataflow;
+pub mod derive_needs;
+pub mod double_free_guard;
+pub mod equiv;
+pub mod known_fn;
+pub mod labeled_ty;
+pub mod last_use;
+pub mod log;
+pub mod mem_report;
+pub mod mir_dump;
+pub mod null_guard;
+pub mod offset_expr;
+pub mod panic_detail;
+pub mod pointee_type;
+pub mod pointer_id;
+pub mod provenance;
+pub mod recent_writes;
+pub mod refcount;
+pub mod rewrite;
+pub mod static_locals;
+pub mod tokenize_loop;
+pub mod trivial;
+pub mod type_desc;
+pub mod unsafe_helper_dedup;
+pub mod util;
+pub mod vtable_struct;
+
+use analyze::AnalysisCallbacks;
+use anyhow::anyhow;
+
+/// Run the analysis on a single crate, as a `rustc` driver callback.
+///
+/// `at_args` is the full `rustc` argument list for the crate being analyzed, exactly as would be
+/// passed to `rustc` itself (this crate's `cargo`/`rustc` wrapper binary builds such an argument
+/// list for each crate it intercepts via `RUSTC_WRAPPER`, but any other caller that can assemble
+/// an equivalent `rustc` invocation may call this directly instead of going through that wrapper).
+pub fn run_compiler(at_args: &[String]) -> anyhow::Result<()> {
+    let dont_catch = std::env::var_os("C2RUST_ANALYZE_TEST_DONT_CATCH_PANIC").is_some();
+    if !dont_catch {
+        panic_detail::set_hook();
+    }
+
+    rustc_driver::RunCompiler::new(at_args, &mut AnalysisCallbacks)
+        .run()
+        .map_err(|_| anyhow!("`rustc` failed"))
+}
@@ -15,30 +15,10 @@ extern crate rustc_span;
 extern crate rustc_target;
 extern crate rustc_type_ir;
 
-mod analyze;
-mod annotate;
-mod borrowck;
-mod context;
-mod dataflow;
-mod equiv;
-mod known_fn;
-mod labeled_ty;
-mod last_use;
-mod log;
-mod panic_detail;
-mod pointee_type;
-mod pointer_id;
-mod recent_writes;
-mod rewrite;
-mod trivial;
-mod type_desc;
-mod util;
-
-use crate::log::init_logger;
-use analyze::AnalysisCallbacks;
 use anyhow::anyhow;
 use anyhow::ensure;
 use anyhow::Context;
+use c2rust_analyze::log::init_logger;
 use clap::{ArgAction, Parser, ValueEnum};
 use rustc_driver::RunCompiler;
 use rustc_driver::TimePassesCallbacks;
@@ -89,6 +69,47 @@ struct Args {
     #[clap(long, hide(true), conflicts_with("rewrite_mode"))]
     rewrite_in_place: bool,
 
+    /// Write rewritten files under this directory instead of in place or alongside the
+    /// originals, mirroring each file's path relative to the crate root. The default (no
+    /// `--rewrite-mode`, `--rewrite-in-place`, or `--output-dir`) is a dry run: nothing is
+    /// written to disk, and the rewritten source is only printed as part of the tool's debug
+    /// output.
+    #[clap(
+        long,
+        conflicts_with("rewrite_mode"),
+        conflicts_with("rewrite_in_place")
+    )]
+    output_dir: Option<PathBuf>,
+
+    /// When rewriting in place (`--rewrite-mode inplace` / `--rewrite-in-place`), save each
+    /// file's pre-rewrite contents to a sibling `.orig` file before overwriting it, unless that
+    /// backup already exists.
+    #[clap(long)]
+    backup_originals: bool,
+
+    /// Also write the rewrites to this path as newline-delimited JSON, in the style of `rustc`'s
+    /// own machine-applicable suggestion output, for `cargo fix`-like tooling or editor
+    /// integrations that know how to apply that format. This is independent of `--rewrite-mode`;
+    /// both, either, or neither can be set.
+    #[clap(long)]
+    emit_suggestions_json: Option<PathBuf>,
+
+    /// Also write every individual rewrite to this path as newline-delimited JSON: span, original
+    /// text, replacement text, and the `Rewrite` variant/reason that produced it. Unlike
+    /// `--emit-suggestions-json`, entries aren't merged into non-overlapping top-level spans, so
+    /// this is for tooling that wants to inspect or choose among individual rewrites rather than
+    /// apply a ready-made patch. Independent of `--rewrite-mode`/`--emit-suggestions-json`.
+    #[clap(long)]
+    emit_rewrites_json: Option<PathBuf>,
+
+    /// Also write the rewrites to this path as newline-delimited JSON, each wrapped in the full
+    /// diagnostic shape `rustc --error-format=json` emits (with a `MachineApplicable` suggestion
+    /// span), for `cargo fix`-style tooling or IDEs that already understand `rustc` diagnostics
+    /// rather than a c2rust-analyze-specific format. Independent of `--rewrite-mode` and the other
+    /// `--emit-*-json` flags.
+    #[clap(long)]
+    emit_rustc_diagnostics_json: Option<PathBuf>,
+
     /// Use `todo!()` placeholders in shims for casts that must be implemented manually.
     ///
     /// When a function requires a shim, and the shim requires a cast that can't be generated
@@ -104,6 +125,34 @@ struct Args {
     #[clap(long)]
     annotate_def_spans: bool,
 
+    /// Add a `#[must_use]` attribute to functions whose return value is rewritten from a raw
+    /// pointer to an owning `Box<T>`, so that discarding the result (which used to be harmless)
+    /// doesn't silently leak a now-meaningful drop.
+    #[clap(long)]
+    annotate_must_use: bool,
+
+    /// Add a `#[derive(Default, Clone)]` attribute to structs that gain a field rewritten from a
+    /// raw pointer to an owning `Box<T>`, when every other field can also be derived. Structs
+    /// that still have a raw-pointer or `&mut T` field after rewriting are logged instead, since
+    /// those need a hand-written impl.
+    #[clap(long)]
+    annotate_derive_needs: bool,
+
+    /// For each public function whose signature rewriting changed, log a line mapping its old
+    /// signature to its new one, and emit a `#[deprecated]` compatibility shim with the old
+    /// (raw-pointer) signature that forwards to the rewritten function, so that downstream crates
+    /// recompiled unchanged get a deprecation warning instead of a type error.
+    #[clap(long)]
+    emit_pub_api_compat_shims: bool,
+
+    /// Compile with `--cfg test` active, so that bodies under `#[cfg(test)]` are visible to the
+    /// same rustc invocation (and thus the same analysis run) as the rest of the crate, rather
+    /// than being stripped out entirely before `c2rust-analyze` ever sees them. With this set,
+    /// a signature rewrite to a function under test is generated once and applies consistently
+    /// to both its non-test callers and its test call sites.
+    #[clap(long)]
+    analyze_tests: bool,
+
     /// Completely disable the `borrowck` pass.  All pointers will be given the `UNIQUE`
     /// permission; none will be wrapped in `Cell`.
     #[clap(long)]
@@ -126,6 +175,30 @@ struct Args {
     #[clap(long)]
     skip_pointee_defs_list: Option<PathBuf>,
 
+    /// Read a list of defs to dump annotated MIR for (at the `debug` log level): each
+    /// statement/terminator is printed alongside the `PointerId`/`PermissionSet`/`FlagSet` of
+    /// every local it mentions, to make it easier to correlate analysis state with MIR structure
+    /// than scattered `eprintln!`/`debug!` output allows.
+    #[clap(long)]
+    dump_mir_annotated_list: Option<PathBuf>,
+
+    /// Log peak memory usage (`VmHWM`) at major phase boundaries, to help track down where a
+    /// large crate's memory is going.
+    #[clap(long)]
+    memory_report: bool,
+
+    /// Look for fields that are manually incremented/decremented (a hand-rolled refcount) and log
+    /// them as candidates for conversion to `Rc`/`Arc`.  This is a heuristic that will also flag
+    /// ordinary integer counters, and it only reports candidates -- it doesn't rewrite anything.
+    #[clap(long)]
+    detect_manual_refcount: bool,
+
+    /// Look for `strtok`/`strsep` calls inside loops and log them as candidates for rewriting to
+    /// a `split`/`split_mut`-based iteration.  This only reports candidates -- it doesn't rewrite
+    /// anything.
+    #[clap(long)]
+    detect_tokenize_loops: bool,
+
     /// `cargo` args.
     cargo_args: Vec<OsString>,
 }
@@ -343,24 +416,22 @@ fn rustc_wrapper() -> anyhow::Result<()> {
         .ok_or_else(|| anyhow!("sysroot path is not UTF-8: {}", sysroot.display()))?;
     at_args.extend(["--sysroot".into(), sysroot.into()]);
     let result = if is_primary_compilation {
-        let dont_catch = env::var_os("C2RUST_ANALYZE_TEST_DONT_CATCH_PANIC").is_some();
-        if !dont_catch {
-            panic_detail::set_hook();
-        }
-
-        RunCompiler::new(&at_args, &mut AnalysisCallbacks).run()
+        c2rust_analyze::run_compiler(&at_args)
     } else {
         // Always use the dynamically linked `librustc_driver-{hash}.so`,
         // as it is guaranteed to be the same version as the instrumented version.
         // Furthermore, we can't accidentally load the wrong `librustc_driver-{hash}.so`,
         // as it contains its hash.
         // This also avoids an extra `rustc` (and potentially `rustup` `rustc`) invocation.
-        RunCompiler::new(&at_args, &mut TimePassesCallbacks::default()).run()
+        //
+        // `ErrorReported` means the error has already been reported to the user,
+        // so we just have to fail/exit with a failing exit code.
+        // There is no `impl Error for ErrorReported`.
+        RunCompiler::new(&at_args, &mut TimePassesCallbacks::default())
+            .run()
+            .map_err(|_| anyhow!("`rustc` failed"))
     };
-    // `ErrorReported` means the error has already been reported to the user,
-    // so we just have to fail/exit with a failing exit code.
-    // There is no `impl Error for ErrorReported`.
-    result.map_err(|_| anyhow!("`rustc` failed"))?;
+    result?;
     Ok(())
 }
 
@@ -411,12 +482,25 @@ fn cargo_wrapper(rustc_wrapper: &Path) -> anyhow::Result<()> {
         rewrite_paths,
         mut rewrite_mode,
         rewrite_in_place,
+        output_dir,
+        backup_originals,
+        emit_suggestions_json,
+        emit_rewrites_json,
+        emit_rustc_diagnostics_json,
         use_manual_shims,
         annotate_def_spans,
+        annotate_must_use,
+        annotate_derive_needs,
+        emit_pub_api_compat_shims,
+        analyze_tests,
         skip_borrowck,
         fixed_defs_list,
         force_rewrite_defs_list,
         skip_pointee_defs_list,
+        dump_mir_annotated_list,
+        memory_report,
+        detect_manual_refcount,
+        detect_tokenize_loops,
         cargo_args,
     } = Args::parse();
 
@@ -449,6 +533,7 @@ fn cargo_wrapper(rustc_wrapper: &Path) -> anyhow::Result<()> {
         let rustflags = [
             env::var_os("RUSTFLAGS"),
             Some("-A warnings".into()),
+            analyze_tests.then(|| OsString::from("--cfg test")),
             rustflags,
         ]
         .into_iter()
@@ -472,6 +557,13 @@ fn cargo_wrapper(rustc_wrapper: &Path) -> anyhow::Result<()> {
             cmd.env("C2RUST_ANALYZE_SKIP_POINTEE_LIST", skip_pointee_defs_list);
         }
 
+        if let Some(ref dump_mir_annotated_list) = dump_mir_annotated_list {
+            cmd.env(
+                "C2RUST_ANALYZE_DUMP_MIR_ANNOTATED_LIST",
+                dump_mir_annotated_list,
+            );
+        }
+
         if !rewrite_paths.is_empty() {
             let rewrite_paths = rewrite_paths.join(OsStr::new(","));
             cmd.env("C2RUST_ANALYZE_REWRITE_PATHS", rewrite_paths);
@@ -487,6 +579,32 @@ fn cargo_wrapper(rustc_wrapper: &Path) -> anyhow::Result<()> {
             cmd.env("C2RUST_ANALYZE_REWRITE_MODE", val);
         }
 
+        if let Some(ref output_dir) = output_dir {
+            cmd.env("C2RUST_ANALYZE_OUTPUT_DIR", output_dir);
+        }
+
+        if backup_originals {
+            cmd.env("C2RUST_ANALYZE_BACKUP_ORIGINALS", "1");
+        }
+
+        if let Some(ref emit_suggestions_json) = emit_suggestions_json {
+            cmd.env(
+                "C2RUST_ANALYZE_SUGGESTIONS_JSON_PATH",
+                emit_suggestions_json,
+            );
+        }
+
+        if let Some(ref emit_rewrites_json) = emit_rewrites_json {
+            cmd.env("C2RUST_ANALYZE_REWRITES_JSON_PATH", emit_rewrites_json);
+        }
+
+        if let Some(ref emit_rustc_diagnostics_json) = emit_rustc_diagnostics_json {
+            cmd.env(
+                "C2RUST_ANALYZE_RUSTC_DIAGNOSTICS_JSON_PATH",
+                emit_rustc_diagnostics_json,
+            );
+        }
+
         if use_manual_shims {
             cmd.env("C2RUST_ANALYZE_USE_MANUAL_SHIMS", "1");
         }
@@ -495,10 +613,33 @@ fn cargo_wrapper(rustc_wrapper: &Path) -> anyhow::Result<()> {
             cmd.env("C2RUST_ANALYZE_ANNOTATE_DEF_SPANS", "1");
         }
 
+        if annotate_must_use {
+            cmd.env("C2RUST_ANALYZE_ANNOTATE_MUST_USE", "1");
+        }
+
+        if annotate_derive_needs {
+            cmd.env("C2RUST_ANALYZE_ANNOTATE_DERIVE_NEEDS", "1");
+        }
+        if emit_pub_api_compat_shims {
+            cmd.env("C2RUST_ANALYZE_EMIT_PUB_API_COMPAT_SHIMS", "1");
+        }
+
         if skip_borrowck {
             cmd.env("C2RUST_ANALYZE_SKIP_BORROWCK", "1");
         }
 
+        if memory_report {
+            cmd.env("C2RUST_ANALYZE_MEMORY_REPORT", "1");
+        }
+
+        if detect_manual_refcount {
+            cmd.env("C2RUST_ANALYZE_DETECT_MANUAL_REFCOUNT", "1");
+        }
+
+        if detect_tokenize_loops {
+            cmd.env("C2RUST_ANALYZE_DETECT_TOKENIZE_LOOPS", "1");
+        }
+
         Ok(())
     })?;
 
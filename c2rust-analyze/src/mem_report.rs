@@ -0,0 +1,41 @@
+//! Peak-memory checkpoints, enabled via `--memory-report`.
+//!
+//! This reads `VmHWM` (the process's peak resident set size so far) out of `/proc/self/status`
+//! rather than pulling in a profiling crate or instrumenting individual allocations; it's meant
+//! to give a rough per-phase breakdown of where memory is going on large crates, not to replace
+//! a real profiler.
+use std::fs;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use log::info;
+
+static ENABLED: AtomicBool = AtomicBool::new(false);
+
+/// Enable memory checkpoint reporting for the remainder of this process's run.
+pub fn enable() {
+    ENABLED.store(true, Ordering::Relaxed);
+}
+
+fn peak_rss_kb() -> Option<u64> {
+    let status = fs::read_to_string("/proc/self/status").ok()?;
+    let line = status.lines().find(|line| line.starts_with("VmHWM:"))?;
+    line.trim_start_matches("VmHWM:")
+        .trim()
+        .trim_end_matches(" kB")
+        .trim()
+        .parse()
+        .ok()
+}
+
+/// Record a checkpoint under `label`, if `--memory-report` was passed.  Checkpoints are logged
+/// immediately rather than buffered, so they're still useful if the process aborts partway
+/// through analysis.
+pub fn checkpoint(label: &str) {
+    if !ENABLED.load(Ordering::Relaxed) {
+        return;
+    }
+    match peak_rss_kb() {
+        Some(kb) => info!("memory report: {label}: peak RSS so far = {kb} kB"),
+        None => info!("memory report: {label}: peak RSS unavailable"),
+    }
+}
@@ -0,0 +1,83 @@
+//! Opt-in `--dump-mir-annotated`-style debug dump (see `C2RUST_ANALYZE_DUMP_MIR_ANNOTATED_LIST` in
+//! `analyze::run`): for a config-listed set of functions, print each MIR statement/terminator
+//! together with the `PointerId`/`PermissionSet`/`FlagSet` of every local it mentions, so
+//! correlating analysis state with MIR structure doesn't require cross-referencing the separate
+//! per-var labeling dump and per-function rewrite report by hand.
+//!
+//! This only annotates *locals*; it doesn't print a "planned rewrite" for a given `Location`,
+//! because MIR-level rewrites aren't kept in a `Location`-indexed table once they're folded into
+//! HIR-level rewrites by `rewrite::expr::convert` -- recovering that would need a bigger plumbing
+//! change than this dump is meant to make.
+
+use crate::context::{AnalysisCtxt, Assignment};
+use log::debug;
+use rustc_middle::mir::visit::{PlaceContext, Visitor};
+use rustc_middle::mir::{Body, Local, Location};
+use std::collections::BTreeSet;
+
+/// Collects every distinct `Local` visited, in order, discarding `Location`s -- we only want the
+/// set of locals mentioned by a single statement/terminator, not where within it.
+#[derive(Default)]
+struct LocalFinder {
+    locals: BTreeSet<Local>,
+}
+
+impl<'tcx> Visitor<'tcx> for LocalFinder {
+    fn visit_local(&mut self, local: Local, _context: PlaceContext, _location: Location) {
+        self.locals.insert(local);
+    }
+}
+
+fn describe_local(acx: &AnalysisCtxt, asn: &Assignment, local: Local) -> String {
+    let lty = acx.local_tys[local];
+    let mut ptrs = Vec::new();
+    for lty in lty.iter() {
+        let ptr = lty.label;
+        if ptr.is_none() {
+            continue;
+        }
+        ptrs.push(format!(
+            "{:?} = {:?}, {:?}",
+            ptr,
+            asn.perms()[ptr],
+            asn.flags()[ptr]
+        ));
+    }
+    if ptrs.is_empty() {
+        format!("{:?}: (no pointers)", local)
+    } else {
+        format!("{:?}: {}", local, ptrs.join("; "))
+    }
+}
+
+/// Print `mir`'s basic blocks to the `debug!` log, with each statement/terminator followed by an
+/// annotation line for every local it mentions.
+pub fn dump_annotated_mir<'tcx>(acx: &AnalysisCtxt<'_, 'tcx>, asn: &Assignment, mir: &Body<'tcx>) {
+    for (bb, bb_data) in mir.basic_blocks().iter_enumerated() {
+        debug!("{:?}:", bb);
+        for (i, stmt) in bb_data.statements.iter().enumerate() {
+            let loc = Location {
+                block: bb,
+                statement_index: i,
+            };
+            debug!("  {:?}", stmt.kind);
+            let mut finder = LocalFinder::default();
+            finder.visit_statement(stmt, loc);
+            for local in finder.locals {
+                debug!("    {}", describe_local(acx, asn, local));
+            }
+        }
+        if let Some(ref term) = bb_data.terminator {
+            let loc = Location {
+                block: bb,
+                statement_index: bb_data.statements.len(),
+            };
+            debug!("  {:?}", term.kind);
+            let mut finder = LocalFinder::default();
+            finder.visit_terminator(term, loc);
+            for local in finder.locals {
+                debug!("    {}", describe_local(acx, asn, local));
+            }
+        }
+    }
+}
@@ -0,0 +1,115 @@
+//! Opt-in detection of "null guard" patterns like `if (!p) abort();` or `if (p == NULL) exit(1);`
+//! followed by unconditional uses of `p`.
+//!
+//! Once `p`'s pointer is inferred nullable, the existing rewrite passes already turn the guard's
+//! `p.is_null()`/`p == null` check into `p.is_none()` (see [`mir_op::RewriteKind::IsNullToIsNone`])
+//! and already turn `p`'s later dereference into `p.unwrap()` (see
+//! [`mir_op::RewriteKind::Unwrap`]), since each of those is just a single-expression cast at its
+//! own MIR location.  The result is correct but, as the name of this module's originating request
+//! put it, "awkward": a separate `is_none` check plus a separate `unwrap` at the use site, instead
+//! of a single `let p = p.expect("...")` that replaces the whole guard statement.
+//!
+//! Collapsing the two into one `expect()` call isn't implemented here.  The rewrite pipeline in
+//! [`crate::rewrite::expr`] only ever rewrites one HIR expression at a time, via the
+//! [`unlower`](crate::rewrite::expr::unlower) map from a MIR location back to the span of the HIR
+//! expression that produced it; it has no notion of deleting an entire `if` statement/block or of
+//! merging two rewrites at two different MIR locations (the guard's condition and the later
+//! dereference) into a single replacement at the guard's location. Doing that soundly would also
+//! require proving that the `abort`/`exit`-style call on the guard's failure arm truly never
+//! returns for every such call the C source makes, and that nothing else observes `p` being
+//! `None` between the guard and the point where the combined `.expect()` rewrite would go. For
+//! now, this module only detects and logs candidates for a human to convert by hand.
+use crate::util::{self, ty_callee, Callee, UnknownDefCallee};
+use log::debug;
+use rustc_middle::mir::{BasicBlock, BinOp, Body, Local, Operand, StatementKind, TerminatorKind};
+use rustc_middle::ty::TyCtxt;
+use std::collections::HashSet;
+
+/// libc functions that unconditionally abort the process, as commonly used on the failure arm of
+/// a null guard.
+const GUARD_FAIL_FNS: &[&str] = &["abort", "exit", "_exit"];
+
+/// A candidate null-guard-then-use pattern: `local` is compared against `NULL` in `guard_block`,
+/// and one of the comparison's branches unconditionally calls one of [`GUARD_FAIL_FNS`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct NullGuardCandidate {
+    pub guard_block: BasicBlock,
+    pub local: Local,
+}
+
+/// Find `if (p == NULL) abort();`/`if (p != NULL) { .. } else { abort(); }`-shaped guards in
+/// `mir`.  Run only when `C2RUST_ANALYZE_DETECT_NULL_GUARDS=1` is set, since (like the other
+/// opt-in detectors in this crate) this is a heuristic, not a conclusion backed by the dataflow
+/// analysis.
+pub fn find_null_guard_candidates<'tcx>(
+    tcx: TyCtxt<'tcx>,
+    mir: &Body<'tcx>,
+) -> HashSet<NullGuardCandidate> {
+    let mut out = HashSet::new();
+    for (bb, bb_data) in mir.basic_blocks.iter_enumerated() {
+        let targets = match bb_data.terminator().kind {
+            TerminatorKind::SwitchInt { ref targets, .. } => targets,
+            _ => continue,
+        };
+        let local = match find_null_check_local(bb_data) {
+            Some(x) => x,
+            None => continue,
+        };
+        let calls_guard_fail_fn = targets.all_targets().iter().any(|&target| {
+            let target_data = &mir.basic_blocks[target];
+            // Only recognize the common case of an empty block that immediately calls the
+            // failure function; a guard arm that does anything else first isn't handled.
+            if !target_data.statements.is_empty() {
+                return false;
+            }
+            let func = match target_data.terminator().kind {
+                TerminatorKind::Call { ref func, .. } => func,
+                _ => return false,
+            };
+            let func_ty = func.ty(mir, tcx);
+            let def_id = match ty_callee(tcx, func_ty) {
+                Callee::UnknownDef(UnknownDefCallee::Direct {
+                    def_id,
+                    is_foreign: true,
+                    ..
+                }) => def_id,
+                _ => return false,
+            };
+            GUARD_FAIL_FNS.contains(&tcx.item_name(def_id).as_str())
+        });
+        if !calls_guard_fail_fn {
+            continue;
+        }
+        debug!("found null guard on {local:?} at {bb:?}");
+        out.insert(NullGuardCandidate {
+            guard_block: bb,
+            local,
+        });
+    }
+    out
+}
+
+/// If the last statement in `bb_data` assigns the switch discriminant from `local == NULL` or
+/// `local != NULL`, return `local`.
+fn find_null_check_local<'tcx>(bb_data: &rustc_middle::mir::BasicBlockData<'tcx>) -> Option<Local> {
+    let stmt = bb_data.statements.last()?;
+    let (_, rv) = match &stmt.kind {
+        StatementKind::Assign(x) => (&x.0, &x.1),
+        _ => return None,
+    };
+    let (op, ops) = match rv {
+        rustc_middle::mir::Rvalue::BinaryOp(op, ops) => (*op, ops),
+        _ => return None,
+    };
+    if !matches!(op, BinOp::Eq | BinOp::Ne) {
+        return None;
+    }
+    let (ref a, ref b) = **ops;
+    let place_local_if_null_cmp = |ptr_op: &Operand<'tcx>, null_op: &Operand<'tcx>| {
+        if !util::is_null_const_operand(null_op) {
+            return None;
+        }
+        ptr_op.place().and_then(|pl| pl.as_local())
+    };
+    place_local_if_null_cmp(a, b).or_else(|| place_local_if_null_cmp(b, a))
+}
@@ -0,0 +1,150 @@
+//! A small symbolic form for pointer-derived index expressions built from several chained
+//! `+`/`-` operations, e.g. `(p + i) - q` used to compute an index into a slice derived from `p`.
+//!
+//! [`rewrite::expr::mir_op::MirRewriteVisitor::visit_ptr_offset`] already converts a single
+//! `p.offset(count)` into the `OffsetSlice` family of rewrites, but it only looks at `count`
+//! itself -- it can't see past a few statements of integer arithmetic to find where `count` was
+//! actually built up, e.g.:
+//! ```text
+//! _1 = move _2 as isize (PointerExposeAddress);  // p as isize
+//! _2 = _1 + i;
+//! _3 = _2 - (q as isize);
+//! ... use _3 as an offset/index ...
+//! ```
+//! This module walks such a chain backward (the same [`RecentWrites`]-based pattern used by
+//! `dataflow::type_check::operand_is_size_of_t`) and folds it into a [`SymbolicOffset`]: a sum of
+//! opaque [`SymbolicTerm`]s, each either a plain operand or the negation of one. Only the fragment
+//! actually seen in transpiled C index computations -- chained `Add`/`Sub`/`Use` -- is supported;
+//! anything else (a multiply, a cast this module doesn't recognize, more than [`MAX_TERMS`] terms)
+//! bails out with a `debug!` diagnostic rather than guessing, so callers can fall back to their
+//! existing handling of the un-folded expression.
+//!
+//! Like [`crate::array_of_ptrs`] and friends, this module is detect-only for now: folding an
+//! index expression into a [`SymbolicOffset`] is a first step toward feeding it to the
+//! `OffsetSlice` rewrites above, but actually doing so (choosing which term is the "base" pointer,
+//! checking the others are in-bounds) is future work.
+use crate::recent_writes::RecentWrites;
+use either::Either;
+use log::debug;
+use rustc_middle::mir::{BinOp, Body, CastKind, Location, Operand, Rvalue, StatementKind};
+
+/// The maximum number of chained `+`/`-` terms this module will try to fold into one
+/// [`SymbolicOffset`] before giving up -- hand-written C index expressions rarely exceed a
+/// handful of terms, and bounding this keeps the backward walk from running away on unrelated
+/// code that happens to share a temporary.
+const MAX_TERMS: usize = 8;
+
+/// One term in a folded `+`/`-` chain: `value` if `negate` is `false`, `-value` otherwise.
+#[derive(Clone, Debug)]
+pub struct SymbolicTerm<'tcx> {
+    pub negate: bool,
+    pub value: Operand<'tcx>,
+}
+
+/// A chain of `+`/`-`-combined [`SymbolicTerm`]s, e.g. `(p + i) - q` folds to `[+p, +i, -q]`.
+#[derive(Clone, Debug, Default)]
+pub struct SymbolicOffset<'tcx> {
+    pub terms: Vec<SymbolicTerm<'tcx>>,
+}
+
+/// Attempt to fold the chain of `Add`/`Sub`/`Use` assignments that produced `op`'s value (as of
+/// `loc`) into a [`SymbolicOffset`]. Returns `None`, after logging why, if the chain uses anything
+/// outside that supported fragment.
+pub fn symbolic_offset_of<'tcx>(
+    mir: &Body<'tcx>,
+    recent_writes: &RecentWrites,
+    loc: Location,
+    op: &Operand<'tcx>,
+) -> Option<SymbolicOffset<'tcx>> {
+    let mut terms = Vec::new();
+    let mut work = vec![(false, op.clone(), loc)];
+
+    while let Some((negate, operand, loc)) = work.pop() {
+        if terms.len() + work.len() >= MAX_TERMS {
+            debug!(
+                "symbolic_offset_of: exceeded {} terms while folding {:?}, giving up",
+                MAX_TERMS, op
+            );
+            return None;
+        }
+
+        let place = match operand {
+            Operand::Copy(pl) | Operand::Move(pl) => pl,
+            Operand::Constant(_) => {
+                terms.push(SymbolicTerm {
+                    negate,
+                    value: operand,
+                });
+                continue;
+            }
+        };
+        if place.projection.len() > 0 {
+            terms.push(SymbolicTerm {
+                negate,
+                value: Operand::Copy(place),
+            });
+            continue;
+        }
+
+        let write_loc = match recent_writes.get_write_before(loc, place.local) {
+            Some(x) => x,
+            None => {
+                terms.push(SymbolicTerm {
+                    negate,
+                    value: Operand::Copy(place),
+                });
+                continue;
+            }
+        };
+
+        let stmt = match mir.stmt_at(write_loc) {
+            Either::Left(stmt) => stmt,
+            Either::Right(_) => {
+                terms.push(SymbolicTerm {
+                    negate,
+                    value: Operand::Copy(place),
+                });
+                continue;
+            }
+        };
+        let (_pl, rv) = match &stmt.kind {
+            StatementKind::Assign(x) => (&x.0, &x.1),
+            _ => {
+                terms.push(SymbolicTerm {
+                    negate,
+                    value: Operand::Copy(place),
+                });
+                continue;
+            }
+        };
+
+        match rv {
+            Rvalue::Use(rhs_op) => {
+                work.push((negate, rhs_op.clone(), write_loc));
+            }
+            // `p as isize`: fold through to the pointer `p` itself.
+            Rvalue::Cast(CastKind::PointerExposeAddress, rhs_op, _) => {
+                work.push((negate, rhs_op.clone(), write_loc));
+            }
+            Rvalue::BinaryOp(BinOp::Add, ref ops) => {
+                work.push((negate, ops.0.clone(), write_loc));
+                work.push((negate, ops.1.clone(), write_loc));
+            }
+            Rvalue::BinaryOp(BinOp::Sub, ref ops) => {
+                work.push((negate, ops.0.clone(), write_loc));
+                work.push((!negate, ops.1.clone(), write_loc));
+            }
+            _ => {
+                debug!(
+                    "symbolic_offset_of: unsupported rvalue {:?} while folding {:?}",
+                    rv, op
+                );
+                return None;
+            }
+        }
+    }
+
+    // `work` is a stack, so terms were collected right-to-left; restore source order.
+    terms.reverse();
+    Some(SymbolicOffset { terms })
+}
@@ -1,9 +1,10 @@
 use backtrace::Backtrace;
 use rustc_span::{Span, DUMMY_SP};
 use std::any::Any;
-use std::cell::Cell;
+use std::cell::{Cell, RefCell};
 use std::fmt::Write as _;
 use std::panic::{self, PanicInfo, UnwindSafe};
+use std::sync::Mutex;
 
 /// Detailed information about a panic.
 #[derive(Clone, Debug)]
@@ -13,6 +14,15 @@ pub struct PanicDetail {
     relevant_loc: Option<String>,
     backtrace: Option<Backtrace>,
     span: Span,
+    /// Name of the analysis phase that was running when this panic occurred, if the caller of
+    /// [`catch_unwind`] chose to tag it via [`PanicDetail::with_phase`].  Callers that aggregate
+    /// several `PanicDetail`s for the same item (such as multiple `catch_unwind` scopes for one
+    /// function) use this to distinguish which phase each one came from.
+    phase: Option<String>,
+    /// Named context ([`push_breadcrumb`]) that was active when the panic occurred, outermost
+    /// first.  This lets a skipped-function report point at the current function, MIR location,
+    /// or pointer ID without needing to attach a debugger.
+    breadcrumbs: Vec<String>,
 }
 
 impl PanicDetail {
@@ -25,9 +35,18 @@ impl PanicDetail {
             relevant_loc: None,
             backtrace: None,
             span: DUMMY_SP,
+            phase: None,
+            breadcrumbs: Vec::new(),
         }
     }
 
+    /// Tag this `PanicDetail` with the name of the analysis phase that was running when the panic
+    /// occurred.
+    pub fn with_phase(mut self, phase: impl Into<String>) -> PanicDetail {
+        self.phase = Some(phase.into());
+        self
+    }
+
     /// Returns `true` if this `PanicDetail` contains a backtrace.
     pub fn has_backtrace(&self) -> bool {
         self.backtrace.is_some()
@@ -40,7 +59,10 @@ impl PanicDetail {
             .as_ref()
             .or(self.loc.as_ref())
             .map_or("[unknown]", |s| s);
-        format!("{}: {}", loc_str, self.msg.trim())
+        match self.phase {
+            Some(ref phase) => format!("[{}] {}: {}", phase, loc_str, self.msg.trim()),
+            None => format!("{}: {}", loc_str, self.msg.trim()),
+        }
     }
 
     /// Return a full description of this panic, including a complete backtrace if available.
@@ -48,6 +70,12 @@ impl PanicDetail {
         let mut s = String::new();
         let loc_str = self.loc.as_ref().map_or("[unknown]", |s| s);
         writeln!(s, "panic at {}: {}", loc_str, self.msg).unwrap();
+        if let Some(ref phase) = self.phase {
+            writeln!(s, "phase: {}", phase).unwrap();
+        }
+        for breadcrumb in &self.breadcrumbs {
+            writeln!(s, "  while: {}", breadcrumb).unwrap();
+        }
         if let Some(ref relevant_loc) = self.relevant_loc {
             writeln!(s, "related location: {}", relevant_loc).unwrap();
         }
@@ -104,6 +132,8 @@ fn panic_hook(default_hook: &dyn Fn(&PanicInfo), info: &PanicInfo) {
             relevant_loc: guess_relevant_loc(&bt),
             backtrace: Some(bt),
             span: CURRENT_SPAN.with(|cell| cell.get()),
+            phase: None,
+            breadcrumbs: BREADCRUMBS.with(|cell| cell.borrow().clone()),
         };
         cell.set(PanicState::Unwinding(detail));
     });
@@ -144,23 +174,48 @@ pub fn catch_unwind<F: FnOnce() -> R + UnwindSafe, R>(f: F) -> Result<R, PanicDe
     }
 }
 
+/// Default set of patterns used by [`guess_relevant_loc`] to recognize an "interesting" frame.
+/// Overridable via [`set_relevant_name_patterns`].
+fn default_relevant_name_patterns() -> Vec<String> {
+    [
+        "c2rust_analyze::dataflow",
+        "c2rust_analyze::borrowck",
+        "c2rust_analyze::rewrite",
+        "type_of_rvalue",
+        "TypeOf",
+        "lty_project",
+    ]
+    .into_iter()
+    .map(String::from)
+    .collect()
+}
+
+static RELEVANT_NAME_PATTERNS: Mutex<Option<Vec<String>>> = Mutex::new(None);
+
+/// Override the list of substrings used by [`guess_relevant_loc`] to recognize a frame as
+/// "interesting".  By default, this is the small set of `c2rust-analyze`-internal module and
+/// function names baked into [`default_relevant_name_patterns`]; callers working on a fork with
+/// different module names, or who want to narrow/widen the heuristic, can replace the whole list.
+pub fn set_relevant_name_patterns(patterns: Vec<String>) {
+    *RELEVANT_NAME_PATTERNS.lock().unwrap() = Some(patterns);
+}
+
 /// Crude heuristic to guess the first interesting location in a [`Backtrace`], skipping over
 /// helper functions, wrappers, and panic machinery.  The resulting location is used in the summary
 /// message produced by [`PanicDetail::to_string_short`].
 fn guess_relevant_loc(bt: &Backtrace) -> Option<String> {
+    let configured = RELEVANT_NAME_PATTERNS.lock().unwrap();
+    let patterns = configured.as_deref();
+    let default_patterns = default_relevant_name_patterns();
+    let patterns = patterns.unwrap_or(&default_patterns);
+
     for frame in bt.frames() {
         for symbol in frame.symbols() {
             let name = match symbol.name() {
                 Some(x) => x.to_string(),
                 None => continue,
             };
-            if name.starts_with("c2rust_analyze::dataflow")
-                || name.starts_with("c2rust_analyze::borrowck")
-                || name.starts_with("c2rust_analyze::rewrite")
-                || name.contains("type_of_rvalue")
-                || name.contains("TypeOf")
-                || name.contains("lty_project")
-            {
+            if patterns.iter().any(|pattern| name.contains(pattern)) {
                 let filename_str = match symbol.filename() {
                     Some(x) => x.display().to_string(),
                     None => "[unknown]".to_string(),
@@ -210,3 +265,28 @@ pub fn set_current_span(span: Span) -> CurrentSpanGuard {
     let old = CURRENT_SPAN.with(|cell| cell.replace(span));
     CurrentSpanGuard { old }
 }
+
+thread_local! {
+    static BREADCRUMBS: RefCell<Vec<String>> = RefCell::new(Vec::new());
+}
+
+pub struct BreadcrumbGuard {
+    _private: (),
+}
+
+impl Drop for BreadcrumbGuard {
+    fn drop(&mut self) {
+        BREADCRUMBS.with(|cell| {
+            cell.borrow_mut().pop();
+        });
+    }
+}
+
+/// Push a named piece of context (e.g. the current function, MIR location, or pointer ID) onto
+/// the current thread's breadcrumb stack.  If a panic occurs anywhere before the returned guard is
+/// dropped, the full stack of active breadcrumbs is attached to the resulting [`PanicDetail`],
+/// making skipped-function reports actionable without rerunning under a debugger.
+pub fn push_breadcrumb(breadcrumb: impl Into<String>) -> BreadcrumbGuard {
+    BREADCRUMBS.with(|cell| cell.borrow_mut().push(breadcrumb.into()));
+    BreadcrumbGuard { _private: () }
+}
@@ -280,10 +280,11 @@ impl<'tcx> TypeChecker<'tcx, '_> {
                 self.define_pointer_with_type(dest_lty.label, elem_lty);
             }
 
-            Callee::Malloc | Callee::Calloc => {
+            Callee::Malloc | Callee::Calloc | Callee::CustomMalloc { .. } => {
                 // Currently, we just treat this as a definition of unknown type and assert that a
                 // single common pointee type can be found.  In the future, we might expand this to
-                // assert that the inferred pointee type matches the size passed to `malloc`.
+                // assert that the inferred pointee type matches the size passed to `malloc` (or,
+                // for `CustomMalloc`, the argument at its declared `size_arg_idx`).
                 self.define_pointer(dest_lty.label);
             }
             Callee::Realloc => {
@@ -294,7 +295,7 @@ impl<'tcx> TypeChecker<'tcx, '_> {
                 let arg_lty = self.acx.type_of(&args[0]);
                 self.assign(dest_lty.label, arg_lty.label);
             }
-            Callee::Free => {
+            Callee::Free | Callee::CustomFree => {
                 // Here we create a fresh inference variable and associate it with the argument
                 // pointer.  This doesn't constraint the type, since `free` doesn't reveal anything
                 // about the concrete type of the data, but it does ensure that the pointee type of
@@ -306,11 +307,11 @@ impl<'tcx> TypeChecker<'tcx, '_> {
                 self.use_pointer_at_type(arg_lty.label, var);
             }
 
-            Callee::Memcpy => {
-                // We treat the `memcpy` as loading from `*src` and then storing to `*dest`.  The
-                // type of the load and store is unknown at this point (it definitely isn't the
-                // actual type of `*src`/`*dest`, which is `void`), so we introduce a new inference
-                // variable and solve for it later.
+            Callee::Memcpy | Callee::Memmove => {
+                // We treat the `memcpy`/`memmove` as loading from `*src` and then storing to
+                // `*dest`.  The type of the load and store is unknown at this point (it
+                // definitely isn't the actual type of `*src`/`*dest`, which is `void`), so we
+                // introduce a new inference variable and solve for it later.
                 //
                 // In the future, we might check the copy length as described for `malloc`.
                 let var = self.vars.fresh();
@@ -338,6 +339,11 @@ impl<'tcx> TypeChecker<'tcx, '_> {
             Callee::Null { .. } => {
                 // No constraints.
             }
+            Callee::Transmute { .. } => {
+                // No constraints: see `analyze::mark_transmute_ptrs_fixed`, which pins both sides
+                // to `FIXED` instead of relying on pointee-type analysis for a call that's
+                // explicitly allowed to disagree on pointee type.
+            }
         }
     }
 }
@@ -0,0 +1,101 @@
+//! Maps translated items back to the original C declaration site.
+//!
+//! When the input crate was produced by `c2rust-transpile`, every translated item carries a
+//! `#[c2rust::src_loc = "line:col"]` attribute, and (if `--reorganize-definitions` was used) each
+//! module wrapping one original header/source file carries a `#[c2rust::header_src =
+//! "path:line"]` attribute.  Neither attribute is required -- hand-written code, or code that's
+//! been edited since transpilation, simply won't have them -- so every lookup here returns
+//! `None` rather than failing when the metadata is absent.
+use rustc_ast::ast::AttrKind;
+use rustc_hir::def_id::DefId;
+use rustc_middle::ty::TyCtxt;
+use rustc_span::FileName;
+use std::fmt;
+
+/// The original C declaration site of a translated item.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CSourceLoc {
+    /// Path to the original `.c`/`.h` file, as recorded by the transpiler.  Falls back to the
+    /// path of the generated Rust file if the transpiler didn't record a `header_src` (e.g.
+    /// `--reorganize-definitions` wasn't used).
+    pub file: String,
+    pub line: u64,
+}
+
+impl fmt::Display for CSourceLoc {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}:{}", self.file, self.line)
+    }
+}
+
+/// Read the value of a `#[c2rust::<name> = "..."]` attribute on `def_id`, if present.
+fn c2rust_attr_value(tcx: TyCtxt, def_id: DefId, name: &str) -> Option<String> {
+    for attr in tcx.get_attrs_unchecked(def_id) {
+        let item = match attr.kind {
+            AttrKind::Normal(ref item, _) => item,
+            AttrKind::DocComment(..) => continue,
+        };
+        let (a, b) = match &item.path.segments[..] {
+            [a, b] => (a, b),
+            _ => continue,
+        };
+        if a.ident.as_str() == "c2rust" && b.ident.as_str() == name {
+            return attr.value_str().map(|sym| sym.to_string());
+        }
+    }
+    None
+}
+
+/// Returns `true` if `def_id` carries a bare `#[c2rust::<name>]` marker attribute (no `= "..."`
+/// value), such as `#[c2rust::hot]`.
+pub fn has_c2rust_attr(tcx: TyCtxt, def_id: DefId, name: &str) -> bool {
+    for attr in tcx.get_attrs_unchecked(def_id) {
+        let item = match attr.kind {
+            AttrKind::Normal(ref item, _) => item,
+            AttrKind::DocComment(..) => continue,
+        };
+        let (a, b) = match &item.path.segments[..] {
+            [a, b] => (a, b),
+            _ => continue,
+        };
+        if a.ident.as_str() == "c2rust" && b.ident.as_str() == name {
+            return true;
+        }
+    }
+    false
+}
+
+/// Look up the original C source location of `def_id`, if the transpiler recorded one.
+pub fn c_source_loc(tcx: TyCtxt, def_id: DefId) -> Option<CSourceLoc> {
+    let src_loc = c2rust_attr_value(tcx, def_id, "src_loc")?;
+    let line: u64 = src_loc.split(':').next()?.parse().ok()?;
+
+    let header_src = tcx
+        .opt_parent(def_id)
+        .and_then(|parent_id| c2rust_attr_value(tcx, parent_id, "header_src"));
+    let file = match header_src {
+        Some(header_src) => match header_src.rsplit_once(':') {
+            Some((path, _include_line)) => path.to_string(),
+            None => header_src,
+        },
+        None => match tcx.sess.source_map().span_to_filename(tcx.def_span(def_id)) {
+            FileName::Real(ref name) => name
+                .local_path()
+                .map(|p| p.display().to_string())
+                .unwrap_or_else(|| "<unknown>".to_string()),
+            other => format!("{:?}", other),
+        },
+    };
+
+    Some(CSourceLoc { file, line })
+}
+
+/// Format `def_id`'s original C source location (if any) as `" (from foo.c:42)"`, suitable for
+/// appending to a diagnostic or report line about `def_id`.  Returns the empty string if no
+/// provenance metadata is available.
+pub fn describe(tcx: TyCtxt, def_id: DefId) -> String {
+    match c_source_loc(tcx, def_id) {
+        Some(loc) => format!(" (from {})", loc),
+        None => String::new(),
+    }
+}
@@ -0,0 +1,99 @@
+//! Opt-in detection of hand-rolled reference counting.
+//!
+//! Some C code implements its own refcounting by hand: a struct carries an integer field (e.g.
+//! `refs`), every new reference does `obj->refs++`, and every dropped reference does
+//! `obj->refs--` followed by a conditional `free` once the count reaches zero.  This module scans
+//! MIR for the increment/decrement half of that idiom -- `field = field + 1` / `field = field -
+//! 1` -- and reports each matching field as a candidate for replacing with `Rc`/`Arc`
+//! (`Ownership::Rc` already exists as a rewrite target; see [`crate::type_desc`] and
+//! [`crate::rewrite`]).
+//!
+//! This is detection only.  Actually deleting the field and rewriting its increment/decrement/
+//! conditional-free call sites into `Rc`/`Arc` clone/drop isn't implemented here; that requires
+//! recognizing the conditional-free half of the idiom and threading a new `Ownership::Rc`
+//! inference path through the dataflow and rewrite passes, which is a much larger change.  For
+//! now, candidates are only logged, so a human can decide whether to convert a given field by
+//! hand.
+use crate::util;
+use log::debug;
+use rustc_hir::def_id::DefId;
+use rustc_middle::mir::{BinOp, Body, PlaceRef, ProjectionElem, Rvalue, StatementKind};
+use rustc_middle::ty::{TyCtxt, TyKind};
+use std::collections::HashSet;
+
+/// An ADT field that's incremented/decremented by exactly one somewhere in the crate, and so is a
+/// candidate for being a hand-rolled refcount.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct RefcountFieldCandidate {
+    pub adt_did: DefId,
+    pub field_name: String,
+}
+
+/// Find fields of the form `place.field = place.field + 1` or `place.field = place.field - 1` in
+/// `mir`.  Run only when `C2RUST_ANALYZE_DETECT_MANUAL_REFCOUNT=1` is set, since this is a
+/// heuristic that will also flag ordinary counters that have nothing to do with memory
+/// management.
+pub fn find_refcount_field_candidates<'tcx>(
+    tcx: TyCtxt<'tcx>,
+    mir: &Body<'tcx>,
+) -> HashSet<RefcountFieldCandidate> {
+    let mut out = HashSet::new();
+    for bb in mir.basic_blocks().iter() {
+        for stmt in &bb.statements {
+            let (lhs, rv) = match &stmt.kind {
+                StatementKind::Assign(x) => (&x.0, &x.1),
+                _ => continue,
+            };
+            let (op, ops) = match rv {
+                Rvalue::BinaryOp(op, ops) | Rvalue::CheckedBinaryOp(op, ops) => (*op, ops),
+                _ => continue,
+            };
+            if !matches!(op, BinOp::Add | BinOp::Sub) {
+                continue;
+            }
+            let (ref a, ref b) = **ops;
+            let reads_lhs = |operand: &rustc_middle::mir::Operand<'tcx>| {
+                operand.place().map_or(false, |pl| pl == *lhs)
+            };
+            let is_step = (reads_lhs(a) && util::is_one_const_operand(b))
+                || (reads_lhs(b) && util::is_one_const_operand(a));
+            if !is_step {
+                continue;
+            }
+            if let Some(candidate) = field_candidate(tcx, mir, lhs.as_ref()) {
+                debug!("found possible manual refcount field: {candidate:?}");
+                out.insert(candidate);
+            }
+        }
+    }
+    out
+}
+
+/// If `pl` is a field projection onto an ADT (e.g. `(*obj).refs`), return the field it names.
+fn field_candidate<'tcx>(
+    tcx: TyCtxt<'tcx>,
+    mir: &Body<'tcx>,
+    pl: PlaceRef<'tcx>,
+) -> Option<RefcountFieldCandidate> {
+    let (&last_proj, rest) = pl.projection.split_last()?;
+    let field = match last_proj {
+        ProjectionElem::Field(f, _) => f,
+        _ => return None,
+    };
+    let base_pl = PlaceRef {
+        local: pl.local,
+        projection: rest,
+    };
+    let base_ty = base_pl.ty(&mir.local_decls, tcx).ty;
+    let adt_def = match base_ty.kind() {
+        TyKind::Adt(def, _) => *def,
+        _ => return None,
+    };
+    let field_name = adt_def.non_enum_variant().fields[field.index()]
+        .name
+        .to_string();
+    Some(RefcountFieldCandidate {
+        adt_did: adt_def.did(),
+        field_name,
+    })
+}
@@ -0,0 +1,84 @@
+//! Opt-in reporting of how rewriting changed a public function's signature, plus generation of a
+//! `#[deprecated]` compatibility shim that keeps the old raw signature available, forwarding to
+//! the rewritten function.
+//!
+//! Downstream crates that call a `pub fn` by its old raw-pointer signature break as soon as that
+//! signature is rewritten (a raw pointer becoming `&mut T`, an out-param becoming part of a
+//! `Result`, etc.); unlike a call site in this crate, such callers can't be updated by the
+//! rewriter. [`describe_api_change`] produces one line of a machine-readable (grep/diff-friendly)
+//! map from the item's old signature to its new one, for a human or build script to act on, and
+//! [`gen_pub_api_compat_shim_rewrites`] reuses [`super::shim::gen_shim_definition_rewrite`] (the
+//! same raw-to-safe cast machinery used for calls from not-yet-rewritten code in this crate) to
+//! emit a `#[deprecated]`-annotated shim with the function's old signature, so that recompiling a
+//! downstream crate unchanged produces a deprecation warning instead of a hard type error.
+use super::shim::{lty_to_desc_pair, ManualShimCasts};
+use super::ty::desc_to_ty;
+use super::Rewrite;
+use crate::context::{Assignment, GlobalAnalysisCtxt, LFnSig};
+use rustc_hir::def_id::DefId;
+use rustc_middle::ty::TyCtxt;
+use rustc_span::Span;
+
+/// Render `lsig`'s inputs/output as a `fn(...) -> ...`-shaped string, using the "before" (always
+/// `FIXED`) type for each pointer if `fixed` is `true`, or its actual (possibly rewritten) type
+/// otherwise.
+fn describe_sig<'tcx>(
+    tcx: TyCtxt<'tcx>,
+    asn: &Assignment,
+    lsig: &LFnSig<'tcx>,
+    fixed: bool,
+) -> String {
+    let describe_one = |lty| match lty_to_desc_pair(tcx, asn, lty) {
+        Some((desc, fixed_desc)) => {
+            let desc = if fixed { fixed_desc } else { desc };
+            desc_to_ty(tcx, desc).to_string()
+        }
+        // Already `FIXED` (or not a pointer at all): the "before" and "after" types are the same.
+        None => lty.ty.to_string(),
+    };
+    let args = lsig
+        .inputs
+        .iter()
+        .map(|&lty| describe_one(lty))
+        .collect::<Vec<_>>()
+        .join(", ");
+    format!("fn({}) -> {}", args, describe_one(lsig.output))
+}
+
+/// If rewriting changed `did`'s signature, return a `"{did:?}: old_sig => new_sig"` line describing the
+/// change. Returns `None` if every pointer in the signature is `FIXED` (i.e. nothing changed).
+pub fn describe_api_change(
+    gacx: &GlobalAnalysisCtxt,
+    asn: &Assignment,
+    did: DefId,
+) -> Option<String> {
+    let tcx = gacx.tcx;
+    let lsig = gacx.fn_sigs.get(&did)?;
+    let changed = lsig
+        .inputs_and_output()
+        .any(|lty| lty_to_desc_pair(tcx, asn, lty).is_some());
+    if !changed {
+        return None;
+    }
+
+    let old = describe_sig(tcx, asn, lsig, true);
+    let new = describe_sig(tcx, asn, lsig, false);
+    Some(format!("{:?}: {} => {}", did, old, new))
+}
+
+/// Generate a `#[deprecated]`-annotated compatibility shim for `did`, with its old (`FIXED`) raw
+/// signature, forwarding to the rewritten function. Both returned rewrites insert at the same
+/// (zero-width) span, right after the original item.
+pub fn gen_pub_api_compat_shim_rewrites<'tcx>(
+    gacx: &GlobalAnalysisCtxt<'tcx>,
+    asn: &Assignment,
+    def_id: DefId,
+    manual_casts: ManualShimCasts,
+) -> Vec<(Span, Rewrite)> {
+    let (span, shim_rw) = super::shim::gen_shim_definition_rewrite(gacx, asn, def_id, manual_casts);
+    let attr = Rewrite::Print(
+        "\n#[deprecated(note = \"raw-pointer compatibility shim; switch to the safe signature\")]"
+            .to_string(),
+    );
+    vec![(span, attr), (span, shim_rw)]
+}
@@ -1,14 +1,29 @@
 use crate::rewrite::Rewrite;
 use log::warn;
+use rustc_data_structures::sync::{par_for_each_in, Lock, Lrc};
 use rustc_hir::Mutability;
 use rustc_span::source_map::{FileName, SourceMap};
 use rustc_span::{BytePos, SourceFile, Span, SyntaxContext};
 use std::cmp::{self, Reverse};
 use std::collections::HashMap;
 use std::convert::Infallible;
+use std::env;
 use std::fmt;
 use std::mem;
 
+/// Whether to emit `&raw const e` / `&raw mut e` instead of `core::ptr::addr_of!(e)` /
+/// `core::ptr::addr_of_mut!(e)` for [`Rewrite::AddrOf`](crate::rewrite::Rewrite::AddrOf).
+///
+/// The native syntax was stabilized in edition-independent form in Rust 1.82, but the output of
+/// this tool is meant to be usable on whatever (possibly older) toolchain the user is already
+/// building with, so we keep emitting the macro unless the user opts in -- e.g. because their
+/// project has already moved to an edition/toolchain new enough that the native syntax reads more
+/// naturally and plays nicer with `rustfmt`.
+fn use_raw_ref_syntax() -> bool {
+    env::var("C2RUST_ANALYZE_RAW_REF_SYNTAX").as_deref() == Ok("1")
+}
+
+use super::span_like::SpanLike;
 use super::LifetimeName;
 
 #[derive(Clone, PartialEq, Eq, Debug)]
@@ -33,31 +48,6 @@ struct RewriteTree<S = Span> {
     children: Vec<RewriteTree<S>>,
 }
 
-/// This trait defines the subset of the [`Span`] API that we use in this module.  It's implemented
-/// for `Span` and also for `FakeSpan`, which is a simple span type we use in tests to avoid
-/// dealing with interner/`SourceMap` machinery.
-trait SpanLike: Copy + Eq {
-    fn lo(self) -> BytePos;
-    fn hi(self) -> BytePos;
-    fn contains(self, other: Self) -> bool;
-    fn overlaps(self, other: Self) -> bool;
-}
-
-impl SpanLike for Span {
-    fn lo(self) -> BytePos {
-        self.lo()
-    }
-    fn hi(self) -> BytePos {
-        self.hi()
-    }
-    fn contains(self, other: Self) -> bool {
-        self.contains(other)
-    }
-    fn overlaps(self, other: Self) -> bool {
-        self.overlaps(other)
-    }
-}
-
 impl<S: SpanLike> RewriteTree<S> {
     #[allow(clippy::type_complexity)]
     pub fn build(
@@ -297,16 +287,38 @@ impl<S: Sink> Emitter<'_, S> {
                 slf.emit(rw, 2)
             }),
             Rewrite::AddrOf(ref rw, mutbl) => {
-                match mutbl {
-                    Mutability::Not => self.emit_str("core::ptr::addr_of!")?,
-                    Mutability::Mut => self.emit_str("core::ptr::addr_of_mut!")?,
+                if use_raw_ref_syntax() {
+                    self.emit_parenthesized(prec > 2, |slf| {
+                        match mutbl {
+                            Mutability::Not => slf.emit_str("&raw const ")?,
+                            Mutability::Mut => slf.emit_str("&raw mut ")?,
+                        }
+                        slf.emit(rw, 2)
+                    })
+                } else {
+                    match mutbl {
+                        Mutability::Not => self.emit_str("core::ptr::addr_of!")?,
+                        Mutability::Mut => self.emit_str("core::ptr::addr_of_mut!")?,
+                    }
+                    self.emit_parenthesized(true, |slf| slf.emit(rw, 0))
                 }
-                self.emit_parenthesized(true, |slf| slf.emit(rw, 0))
             }
             Rewrite::Deref(ref rw) => self.emit_parenthesized(prec > 2, |slf| {
                 slf.emit_str("*")?;
                 slf.emit(rw, 2)
             }),
+            Rewrite::Not(ref rw) => self.emit_parenthesized(prec > 2, |slf| {
+                slf.emit_str("!")?;
+                slf.emit(rw, 2)
+            }),
+            Rewrite::Unsafe(ref rw) => {
+                self.emit_str("unsafe ")?;
+                self.emit(rw, 0)
+            }
+            Rewrite::Commented(ref rw, ref comment) => self.emit_parenthesized(prec > 0, |slf| {
+                slf.emit(rw, 0)?;
+                slf.emit_fmt(format_args!(" /* {} */", comment))
+            }),
             Rewrite::Index(ref arr, ref idx) => self.emit_parenthesized(prec > 3, |slf| {
                 slf.emit(arr, 3)?;
                 slf.emit_str("[")?;
@@ -331,6 +343,10 @@ impl<S: Sink> Emitter<'_, S> {
                     slf.emit_str("]")
                 })
             }
+            Rewrite::RangeFrom(ref lo) => self.emit_parenthesized(prec > 999, |slf| {
+                slf.emit(lo, 999)?;
+                slf.emit_str("..")
+            }),
             Rewrite::Cast(ref rw, ref ty) => self.emit_parenthesized(prec > 1, |slf| {
                 slf.emit(rw, 1)?;
                 slf.emit_str(" as ")?;
@@ -658,8 +674,64 @@ pub struct FileRewrite {
     pub line_map: Vec<usize>,
 }
 
+/// Split `rts` into per-file chunks, in source order.  Each chunk's spans all lie within the
+/// `SourceFile` returned alongside it.
+fn split_by_file<'a>(
+    source_map: &SourceMap,
+    mut rts: &'a [RewriteTree<Span>],
+) -> Vec<(Lrc<SourceFile>, &'a [RewriteTree<Span>])> {
+    let mut chunks = Vec::new();
+    while !rts.is_empty() {
+        let file = source_map.lookup_source_file(rts[0].span.lo());
+        let idx = rts
+            .iter()
+            .position(|rt| rt.span.lo() >= file.end_pos)
+            .unwrap_or(rts.len());
+        assert!(idx > 0);
+        let (file_rts, rest) = rts.split_at(idx);
+        rts = rest;
+        chunks.push((file, file_rts));
+    }
+    chunks
+}
+
+/// Render the rewrites in `file_rts`, all of which apply to `file`, into a [`FileRewrite`].
+fn render_file_rewrite(file: &SourceFile, file_rts: &[RewriteTree<Span>]) -> FileRewrite {
+    let mut buf = String::new();
+    // Number of newlines in `buf`.
+    let mut buf_line = 0;
+    let mut line_map = LineMapBuilder::default();
+    let mut emit = |s: &str, line| {
+        if let Some(mut line) = line {
+            line_map.record(line, buf_line);
+            for _ in s.matches('\n') {
+                line += 1;
+                buf_line += 1;
+                line_map.record(line, buf_line);
+            }
+        } else {
+            buf_line += s.matches('\n').count();
+        }
+        buf.push_str(s);
+    };
+
+    let mut sink = RewriteTreeSink::new(file, &mut emit);
+    let file_span = Span::new(file.start_pos, file.end_pos, SyntaxContext::root(), None);
+    sink.emit_span_with_rewrites(file_span, file_rts).unwrap();
+
+    FileRewrite {
+        new_src: buf,
+        line_map: line_map.finish(),
+    }
+}
+
 /// Apply rewrites `rws` to the source files covered by their `Span`s.  Returns a map giving the
 /// rewritten source code for each file that contains at least one rewritten `Span`.
+///
+/// Each file's rewrites are independent of every other file's, so once the rewrites are grouped
+/// by file, we render them in parallel across files using `rustc_data_structures::sync`'s
+/// parallel helpers.  Those fall back to sequential execution when rustc itself wasn't built with
+/// the `parallel_compiler` feature, so this is always correct, just not always actually parallel.
 pub fn apply_rewrites(
     source_map: &SourceMap,
     rws: Vec<(Span, Rewrite)>,
@@ -672,50 +744,77 @@ pub fn apply_rewrites(
         );
     }
 
-    let mut file_rewrites = HashMap::new();
-    let mut rts = &rts as &[RewriteTree<Span>];
-    while !rts.is_empty() {
-        let file = source_map.lookup_source_file(rts[0].span.lo());
-        let idx = rts
-            .iter()
-            .position(|rt| rt.span.lo() >= file.end_pos)
-            .unwrap_or(rts.len());
-        assert!(idx > 0);
-        let (file_rts, rest) = rts.split_at(idx);
-        rts = rest;
+    let chunks = split_by_file(source_map, &rts);
+    let file_rewrites = Lock::new(HashMap::new());
+    par_for_each_in(chunks, |(file, file_rts)| {
+        let rewrite = render_file_rewrite(&file, file_rts);
+        file_rewrites.lock().insert(file.name.clone(), rewrite);
+    });
+    file_rewrites.into_inner()
+}
 
-        let mut buf = String::new();
-        // Number of newlines in `buf`.
-        let mut buf_line = 0;
-        let mut line_map = LineMapBuilder::default();
-        let mut emit = |s: &str, line| {
-            if let Some(mut line) = line {
-                line_map.record(line, buf_line);
-                for _ in s.matches('\n') {
-                    line += 1;
-                    buf_line += 1;
-                    line_map.record(line, buf_line);
-                }
-            } else {
-                buf_line += s.matches('\n').count();
-            }
-            buf.push_str(s);
-        };
-
-        let mut sink = RewriteTreeSink::new(&file, &mut emit);
-        let file_span = Span::new(file.start_pos, file.end_pos, SyntaxContext::root(), None);
-        sink.emit_span_with_rewrites(file_span, file_rts).unwrap();
-
-        file_rewrites.insert(
-            file.name.clone(),
-            FileRewrite {
-                new_src: buf,
-                line_map: line_map.finish(),
-            },
+/// Render a single top-level [`RewriteTree`] node's replacement text in isolation, the same way
+/// [`render_file_rewrite`] would render it as part of a whole file, but without splicing it into
+/// any surrounding source.  `rt.children` (nested rewrites, if any) are still rendered inline.
+fn render_node_standalone(file: &SourceFile, rt: &RewriteTree) -> String {
+    let mut buf = String::new();
+    let mut emit = |s: &str, _line: Option<usize>| buf.push_str(s);
+    let mut sink = RewriteTreeSink::new(file, &mut emit);
+    sink.with_rt(rt, |slf| slf.emit_expr()).unwrap();
+    buf
+}
+
+/// Like [`apply_rewrites`], but instead of splicing every rewrite into a combined whole-file
+/// source, return each outermost rewrite's `Span` paired with just the replacement text for that
+/// span.  This is the granularity a "replace this span with this text" suggestion (e.g. a
+/// machine-applicable compiler suggestion, see
+/// [`crate::rewrite::emit_machine_applicable_suggestions`]) needs; a rewrite nested inside a
+/// larger one is rendered as part of its parent's replacement text rather than as its own entry,
+/// matching how `apply_rewrites` treats nested rewrites.
+pub fn collect_top_level_rewrites(
+    source_map: &SourceMap,
+    rws: Vec<(Span, Rewrite)>,
+) -> Vec<(Span, String)> {
+    let (rts, errs) = RewriteTree::build(rws);
+    for (span, rw, err) in errs {
+        warn!(
+            "{:?}: warning: failed to apply rewrite {:?}: {:?}",
+            span, rw, err
+        );
+    }
+
+    let chunks = split_by_file(source_map, &rts);
+    let mut out = Vec::new();
+    for (file, file_rts) in chunks {
+        for rt in file_rts {
+            out.push((rt.span, render_node_standalone(&file, rt)));
+        }
+    }
+    out
+}
+
+/// Render `rws` (all of which must apply within `span`) as a standalone string, rather than
+/// splicing the result into a whole file's source as [`apply_rewrites`] does.  Used by
+/// [`crate::rewrite::dual_impl`] to obtain the rewritten text of a single function in isolation.
+pub(crate) fn render_standalone(
+    source_map: &SourceMap,
+    span: Span,
+    rws: Vec<(Span, Rewrite)>,
+) -> String {
+    let (rts, errs) = RewriteTree::build(rws);
+    for (span, rw, err) in errs {
+        warn!(
+            "{:?}: warning: failed to apply rewrite {:?}: {:?}",
+            span, rw, err
         );
     }
 
-    file_rewrites
+    let file = source_map.lookup_source_file(span.lo());
+    let mut buf = String::new();
+    let mut emit = |s: &str, _line: Option<usize>| buf.push_str(s);
+    let mut sink = RewriteTreeSink::new(&file, &mut emit);
+    sink.emit_span_with_rewrites(span, &rts).unwrap();
+    buf
 }
 
 #[cfg(test)]
@@ -0,0 +1,43 @@
+//! Support for emitting a feature-gated "dual implementation" of a function, for functions whose
+//! rewrite changes behavior in a way that's hard to validate statically (e.g. switching a direct
+//! write to a `Cell::set`, or adding a bounds check to a pointer walk that previously trusted the
+//! caller).  Rather than rewriting the function in place, we keep the original body available
+//! under `#[cfg(not(feature = "c2rust_safe"))]` and place the rewritten body alongside it under
+//! `#[cfg(feature = "c2rust_safe")]`, so a team can build and run both versions of the crate and
+//! compare them before deleting the original.
+//!
+//! This only swaps in a whole-function replacement; it doesn't update callers, and it doesn't
+//! declare the `c2rust_safe` feature for the rewritten crate -- that has to be added to the
+//! target crate's own `Cargo.toml` by whoever enables this.  It also only duplicates the `fn`
+//! item's own span, so an item-level attribute written above the function (such as
+//! `#[no_mangle]`) won't be duplicated onto the `c2rust_safe` copy; functions that need such an
+//! attribute to keep working aren't good candidates for this mode.
+//!
+//! Opted into per-function via [`crate::analyze::get_dual_impl_defs`]
+//! (`C2RUST_ANALYZE_DUAL_IMPL_LIST`); everything else is rewritten normally.
+use super::apply;
+use super::Rewrite;
+use rustc_span::source_map::SourceMap;
+use rustc_span::Span;
+
+/// Build a single [`Rewrite::Text`] that replaces `fn_span` (the `fn ... { ... }` item) with two
+/// copies of it behind opposite `cfg(feature = "c2rust_safe")` gates: the original source
+/// unchanged, and the same span with `rws` (the rewrites that would otherwise have been applied
+/// to it) rendered in.
+pub fn build_dual_impl_rewrite(
+    source_map: &SourceMap,
+    fn_span: Span,
+    rws: Vec<(Span, Rewrite)>,
+) -> Rewrite {
+    let original = source_map.span_to_snippet(fn_span).unwrap_or_else(|e| {
+        panic!(
+            "failed to extract original source for {:?}: {:?}",
+            fn_span, e
+        )
+    });
+    let rewritten = apply::render_standalone(source_map, fn_span, rws);
+    Rewrite::Text(format!(
+        "#[cfg(not(feature = \"c2rust_safe\"))]\n{}\n\n#[cfg(feature = \"c2rust_safe\")]\n{}",
+        original, rewritten,
+    ))
+}
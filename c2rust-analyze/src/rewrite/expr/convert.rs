@@ -12,7 +12,7 @@ use rustc_hir::{ExprKind, HirId};
 use rustc_middle::hir::nested_filter;
 use rustc_middle::ty::adjustment::{Adjust, Adjustment, AutoBorrow, PointerCast};
 use rustc_middle::ty::print::{FmtPrinter, Print};
-use rustc_middle::ty::{Ty, TyCtxt, TyKind, TypeckResults};
+use rustc_middle::ty::{Ty, TyCtxt, TyKind, TypeckResults, UintTy};
 use rustc_span::Span;
 use std::cell::RefCell;
 use std::collections::{HashMap, HashSet};
@@ -66,8 +66,29 @@ impl<'tcx> ConvertVisitor<'tcx> {
     /// Get subexpression `idx` of `ex`.  Panics if the index is out of range for `ex`.  The
     /// precise meaning of the index depends on the expression kind.
     fn get_subexpr(&self, ex: &'tcx hir::Expr<'tcx>, idx: usize) -> Rewrite {
+        let sub_ex = self.get_subexpr_hir(ex, idx);
+        let rw_sub = Rewrite::Sub(idx, sub_ex.span);
+        if let Some(child_span_rw) = self.rewrites.get(&sub_ex.hir_id) {
+            let child_rw = &child_span_rw.1;
+            if let Some(subst_rw) = child_rw.try_subst(&rw_sub) {
+                debug!(
+                    "get_subexpr: substituted {rw_sub:?} into {child_rw:?}, producing {subst_rw:?}"
+                );
+                self.subsumed_child_rewrites
+                    .borrow_mut()
+                    .insert(sub_ex.hir_id);
+                return subst_rw;
+            }
+        }
+        rw_sub
+    }
+
+    /// Get subexpression `idx` of `ex` as a HIR node, for cases (like
+    /// [`offset_index_to_usize_rewrite`]) that need the subexpression's type rather than just a
+    /// [`Rewrite`] for it.  Panics if the index is out of range for `ex`.
+    fn get_subexpr_hir(&self, ex: &'tcx hir::Expr<'tcx>, idx: usize) -> &'tcx hir::Expr<'tcx> {
         use hir::ExprKind::*;
-        let sub_ex = match (&ex.kind, idx) {
+        match (&ex.kind, idx) {
             (&Box(e), 0) => e,
             (&Array(es), i) => &es[i],
             (&Call(_, args), i) => &args[i],
@@ -103,21 +124,37 @@ impl<'tcx> ConvertVisitor<'tcx> {
             (&Repeat(e, _), 0) => e,
             (&Yield(e, _), 0) => e,
             _ => panic!("bad subexpression index {} for {:?}", idx, ex),
-        };
-        let rw_sub = Rewrite::Sub(idx, sub_ex.span);
-        if let Some(child_span_rw) = self.rewrites.get(&sub_ex.hir_id) {
-            let child_rw = &child_span_rw.1;
-            if let Some(subst_rw) = child_rw.try_subst(&rw_sub) {
-                debug!(
-                    "get_subexpr: substituted {rw_sub:?} into {child_rw:?}, producing {subst_rw:?}"
-                );
-                self.subsumed_child_rewrites
-                    .borrow_mut()
-                    .insert(sub_ex.hir_id);
-                return subst_rw;
-            }
         }
-        rw_sub
+    }
+
+    /// Build the `Rewrite` that converts an `OffsetSlice`-family rewrite's index operand (the
+    /// subexpression at `idx` of `ex`) to `usize`, the type required for indexing.  C offsets are
+    /// usually `int`/`long`, which can be negative (UB to offset with in the first place, but C
+    /// code sometimes does it anyway) or, on a 32-bit target, wider than `usize`; blindly emitting
+    /// `x as usize` silently wraps a negative offset into a huge index instead of panicking the
+    /// way an analogous bounds violation elsewhere in the rewritten code would. For any index type
+    /// that can hold a value `usize` can't represent, emit `usize::try_from(x).unwrap()` instead,
+    /// so the out-of-range case panics at the conversion instead of silently wrapping.
+    fn offset_index_to_usize_rewrite(&self, ex: &'tcx hir::Expr<'tcx>, idx: usize) -> Rewrite {
+        let idx_rw = self.get_subexpr(ex, idx);
+        let idx_ty = self.typeck_results.expr_ty(self.get_subexpr_hir(ex, idx));
+        if ty_fits_in_usize(idx_ty) {
+            Rewrite::Cast(
+                Box::new(idx_rw),
+                Box::new(Rewrite::Print("usize".to_owned())),
+            )
+        } else {
+            debug!(
+                "offset index at {:?} has type {:?}, which doesn't always fit in `usize`; \
+                 emitting a checked `try_from` instead of `as usize`",
+                ex.span, idx_ty
+            );
+            Rewrite::MethodCall(
+                "unwrap".to_string(),
+                Box::new(Rewrite::Call("usize::try_from".to_string(), vec![idx_rw])),
+                vec![],
+            )
+        }
     }
 
     fn rewrite_from_mir_rw(
@@ -138,11 +175,8 @@ impl<'tcx> ConvertVisitor<'tcx> {
             mir_op::RewriteKind::OffsetSlice { mutbl } => {
                 // `p.offset(i)` -> `&p[i as usize ..]`
                 assert!(matches!(hir_rw, Rewrite::Identity));
-                let arr = self.get_subexpr(ex, 0);
-                let idx = Rewrite::Cast(
-                    Box::new(self.get_subexpr(ex, 1)),
-                    Box::new(Rewrite::Print("usize".to_owned())),
-                );
+                let arr = simplify_reborrowed_slice_base(self.get_subexpr(ex, 0));
+                let idx = self.offset_index_to_usize_rewrite(ex, 1);
                 let elem = Rewrite::SliceRange(Box::new(arr), Some(Box::new(idx)), None);
                 Rewrite::Ref(Box::new(elem), mutbl_from_bool(mutbl))
             }
@@ -153,10 +187,7 @@ impl<'tcx> ConvertVisitor<'tcx> {
 
                 // Build let binding
                 let arr = self.get_subexpr(ex, 0);
-                let idx = Rewrite::Cast(
-                    Box::new(self.get_subexpr(ex, 1)),
-                    Box::new(Rewrite::Print("usize".to_owned())),
-                );
+                let idx = self.offset_index_to_usize_rewrite(ex, 1);
                 let rw_let = Rewrite::Let(vec![("arr".into(), arr), ("idx".into(), idx)]);
                 let arr = Rewrite::Text("arr".into());
                 let idx = Rewrite::Text("idx".into());
@@ -171,6 +202,32 @@ impl<'tcx> ConvertVisitor<'tcx> {
                 Rewrite::Block(vec![rw_let], Some(Box::new(call)))
             }
 
+            mir_op::RewriteKind::OffsetSliceGet { mutbl } => {
+                // `p.offset(i)` -> `p.get(i as usize ..)` / `p.get_mut(i as usize ..)`
+                assert!(matches!(hir_rw, Rewrite::Identity));
+                let arr = simplify_reborrowed_slice_base(self.get_subexpr(ex, 0));
+                let idx = self.offset_index_to_usize_rewrite(ex, 1);
+                let range = Rewrite::RangeFrom(Box::new(idx));
+                let method = if mutbl { "get_mut" } else { "get" };
+                Rewrite::MethodCall(method.into(), Box::new(arr), vec![range])
+            }
+
+            mir_op::RewriteKind::OffsetSliceUnchecked { mutbl } => {
+                // `p.offset(i)` -> `unsafe { p.get_unchecked(i as usize ..) }` /
+                // `unsafe { p.get_unchecked_mut(i as usize ..) }`
+                assert!(matches!(hir_rw, Rewrite::Identity));
+                let arr = simplify_reborrowed_slice_base(self.get_subexpr(ex, 0));
+                let idx = self.offset_index_to_usize_rewrite(ex, 1);
+                let range = Rewrite::RangeFrom(Box::new(idx));
+                let method = if mutbl {
+                    "get_unchecked_mut"
+                } else {
+                    "get_unchecked"
+                };
+                let call = Rewrite::MethodCall(method.into(), Box::new(arr), vec![range]);
+                Rewrite::Unsafe(Box::new(call))
+            }
+
             mir_op::RewriteKind::RemoveAsPtr => {
                 // `slice.as_ptr()` -> `slice`
                 assert!(matches!(hir_rw, Rewrite::Identity));
@@ -223,6 +280,25 @@ impl<'tcx> ConvertVisitor<'tcx> {
                 Rewrite::Text("None".into())
             }
 
+            mir_op::RewriteKind::PtrEq { negate } => {
+                // `p == q` -> `core::ptr::eq(&*p, &*q)`; `p != q` -> `!core::ptr::eq(&*p, &*q)`
+                assert!(matches!(hir_rw, Rewrite::Identity));
+                let lhs = Rewrite::Ref(
+                    Box::new(Rewrite::Deref(Box::new(self.get_subexpr(ex, 0)))),
+                    hir::Mutability::Not,
+                );
+                let rhs = Rewrite::Ref(
+                    Box::new(Rewrite::Deref(Box::new(self.get_subexpr(ex, 1)))),
+                    hir::Mutability::Not,
+                );
+                let call = Rewrite::Call("core::ptr::eq".to_string(), vec![lhs, rhs]);
+                if negate {
+                    Rewrite::Not(Box::new(call))
+                } else {
+                    call
+                }
+            }
+
             mir_op::RewriteKind::MemcpySafe {
                 ref elem_ty,
                 dest_single,
@@ -352,44 +428,117 @@ impl<'tcx> ConvertVisitor<'tcx> {
                 ref zero_ty,
                 ref elem_ty,
                 single,
+                size_arg_idx,
+            } => {
+                // `malloc(n)` (or a custom allocator wrapper) -> `Box::new(z)` or similar
+                assert!(matches!(hir_rw, Rewrite::Identity));
+                let zeroize_expr = generate_zeroize_expr(zero_ty);
+                let mut stmts = vec![
+                    Rewrite::Let(vec![(
+                        "byte_len".into(),
+                        self.get_subexpr(ex, size_arg_idx),
+                    )]),
+                    // Best-effort check to detect size mismatches, as in `MemcpySafe`.
+                    format_rewrite!(
+                        "assert_eq!(byte_len as usize % std::mem::size_of::<{elem_ty}>(), 0)"
+                    ),
+                    Rewrite::Let1(
+                        "n".into(),
+                        Box::new(format_rewrite!(
+                            "byte_len as usize / std::mem::size_of::<{elem_ty}>()"
+                        )),
+                    ),
+                ];
+                let expr = if single {
+                    stmts.push(Rewrite::Text("assert_eq!(n, 1)".into()));
+                    format_rewrite!("Box::new({})", zeroize_expr)
+                } else {
+                    stmts.push(Rewrite::Let1(
+                        "mut v".into(),
+                        Box::new(Rewrite::Text("Vec::with_capacity(n)".into())),
+                    ));
+                    stmts.push(format_rewrite!(
+                        "for i in 0..n {{\n    v.push({});\n}}",
+                        zeroize_expr,
+                    ));
+                    Rewrite::Text("v.into_boxed_slice()".into())
+                };
+                Rewrite::Block(stmts, Some(Box::new(expr)))
+            }
+
+            mir_op::RewriteKind::MallocUninit {
+                ref elem_ty,
+                single,
+                size_arg_idx,
+            } => {
+                // `malloc(n)` (or a custom allocator wrapper) -> `Box::new(MaybeUninit::uninit())`,
+                // for a pointee type we don't know how to zero-initialize.  The allocation stays
+                // typed as `MaybeUninit<T>` (never `assume_init`), since asserting the memory is
+                // initialized would itself be UB for a type like this that can have invalid bit
+                // patterns.  This is only emitted when `C2RUST_ANALYZE_MALLOC_NEW_UNINIT=1` is
+                // set; see `RewriteKind::MallocUninit`.
+                assert!(matches!(hir_rw, Rewrite::Identity));
+                let uninit_expr = format!("std::mem::MaybeUninit::<{elem_ty}>::uninit()");
+                let mut stmts = vec![
+                    Rewrite::Let(vec![(
+                        "byte_len".into(),
+                        self.get_subexpr(ex, size_arg_idx),
+                    )]),
+                    // Best-effort check to detect size mismatches, as in `MemcpySafe`.
+                    format_rewrite!(
+                        "assert_eq!(byte_len as usize % std::mem::size_of::<{elem_ty}>(), 0)"
+                    ),
+                    Rewrite::Let1(
+                        "n".into(),
+                        Box::new(format_rewrite!(
+                            "byte_len as usize / std::mem::size_of::<{elem_ty}>()"
+                        )),
+                    ),
+                ];
+                let expr = if single {
+                    stmts.push(Rewrite::Text("assert_eq!(n, 1)".into()));
+                    format_rewrite!("Box::new({})", uninit_expr)
+                } else {
+                    stmts.push(Rewrite::Let1(
+                        "mut v".into(),
+                        Box::new(Rewrite::Text("Vec::with_capacity(n)".into())),
+                    ));
+                    stmts.push(format_rewrite!(
+                        "for i in 0..n {{\n    v.push({});\n}}",
+                        uninit_expr,
+                    ));
+                    Rewrite::Text("v.into_boxed_slice()".into())
+                };
+                Rewrite::Block(stmts, Some(Box::new(expr)))
             }
-            | mir_op::RewriteKind::CallocSafe {
+
+            mir_op::RewriteKind::CallocSafe {
                 ref zero_ty,
                 ref elem_ty,
                 single,
             } => {
-                // `malloc(n)` -> `Box::new(z)` or similar
+                // `calloc(n, size)` -> `Box::new(z)` or similar
                 assert!(matches!(hir_rw, Rewrite::Identity));
                 let zeroize_expr = generate_zeroize_expr(zero_ty);
-                let mut stmts = match *rw {
-                    mir_op::RewriteKind::MallocSafe { .. } => vec![
-                        Rewrite::Let(vec![("byte_len".into(), self.get_subexpr(ex, 0))]),
-                        // Best-effort check to detect size mismatches, as in `MemcpySafe`.
-                        format_rewrite!(
-                            "assert_eq!(byte_len as usize % std::mem::size_of::<{elem_ty}>(), 0)"
-                        ),
-                        Rewrite::Let1(
-                            "n".into(),
-                            Box::new(format_rewrite!(
-                                "byte_len as usize / std::mem::size_of::<{elem_ty}>()"
-                            )),
-                        ),
-                    ],
-                    mir_op::RewriteKind::CallocSafe { .. } => vec![
-                        Rewrite::Let(vec![
-                            ("count".into(), self.get_subexpr(ex, 0)),
-                            ("size".into(), self.get_subexpr(ex, 1)),
-                        ]),
-                        format_rewrite!(
-                            "assert_eq!(size as usize, std::mem::size_of::<{elem_ty}>())"
-                        ),
-                        Rewrite::Let1("n".into(), Box::new(format_rewrite!("count as usize"))),
-                    ],
-                    _ => unreachable!(),
-                };
+                let mut stmts = vec![
+                    Rewrite::Let(vec![
+                        ("count".into(), self.get_subexpr(ex, 0)),
+                        ("size".into(), self.get_subexpr(ex, 1)),
+                    ]),
+                    format_rewrite!("assert_eq!(size as usize, std::mem::size_of::<{elem_ty}>())"),
+                    Rewrite::Let1("n".into(), Box::new(format_rewrite!("count as usize"))),
+                ];
                 let expr = if single {
                     stmts.push(Rewrite::Text("assert_eq!(n, 1)".into()));
                     format_rewrite!("Box::new({})", zeroize_expr)
+                } else if matches!(
+                    zero_ty,
+                    ZeroizeType::Int | ZeroizeType::Bool | ZeroizeType::Option
+                ) {
+                    // For the common case where every element is zeroized by storing the same
+                    // literal, the idiomatic `vec![x; n]` macro says the same thing as the
+                    // general push-loop below, without needing one.
+                    format_rewrite!("vec![{}; n].into_boxed_slice()", zeroize_expr)
                 } else {
                     stmts.push(Rewrite::Let1(
                         "mut v".into(),
@@ -688,6 +837,19 @@ impl<'tcx> Visitor<'tcx> for ConvertVisitor<'tcx> {
     }
 }
 
+/// Can every value of `ty` be represented as a `usize` on every target this crate's output might
+/// run on?  Conservative: besides `usize` itself, only the unsigned integer types no wider than 16
+/// bits are always in range (`usize` is at least 16 bits per the reference), so this says `false`
+/// for `u32`/`u64`/`u128` even though some of those are in range on common 32-/64-bit targets,
+/// and for every signed type, whose negative values never fit, since getting this wrong in the
+/// "yes, it fits" direction would reintroduce the silent-wraparound bug this check exists to avoid.
+fn ty_fits_in_usize(ty: Ty) -> bool {
+    matches!(
+        ty.kind(),
+        TyKind::Uint(UintTy::U8 | UintTy::U16 | UintTy::Usize)
+    )
+}
+
 fn mutbl_from_bool(m: bool) -> hir::Mutability {
     if m {
         hir::Mutability::Mut
@@ -825,6 +987,46 @@ fn generate_zeroize_expr(zero_ty: &ZeroizeType) -> String {
     }
 }
 
+/// Simplify `&(&arr[i..])[0]` to `&arr[i]`.  This pattern arises from composing `OffsetSlice`
+/// (applied to a nested `p.offset(i)`) with `SliceFirst` (applied to the enclosing `*...`), which
+/// is exactly what happens for the common C array-indexing idiom `arr[i]` once it has been
+/// transpiled to pointer arithmetic.  The two rewrites are individually correct, but stacking them
+/// produces a much less readable result than a human translating the same code would write, so we
+/// detect the composed shape here and emit a single `Index` instead.
+///
+/// This only fires when the offset and the dereference are part of the same source expression
+/// (so that `hir_rw` is literally the `OffsetSlice` rewrite, rather than, say, a variable that
+/// happens to hold the offset pointer); a pointer stored in a local and dereferenced later, as in
+/// a manual `p++` loop cursor, keeps using the general slice-range rewrite.
+fn fold_offset_slice_first(rw: Rewrite) -> Result<Rewrite, Rewrite> {
+    let elem = match rw {
+        Rewrite::Ref(ref elem, _) => elem,
+        _ => return Err(rw),
+    };
+    if !matches!(**elem, Rewrite::SliceRange(_, Some(_), None)) {
+        return Err(rw);
+    }
+    let (arr, idx) = assert_matches!(rw, Rewrite::Ref(elem, _) =>
+        assert_matches!(*elem, Rewrite::SliceRange(arr, Some(idx), None) => (arr, idx)));
+    Ok(Rewrite::Index(arr, idx))
+}
+
+/// Drop a redundant reborrow (`&*p` / `&mut *p`) from the base of an `Offset*`-family slice-range
+/// index. This arises when composing a chained `as_ptr()`/`as_mut_ptr()` call into `.offset(i)`:
+/// if the two calls disagree on mutability, the `as_ptr` call's own cast resolves to a `Reborrow`
+/// (see `RewriteKind::Reborrow`) before this function ever sees it, so without this the combined
+/// rewrite would read `&mut (&mut *v)[i as usize ..]` instead of the `&mut v[i as usize ..]` a
+/// human would write for `v.as_mut_ptr().offset(i)`. The reborrow and the plain base behave
+/// identically under indexing (both auto-deref the same way), so this is a pure readability
+/// simplification, not a semantic change.
+fn simplify_reborrowed_slice_base(rw: Rewrite) -> Rewrite {
+    if !matches!(&rw, Rewrite::Ref(elem, _) if matches!(**elem, Rewrite::Deref(_))) {
+        return rw;
+    }
+    assert_matches!(rw, Rewrite::Ref(elem, _) =>
+        assert_matches!(*elem, Rewrite::Deref(inner) => *inner))
+}
+
 fn take_prefix_while<'a, T>(slice: &mut &'a [T], mut pred: impl FnMut(&'a T) -> bool) -> &'a [T] {
     let i = slice.iter().position(|x| !pred(x)).unwrap_or(slice.len());
     let (a, b) = slice.split_at(i);
@@ -838,11 +1040,17 @@ fn take_prefix_while<'a, T>(slice: &mut &'a [T], mut pred: impl FnMut(&'a T) ->
 pub fn convert_cast_rewrite(kind: &mir_op::RewriteKind, hir_rw: Rewrite) -> Rewrite {
     match *kind {
         mir_op::RewriteKind::SliceFirst { mutbl } => {
-            // `p` -> `&p[0]`
-            let arr = hir_rw;
-            let idx = Rewrite::LitZero;
-            let elem = Rewrite::Index(Box::new(arr), Box::new(idx));
-            Rewrite::Ref(Box::new(elem), mutbl_from_bool(mutbl))
+            // `p` -> `&p[0]`, or `p.offset(i)` -> `&p[i]` when `hir_rw` is itself the `OffsetSlice`
+            // rewrite for the pointer being dereferenced (see `fold_offset_slice_first`).
+            match fold_offset_slice_first(hir_rw) {
+                Ok(elem) => Rewrite::Ref(Box::new(elem), mutbl_from_bool(mutbl)),
+                Err(hir_rw) => {
+                    let arr = hir_rw;
+                    let idx = Rewrite::LitZero;
+                    let elem = Rewrite::Index(Box::new(arr), Box::new(idx));
+                    Rewrite::Ref(Box::new(elem), mutbl_from_bool(mutbl))
+                }
+            }
         }
 
         mir_op::RewriteKind::Reborrow { mutbl } => {
@@ -860,6 +1068,15 @@ pub fn convert_cast_rewrite(kind: &mir_op::RewriteKind, hir_rw: Rewrite) -> Rewr
             Rewrite::Deref(Box::new(hir_rw))
         }
 
+        mir_op::RewriteKind::IntoRawBox => {
+            // `b` -> `Box::into_raw(b)`
+            Rewrite::Call("std::boxed::Box::into_raw".to_string(), vec![hir_rw])
+        }
+        mir_op::RewriteKind::FromRawBox => {
+            // `p` -> `Box::from_raw(p)`
+            Rewrite::Call("std::boxed::Box::from_raw".to_string(), vec![hir_rw])
+        }
+
         mir_op::RewriteKind::OptionUnwrap => {
             // `p` -> `p.unwrap()`
             Rewrite::MethodCall("unwrap".to_string(), Box::new(hir_rw), vec![])
@@ -914,6 +1131,10 @@ pub fn convert_cast_rewrite(kind: &mir_op::RewriteKind, hir_rw: Rewrite) -> Rewr
         mir_op::RewriteKind::DynOwnedUnwrap => {
             Rewrite::MethodCall("unwrap".to_string(), Box::new(hir_rw), vec![])
         }
+        mir_op::RewriteKind::OptionTake => {
+            // `p` -> `p.take()`, for `q = p; p = NULL;`
+            Rewrite::MethodCall("take".to_string(), Box::new(hir_rw), vec![])
+        }
         mir_op::RewriteKind::DynOwnedTake => {
             // `p` -> `mem::replace(&mut p, Err(()))`
             Rewrite::Call(
@@ -953,6 +1174,18 @@ pub fn convert_cast_rewrite(kind: &mir_op::RewriteKind, hir_rw: Rewrite) -> Rewr
             Rewrite::Ref(Box::new(rw_pl), mutbl_from_bool(mutbl))
         }
 
+        mir_op::RewriteKind::OffsetRawUnsafe => {
+            // `p.offset(i)` -> `unsafe { p.offset(i) } /* TODO(c2rust): ... */`.  `hir_rw` here
+            // is the `.offset(...)` call as already rewritten below this point (e.g. its
+            // receiver may have gotten an `as_ptr()`/`cast_mut()` from an earlier `TypeDesc`
+            // cast) -- we only add the `unsafe` wrapper and trailing comment around it.
+            let wrapped = Rewrite::Unsafe(Box::new(hir_rw));
+            Rewrite::Commented(
+                Box::new(wrapped),
+                crate::rewrite::OFFSET_RAW_UNSAFE_COMMENT.to_string(),
+            )
+        }
+
         mir_op::RewriteKind::CellNew => {
             // `x` to `Cell::new(x)`
             Rewrite::Call("std::cell::Cell::new".to_string(), vec![hir_rw])
@@ -976,6 +1209,13 @@ pub fn convert_cast_rewrite(kind: &mir_op::RewriteKind, hir_rw: Rewrite) -> Rewr
                 hir::Mutability::Not,
             )),
         ),
+        mir_op::RewriteKind::CastBytePointee { ref to_ty, mutbl } => Rewrite::Cast(
+            Box::new(hir_rw),
+            Box::new(Rewrite::TyPtr(
+                Box::new(Rewrite::Print(to_ty.to_string())),
+                mutbl_from_bool(mutbl),
+            )),
+        ),
 
         _ => panic!(
             "rewrite {:?} is not supported by convert_cast_rewrite",
@@ -1053,3 +1293,111 @@ pub fn convert_rewrites(
         .map(|(_, (span, rw))| (span, rw))
         .collect()
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::rewrite::expr::mir_op::{OptionDowngradeKind, RewriteKind};
+
+    /// Convert `kind` as if rewriting the variable `var`, and render the result back to source
+    /// text.  This lets us test cast emission directly against `RewriteKind`s, without needing an
+    /// end-to-end rustc run to produce a real HIR expression to rewrite.
+    fn render(kind: RewriteKind, var: &str) -> String {
+        convert_cast_rewrite(&kind, Rewrite::Text(var.to_string())).to_string()
+    }
+
+    #[test]
+    fn deref() {
+        assert_eq!(render(RewriteKind::Deref, "p"), "*p");
+    }
+
+    #[test]
+    fn reborrow() {
+        assert_eq!(render(RewriteKind::Reborrow { mutbl: false }, "p"), "&*p");
+        assert_eq!(
+            render(RewriteKind::Reborrow { mutbl: true }, "p"),
+            "&mut *p"
+        );
+    }
+
+    #[test]
+    fn slice_first() {
+        assert_eq!(
+            render(RewriteKind::SliceFirst { mutbl: false }, "s"),
+            "&s[0]"
+        );
+        assert_eq!(
+            render(RewriteKind::SliceFirst { mutbl: true }, "s"),
+            "&mut s[0]"
+        );
+    }
+
+    #[test]
+    fn slice_first_of_offset_slice() {
+        // Simulate the `hir_rw` produced by `OffsetSlice` for `p.offset(i)`, and check that
+        // `SliceFirst` folds the composed rewrite for `*p.offset(i)` down to `&p[i]` instead of
+        // `&(&p[i..])[0]`.
+        let offset_rw = Rewrite::Ref(
+            Box::new(Rewrite::SliceRange(
+                Box::new(Rewrite::Text("p".to_string())),
+                Some(Box::new(Rewrite::Text("i".to_string()))),
+                None,
+            )),
+            hir::Mutability::Not,
+        );
+        assert_eq!(
+            convert_cast_rewrite(&RewriteKind::SliceFirst { mutbl: false }, offset_rw).to_string(),
+            "&p[i]"
+        );
+    }
+
+    #[test]
+    fn option_unwrap_and_some() {
+        assert_eq!(render(RewriteKind::OptionUnwrap, "p"), "p.unwrap()");
+        assert_eq!(
+            render(RewriteKind::OptionSome, "p"),
+            "std::option::Option::Some(p)"
+        );
+    }
+
+    #[test]
+    fn option_downgrade() {
+        assert_eq!(
+            render(
+                RewriteKind::OptionDowngrade {
+                    mutbl: false,
+                    kind: OptionDowngradeKind::Borrow,
+                },
+                "p"
+            ),
+            "p.as_ref()"
+        );
+        assert_eq!(
+            render(
+                RewriteKind::OptionDowngrade {
+                    mutbl: true,
+                    kind: OptionDowngradeKind::Deref,
+                },
+                "p"
+            ),
+            "p.as_deref_mut()"
+        );
+    }
+
+    #[test]
+    fn cell_new() {
+        assert_eq!(render(RewriteKind::CellNew, "x"), "std::cell::Cell::new(x)");
+    }
+
+    #[test]
+    fn cast_raw_to_raw() {
+        assert_eq!(
+            render(RewriteKind::CastRawToRaw { to_mutbl: false }, "p"),
+            "p.cast_const()"
+        );
+        assert_eq!(
+            render(RewriteKind::CastRawToRaw { to_mutbl: true }, "p"),
+            "p.cast_mut()"
+        );
+    }
+}
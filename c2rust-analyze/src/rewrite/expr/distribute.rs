@@ -134,7 +134,7 @@ pub fn distribute(
         for mir_rw in mir_rws {
             let key = PreciseLoc {
                 loc,
-                sub: mir_rw.sub_loc,
+                sub: mir_rw.sub_loc.into(),
             };
 
             let origin = match unlower_map.get(&key) {
@@ -13,18 +13,20 @@ use crate::panic_detail;
 use crate::pointee_type::PointeeTypes;
 use crate::pointer_id::{GlobalPointerTable, PointerId, PointerTable};
 use crate::rewrite;
-use crate::type_desc::{self, Ownership, Quantity, TypeDesc};
+use crate::rewrite::RewritePlan;
+use crate::type_desc::{self, Ownership, PtrDesc, Quantity, TypeDesc};
 use crate::util::{self, ty_callee, Callee};
 use log::{debug, error, trace};
 use rustc_ast::Mutability;
 use rustc_middle::mir::{
-    BasicBlock, Body, BorrowKind, Location, Operand, Place, PlaceElem, PlaceRef, Rvalue, Statement,
-    StatementKind, Terminator, TerminatorKind,
+    AggregateKind, BasicBlock, BinOp, Body, BorrowKind, Local, Location, Operand, Place, PlaceElem,
+    PlaceRef, Rvalue, Statement, StatementKind, Terminator, TerminatorKind,
 };
 use rustc_middle::ty::print::{FmtPrinter, PrettyPrinter, Print};
-use rustc_middle::ty::{Ty, TyCtxt, TyKind};
-use std::collections::HashMap;
+use rustc_middle::ty::{IntTy, Ty, TyCtxt, TyKind, UintTy};
+use std::collections::{HashMap, HashSet};
 use std::ops::Index;
+use std::panic::AssertUnwindSafe;
 
 use rustc_hir::def::Namespace;
 
@@ -57,10 +59,33 @@ pub enum RewriteKind {
     OffsetSlice { mutbl: bool },
     /// Replace `ptr.offset(i)` with something like `ptr.as_ref().map(|p| &p[i..])`.
     OptionMapOffsetSlice { mutbl: bool },
+    /// Replace `ptr.offset(i)` with `ptr.get(i..)`/`ptr.get_mut(i..)`, per
+    /// [`OffsetBoundsMode::Option`](crate::util::OffsetBoundsMode::Option).  Unlike
+    /// [`OffsetSlice`](Self::OffsetSlice), out-of-bounds access produces `None` instead of
+    /// panicking; the caller is responsible for handling the `Option`.
+    OffsetSliceGet { mutbl: bool },
+    /// Replace `ptr.offset(i)` with `unsafe { ptr.get_unchecked(i..) }`/`get_unchecked_mut`, per
+    /// [`OffsetBoundsMode::Unchecked`](crate::util::OffsetBoundsMode::Unchecked).  Skips the
+    /// bounds check entirely; out-of-bounds access is undefined behavior.
+    OffsetSliceUnchecked { mutbl: bool },
+    /// Leave `ptr.offset(i)` itself as a raw pointer operation, but wrap it in `unsafe { .. }`
+    /// with a trailing comment explaining why, instead of falling back to leaving the entire
+    /// enclosing function unconverted.  Used for cases like a statically-known negative offset
+    /// that none of the other `Offset*` rewrites can express (see `visit_ptr_offset`).  The
+    /// operand is still cast to a raw pointer beforehand via the usual `TypeDesc` cast machinery,
+    /// so this only localizes the *unsafety*, not the raw-pointer-ness, of the expression.
+    OffsetRawUnsafe,
     /// Replace `slice` with `&slice[0]`.
     SliceFirst { mutbl: bool },
     /// Replace `ptr` with `&*ptr` or `&mut *ptr`, converting `ptr` to `&T` or `&mut T`.
     Reborrow { mutbl: bool },
+    /// Replace `b` with `Box::into_raw(b)`, transferring ownership out of a `Box` into a raw
+    /// pointer, for use where the pointer must cross into code that stays raw (an extern call or
+    /// a `FIXED`-pinned field).
+    IntoRawBox,
+    /// Replace `p` with `Box::from_raw(p)`, reclaiming `Box` ownership of a raw pointer that came
+    /// from [`IntoRawBox`](RewriteKind::IntoRawBox) or an equivalent raw allocation.
+    FromRawBox,
     /// Remove a call to `as_ptr` or `as_mut_ptr`.
     RemoveAsPtr,
     /// Remove a cast, changing `x as T` to just `x`.
@@ -80,6 +105,15 @@ pub enum RewriteKind {
     PtrNullToNone,
     /// Replace `0 as *const T` or `0 as *mut T` with `None`.
     ZeroAsPtrToNone,
+    /// Replace `ptr` with `ptr.take()`, for the common C idiom `q = p; p = NULL;`, which transfers
+    /// ownership out of `p` rather than aliasing `q` and `p`.
+    OptionTake,
+    /// Replace `p == q` with `core::ptr::eq(&*p, &*q)`, or `p != q` with `!core::ptr::eq(&*p,
+    /// &*q)`.  This is needed when `p` and `q` are converted from raw pointers into references,
+    /// `Box`, or `Rc`: unlike `==` on raw pointers, `==` on those safe types forwards to
+    /// `PartialEq` on the pointee and compares contents rather than addresses, which would
+    /// silently change the meaning of a pointer-identity check from the original C.
+    PtrEq { negate: bool },
 
     /// Replace a call to `memcpy(dest, src, n)` with a safe copy operation that works on slices
     /// instead of raw pointers.  `elem_ty` is the pointee type type, whose size is used to convert
@@ -101,12 +135,35 @@ pub enum RewriteKind {
         dest_single: bool,
     },
 
-    /// Replace a call to `malloc(n)` with a safe `Box::new` operation.  The new allocation will be
-    /// zero-initialized.
+    /// Replace a call to `malloc(n)` (or a custom allocator wrapper, see [`Callee::CustomMalloc`])
+    /// with a safe `Box::new` operation.  The new allocation will be zero-initialized.
+    /// `size_arg_idx` is the argument index of the size, 0 for `malloc` itself.
     MallocSafe {
         zero_ty: ZeroizeType,
         elem_ty: String,
         single: bool,
+        size_arg_idx: usize,
+    },
+    /// Like [`MallocSafe`](Self::MallocSafe), but for a pointee type we don't know how to
+    /// zero-initialize (`ZeroizeType::from_lty` returned `None`).  Instead of leaving the call
+    /// unconverted, allocate with `Box::new`/a `Vec` push loop around `MaybeUninit::uninit()`,
+    /// matching `malloc`'s own contract of leaving the memory uninitialized rather than silently
+    /// giving it C semantics Rust can't express. The allocation's element type stays
+    /// `MaybeUninit<T>` (i.e. this produces `Box<MaybeUninit<T>>`/`Box<[MaybeUninit<T>]>`) -- we
+    /// never call `assume_init()`, since doing that on genuinely uninitialized memory is UB on
+    /// its own for pointee types with restricted bit patterns (enums, `bool`, references, ...),
+    /// independent of whether anything reads the value before writing it. Only emitted when
+    /// `C2RUST_ANALYZE_MALLOC_NEW_UNINIT=1` is set; it's on the caller to confirm the original C
+    /// code always initializes before reading, since we have no static check for that here.
+    ///
+    /// Declaring the pointer's pointee type as `MaybeUninit<T>` everywhere it's used isn't wired
+    /// up yet, so the surrounding declared type still expects plain `T`/`[T]`; until that's
+    /// fixed, turning this flag on produces a type error at the allocation site rather than
+    /// working code, which is the honest outcome given the alternative is silent UB.
+    MallocUninit {
+        elem_ty: String,
+        single: bool,
+        size_arg_idx: usize,
     },
     /// Replace a call to `free(p)` with a safe `drop` operation.
     FreeSafe { single: bool },
@@ -175,6 +232,12 @@ pub enum RewriteKind {
     CellFromMut,
     /// `x` to `x.as_ptr()`
     AsPtr,
+
+    /// Cast a raw pointer's pointee type between `i8`/`u8`/`c_char`, which differ only in
+    /// signedness and are otherwise bit-for-bit interchangeable.  `to_ty` is the target pointee
+    /// type's name and `mutbl` carries over the original pointer's mutability, e.g. `p as *mut
+    /// u8`.  See [`CastBuilder::normalize_byte_pointees`].
+    CastBytePointee { to_ty: String, mutbl: bool },
 }
 
 #[derive(Clone, Copy, PartialEq, Eq, Debug)]
@@ -253,6 +316,7 @@ struct ExprRewriteVisitor<'a, 'tcx> {
     acx: &'a AnalysisCtxt<'a, 'tcx>,
     perms: &'a GlobalPointerTable<PermissionSet>,
     flags: &'a GlobalPointerTable<FlagSet>,
+    plan: &'a RewritePlan,
     pointee_types: PointerTable<'a, PointeeTypes<'tcx>>,
     last_use: &'a LastUse,
     rewrites: &'a mut HashMap<Location, Vec<MirRewrite>>,
@@ -260,12 +324,18 @@ struct ExprRewriteVisitor<'a, 'tcx> {
     loc: Location,
     sub_loc: Vec<SubLoc>,
     errors: DontRewriteFnReason,
+    /// Locals that are the target of a MIR `Drop`/`DropAndReplace` terminator somewhere in this
+    /// function.  Such a terminator means the local's value is (or may be) dropped by ordinary
+    /// Rust drop glue, separately from any `free` call we rewrite.  We use this to avoid emitting
+    /// a redundant `FreeSafe` rewrite that would double-free the same allocation.
+    dropped_locals: HashSet<Local>,
 }
 
 impl<'a, 'tcx> ExprRewriteVisitor<'a, 'tcx> {
     pub fn new(
         acx: &'a AnalysisCtxt<'a, 'tcx>,
         asn: &'a Assignment,
+        plan: &'a RewritePlan,
         pointee_types: PointerTable<'a, PointeeTypes<'tcx>>,
         last_use: &'a LastUse,
         rewrites: &'a mut HashMap<Location, Vec<MirRewrite>>,
@@ -277,6 +347,7 @@ impl<'a, 'tcx> ExprRewriteVisitor<'a, 'tcx> {
             acx,
             perms,
             flags,
+            plan,
             pointee_types,
             last_use,
             rewrites,
@@ -287,6 +358,7 @@ impl<'a, 'tcx> ExprRewriteVisitor<'a, 'tcx> {
             },
             sub_loc: Vec::new(),
             errors: DontRewriteFnReason::empty(),
+            dropped_locals: HashSet::new(),
         }
     }
 
@@ -364,6 +436,50 @@ impl<'a, 'tcx> ExprRewriteVisitor<'a, 'tcx> {
             && !self.flags[ptr].contains(FlagSet::FIXED)
     }
 
+    /// Detect the idiom `pl = rv_place; rv_place = <null>;` (two adjacent statements), which is
+    /// how transpiled C code moves ownership out of a pointer variable.  Returns `rv_place` if
+    /// `pl` and `rv_place` are both inferred to be nullable (so they'll both become `Option<_>`
+    /// after rewriting), in which case the pair should become `let pl = rv_place.take();` instead
+    /// of two separate assignments that would otherwise alias `pl` and `rv_place`.
+    fn detect_option_take(
+        &self,
+        rv: &Rvalue<'tcx>,
+        pl_lty: LTy<'tcx>,
+        loc: Location,
+    ) -> Option<Place<'tcx>> {
+        let rv_op = match rv {
+            Rvalue::Use(op) => op,
+            _ => return None,
+        };
+        let rv_pl = rv_op.place()?;
+        if !self.is_nullable(pl_lty.label) {
+            return None;
+        }
+        if !self.is_nullable(self.acx.type_of(rv_pl).label) {
+            return None;
+        }
+
+        let block = &self.mir.basic_blocks()[loc.block];
+        let next_stmt = block.statements.get(loc.statement_index + 1)?;
+        let (next_pl, next_rv) = match &next_stmt.kind {
+            StatementKind::Assign(x) => (x.0, &x.1),
+            _ => return None,
+        };
+        if next_pl != rv_pl {
+            return None;
+        }
+        let sets_null = match next_rv {
+            Rvalue::Use(op) => util::is_null_const_operand(op),
+            Rvalue::Cast(_, op, ty) => ty.is_unsafe_ptr() && util::is_null_const_operand(op),
+            _ => false,
+        };
+        if !sets_null {
+            return None;
+        }
+
+        Some(rv_pl)
+    }
+
     fn is_dyn_owned(&self, lty: LTy) -> bool {
         if !matches!(lty.kind(), TyKind::Ref(..) | TyKind::RawPtr(..)) {
             return false;
@@ -462,29 +578,51 @@ impl<'a, 'tcx> ExprRewriteVisitor<'a, 'tcx> {
 
                 let pl_lty = self.acx.type_of(pl);
 
-                // FIXME: Needs changes to handle CELL pointers in struct fields.  Suppose `pl` is
-                // something like `*(_1.0)`, where the `.0` field is CELL.  This should be
-                // converted to a `Cell::get` call, but we would fail to enter this case because
-                // `_1` fails the `is_any_ptr()` check.
-                if pl.is_indirect() && self.acx.local_tys[pl.local].ty.is_any_ptr() {
-                    let local_lty = self.acx.local_tys[pl.local];
-                    let local_ptr = local_lty.label;
-                    let perms = self.perms[local_ptr];
-                    let flags = self.flags[local_ptr];
-                    if !flags.contains(FlagSet::FIXED) {
-                        let desc = type_desc::perms_to_desc(local_lty.ty, perms, flags);
-                        if desc.own == Ownership::Cell {
-                            if pl.projection.len() > 1 || desc.qty != Quantity::Single {
-                                // NYI: `Cell` inside structs, arrays, or ptr-to-ptr
-                                self.err(DontRewriteFnReason::COMPLEX_CELL);
+                // Find the pointer that's actually dereferenced to produce `pl`: every
+                // projection up to (but not including) the last `Deref`.  For a plain pointer
+                // local like `*x`, that's just `_1` itself; for a pointer stored in a struct
+                // field like `*(_1.0)`, it's the `_1.0` place, whose `LTy` `local_tys[pl.local]`
+                // alone can't give us, since `_1` (a struct) fails `is_any_ptr()` even though the
+                // pointer we actually care about is one of its fields.
+                if let Some(deref_idx) = pl
+                    .projection
+                    .iter()
+                    .rposition(|p| matches!(p, PlaceElem::Deref))
+                {
+                    let ptr_prefix = &pl.projection[..deref_idx];
+                    let ptr_pl = PlaceRef {
+                        local: pl.local,
+                        projection: ptr_prefix,
+                    };
+                    let local_lty = self.acx.type_of(ptr_pl);
+                    if local_lty.ty.is_any_ptr() {
+                        let local_ptr = local_lty.label;
+                        let perms = self.perms[local_ptr];
+                        let flags = self.flags[local_ptr];
+                        if !flags.contains(FlagSet::FIXED) {
+                            let desc = type_desc::perms_to_desc(local_lty.ty, perms, flags);
+                            if desc.own == Ownership::Cell {
+                                // Beyond the final `Deref`, `pl` must have no further
+                                // projections (no `(*x).field`-style access into the Cell's
+                                // contents), and locating the pointer itself must only have
+                                // walked through plain struct fields (no arrays/indices).
+                                let deref_is_last = deref_idx + 1 == pl.projection.len();
+                                let prefix_is_fields =
+                                    ptr_prefix.iter().all(|p| matches!(p, PlaceElem::Field(..)));
+                                if !deref_is_last
+                                    || !prefix_is_fields
+                                    || desc.qty != Quantity::Single
+                                {
+                                    // NYI: `Cell` inside structs, arrays, or ptr-to-ptr
+                                    self.err(DontRewriteFnReason::COMPLEX_CELL);
+                                }
+                                // this is an assignment like `*x = 2` but `x` has CELL permissions
+                                self.emit(RewriteKind::CellSet);
                             }
-                            // this is an assignment like `*x = 2` but `x` has CELL permissions
-                            self.emit(RewriteKind::CellSet);
                         }
                     }
                 }
 
-                #[allow(clippy::single_match)]
                 match rv {
                     Rvalue::Use(rv_op) => {
                         let local_ty = self.acx.local_tys[pl.local].ty;
@@ -502,24 +640,69 @@ impl<'a, 'tcx> ExprRewriteVisitor<'a, 'tcx> {
                         }
 
                         if let Some(rv_place) = rv_op.place() {
-                            if rv_place.is_indirect()
-                                && self.acx.local_tys[rv_place.local].ty.is_any_ptr()
+                            // As above, walk `rv_place`'s own projection to find the pointer
+                            // that's dereferenced, rather than assuming `rv_place.local` itself
+                            // is the pointer -- it might instead be a struct holding the pointer
+                            // in one of its fields.
+                            if let Some(deref_idx) = rv_place
+                                .projection
+                                .iter()
+                                .rposition(|p| matches!(p, PlaceElem::Deref))
                             {
-                                let local_lty = self.acx.local_tys[rv_place.local];
-                                let local_ptr = local_lty.label;
-                                let flags = self.flags[local_ptr];
-                                if !flags.contains(FlagSet::FIXED) && flags.contains(FlagSet::CELL)
-                                {
-                                    // this is an assignment like `let x = *y` but `y` has CELL permissions
-                                    if pl.projection.len() > 1 || desc.qty != Quantity::Single {
-                                        // NYI: `Cell` inside structs, arrays, or ptr-to-ptr
-                                        self.err(DontRewriteFnReason::COMPLEX_CELL);
+                                let ptr_prefix = &rv_place.projection[..deref_idx];
+                                let ptr_pl = PlaceRef {
+                                    local: rv_place.local,
+                                    projection: ptr_prefix,
+                                };
+                                let local_lty = self.acx.type_of(ptr_pl);
+                                if local_lty.ty.is_any_ptr() {
+                                    let local_ptr = local_lty.label;
+                                    let flags = self.flags[local_ptr];
+                                    if !flags.contains(FlagSet::FIXED)
+                                        && flags.contains(FlagSet::CELL)
+                                    {
+                                        // this is an assignment like `let x = *y` but `y` has CELL permissions
+                                        let deref_is_last =
+                                            deref_idx + 1 == rv_place.projection.len();
+                                        let prefix_is_fields = ptr_prefix
+                                            .iter()
+                                            .all(|p| matches!(p, PlaceElem::Field(..)));
+                                        if !deref_is_last
+                                            || !prefix_is_fields
+                                            || desc.qty != Quantity::Single
+                                        {
+                                            // NYI: `Cell` inside structs, arrays, or ptr-to-ptr
+                                            self.err(DontRewriteFnReason::COMPLEX_CELL);
+                                        }
+                                        self.enter_rvalue(|v| v.emit(RewriteKind::CellGet))
                                     }
-                                    self.enter_rvalue(|v| v.emit(RewriteKind::CellGet))
                                 }
                             }
                         }
                     }
+                    Rvalue::Discriminant(rv_place) => {
+                        // Mirrors the `let x = *y` case above: reading an enum's discriminant
+                        // through a pointer is still a read through that pointer, so it needs the
+                        // same `Cell::get` treatment if `y` ends up CELL.  `Rvalue::Discriminant`
+                        // isn't wrapped in an `Operand`, so `rv_place` is available directly
+                        // instead of going through `rv_op.place()`.
+                        if rv_place.is_indirect()
+                            && self.acx.local_tys[rv_place.local].ty.is_any_ptr()
+                        {
+                            let local_lty = self.acx.local_tys[rv_place.local];
+                            let local_ptr = local_lty.label;
+                            let flags = self.flags[local_ptr];
+                            if !flags.contains(FlagSet::FIXED) && flags.contains(FlagSet::CELL) {
+                                // this is a discriminant read like `match *y { .. }` but `y` has
+                                // CELL permissions
+                                if rv_place.projection.len() > 1 {
+                                    // NYI: `Cell` inside structs, arrays, or ptr-to-ptr
+                                    self.err(DontRewriteFnReason::COMPLEX_CELL);
+                                }
+                                self.enter_rvalue(|v| v.emit(RewriteKind::CellGet))
+                            }
+                        }
+                    }
                     _ => {}
                 };
 
@@ -583,6 +766,21 @@ impl<'a, 'tcx> ExprRewriteVisitor<'a, 'tcx> {
                         return;
                     }
 
+                    // Special case: `pl = rv_place; rv_place = <null>;` is the idiom transpiled C
+                    // code uses to transfer ownership out of `rv_place`.  Emit `rv_place.take()`
+                    // instead of a plain copy, so `pl` and `rv_place` don't end up aliasing once
+                    // both become `Option<_>`.
+                    if let Some(rv_pl) = v.detect_option_take(rv, pl_lty, loc) {
+                        v.enter_rvalue_operand(0, |v| {
+                            v.enter_operand_place(|v| {
+                                v.visit_place(rv_pl, PlaceAccess::Mut, RequireSinglePointer::No);
+                            });
+                        });
+                        v.emit(RewriteKind::OptionTake);
+                        v.emit_cast_lty_lty(rv_lty, pl_lty, cast_can_move);
+                        return;
+                    }
+
                     // Normal case: just `visit_rvalue` and emit a cast if needed.
                     v.visit_rvalue(rv, Some(rv_lty));
                     v.emit_cast_lty_lty_or_borrow(rv_lty, pl_lty, cast_can_move)
@@ -590,14 +788,28 @@ impl<'a, 'tcx> ExprRewriteVisitor<'a, 'tcx> {
                 self.enter_dest(|v| v.visit_place(pl, PlaceAccess::Mut, RequireSinglePointer::Yes));
             }
             StatementKind::FakeRead(..) => {}
-            StatementKind::SetDiscriminant { .. } => todo!("statement {:?}", stmt),
+            StatementKind::SetDiscriminant { .. } => {
+                // NYI: no rewrite rule for `SetDiscriminant` yet.  Record this rather than
+                // panicking, so the rest of this function's analysis still runs to completion.
+                error!("unsupported statement {:?}", stmt);
+                self.err(DontRewriteFnReason::UNSUPPORTED_CONSTRUCT);
+            }
             StatementKind::Deinit(..) => {}
             StatementKind::StorageLive(..) => {}
             StatementKind::StorageDead(..) => {}
             StatementKind::Retag(..) => {}
             StatementKind::AscribeUserType(..) => {}
             StatementKind::Coverage(..) => {}
-            StatementKind::CopyNonOverlapping(..) => todo!("statement {:?}", stmt),
+            StatementKind::CopyNonOverlapping(..) => {
+                // NYI: no rewrite rule for `copy_nonoverlapping` yet.  Unlike `memcpy`/`memmove`,
+                // calls to `ptr::copy`/`ptr::copy_nonoverlapping`/`ptr::write_bytes` are lowered
+                // by MIR building straight into this statement form rather than remaining a
+                // `TerminatorKind::Call`, so the `Callee`-based dispatch in `visit_terminator`
+                // never sees them and a rewrite here would need its own statement-level cast and
+                // argument-visiting machinery, not just a new `RewriteKind`.
+                error!("unsupported statement {:?}", stmt);
+                self.err(DontRewriteFnReason::UNSUPPORTED_CONSTRUCT);
+            }
             StatementKind::Nop => {}
         }
     }
@@ -615,6 +827,9 @@ impl<'a, 'tcx> ExprRewriteVisitor<'a, 'tcx> {
             TerminatorKind::Abort => {}
             TerminatorKind::Return => {}
             TerminatorKind::Unreachable => {}
+            // These are accounted for up front in `dropped_locals` (see `collect_dropped_locals`),
+            // which `Callee::Free` consults to avoid emitting a rewrite that would double-free an
+            // allocation also dropped here by ordinary Rust drop glue.
             TerminatorKind::Drop { .. } => {}
             TerminatorKind::DropAndReplace { .. } => {}
             TerminatorKind::Call {
@@ -630,7 +845,7 @@ impl<'a, 'tcx> ExprRewriteVisitor<'a, 'tcx> {
                 // Special cases for particular functions.
                 match ty_callee(tcx, func_ty) {
                     Callee::PtrOffset { .. } => {
-                        self.visit_ptr_offset(&args[0], pl_ty);
+                        self.visit_ptr_offset(&args[0], &args[1], pl_ty);
                     }
                     Callee::SliceAsPtr { elem_ty, .. } => {
                         self.visit_slice_as_ptr(elem_ty, &args[0], pl_ty);
@@ -645,11 +860,13 @@ impl<'a, 'tcx> ExprRewriteVisitor<'a, 'tcx> {
                                         v.enter_call_arg(i, |v| v.visit_operand(op, Some(lty)));
                                     } else {
                                         // This is a call to a variadic function, and we've gone
-                                        // past the end of the declared arguments.
-                                        // TODO: insert a cast to turn `op` back into its original
-                                        // declared type (i.e. upcast the chosen reference type
-                                        // back to a raw pointer)
-                                        continue;
+                                        // past the end of the declared arguments, so there's no
+                                        // `LTy` to cast to here.  But if some other rewrite turned
+                                        // this argument's pointer into a reference, it still needs
+                                        // a cast: a variadic argument is passed with its original
+                                        // declared type, which for any pointer is a raw pointer,
+                                        // never `&T`/`&mut T`/`&[T]`.
+                                        v.enter_call_arg(i, |v| v.cast_variadic_arg_to_raw(op));
                                     }
                                 }
 
@@ -662,7 +879,7 @@ impl<'a, 'tcx> ExprRewriteVisitor<'a, 'tcx> {
                         }
                     }
 
-                    Callee::Memcpy => {
+                    Callee::Memcpy | Callee::Memmove => {
                         self.enter_rvalue(|v| {
                             // TODO: Only emit `MemcpySafe` if the rewritten argument types and
                             // pointees are suitable.  Specifically, the `src` and `dest` arguments
@@ -777,15 +994,25 @@ impl<'a, 'tcx> ExprRewriteVisitor<'a, 'tcx> {
                         });
                     }
 
-                    ref callee @ (Callee::Malloc | Callee::Calloc) => {
+                    ref
+                    callee @ (Callee::Malloc | Callee::Calloc | Callee::CustomMalloc { .. }) => {
                         self.enter_rvalue(|v| {
                             let dest_lty = v.acx.type_of(destination);
+                            // `pointee_lty` consults `pointee_types`, which the constraint-based
+                            // pointee-type analysis (`pointee_type::type_check`) populates from
+                            // every use of `destination`'s `PointerId`, including uses reached
+                            // only through a later `as` cast (`Rvalue::Cast` assigns the cast
+                            // result's uses back onto its operand).  So a cast-free `malloc` into
+                            // a `void*`-typed local that's cast to `T*` before use already gets
+                            // its pointee type inferred here; what's left unresolved is a `malloc`
+                            // result that's never used at any concrete pointee type at all.
                             let dest_pointee = v.pointee_lty(dest_lty);
                             let pointee_lty = match dest_pointee {
                                 Some(x) => x,
                                 // TODO: emit void* cast before bailing out
                                 None => {
                                     trace!("{callee:?}: no pointee type for dest");
+                                    v.err(DontRewriteFnReason::UNSUPPORTED_CONSTRUCT);
                                     return;
                                 }
                             };
@@ -796,35 +1023,62 @@ impl<'a, 'tcx> ExprRewriteVisitor<'a, 'tcx> {
 
                             let opt_zero_ty =
                                 ZeroizeType::from_lty(v.acx, v.perms, v.flags, pointee_lty);
-                            let zero_ty = match opt_zero_ty {
-                                Some(x) => x,
+                            let size_arg_idx = match *callee {
+                                Callee::CustomMalloc { size_arg_idx } => size_arg_idx,
+                                _ => 0,
+                            };
+                            let rw = match opt_zero_ty {
+                                Some(zero_ty) => match *callee {
+                                    Callee::Malloc | Callee::CustomMalloc { .. } => {
+                                        RewriteKind::MallocSafe {
+                                            zero_ty,
+                                            elem_ty,
+                                            single,
+                                            size_arg_idx,
+                                        }
+                                    }
+                                    Callee::Calloc => RewriteKind::CallocSafe {
+                                        zero_ty,
+                                        elem_ty,
+                                        single,
+                                    },
+                                    _ => unreachable!(),
+                                },
+                                // `calloc` is contractually zero-initializing, so there's no
+                                // uninit fallback for it -- only `malloc`/`CustomMalloc` leave
+                                // the memory's initial contents unspecified in the first place.
+                                None if !matches!(*callee, Callee::Calloc)
+                                    && std::env::var_os("C2RUST_ANALYZE_MALLOC_NEW_UNINIT")
+                                        .is_some() =>
+                                {
+                                    RewriteKind::MallocUninit {
+                                        elem_ty,
+                                        single,
+                                        size_arg_idx,
+                                    }
+                                }
                                 // TODO: emit void* cast before bailing out
                                 None => {
                                     trace!(
                                         "{callee:?}: failed to compute ZeroizeType \
                                         for {pointee_lty:?}"
                                     );
+                                    v.err(DontRewriteFnReason::UNSUPPORTED_CONSTRUCT);
                                     return;
                                 }
                             };
-
-                            let rw = match *callee {
-                                Callee::Malloc => RewriteKind::MallocSafe {
-                                    zero_ty,
-                                    elem_ty,
-                                    single,
-                                },
-                                Callee::Calloc => RewriteKind::CallocSafe {
-                                    zero_ty,
-                                    elem_ty,
-                                    single,
-                                },
-                                _ => unreachable!(),
-                            };
                             v.emit(rw);
 
-                            // `MallocSafe` produces either `Box<T>` or `Box<[T]>`.  Emit a cast
-                            // from that type to the required output type.
+                            // `MallocSafe`/`CallocSafe` produce `Box<T>`/`Box<[T]>`; `MallocUninit`
+                            // produces `Box<MaybeUninit<T>>`/`Box<[MaybeUninit<T>]>` instead (see
+                            // its doc comment for why it can't just be `Box<T>`/`Box<[T]>`). This
+                            // cast still targets `dest_lty`'s plain `T`/`[T]`-shaped declared type
+                            // either way: `rewrite::ty` doesn't yet know to declare `MallocUninit`
+                            // pointees as `MaybeUninit<T>`, so for now the `MallocUninit` case
+                            // intentionally emits code that fails to *compile* (a visible, loud
+                            // error) rather than code that silently calls `assume_init()` on
+                            // uninitialized memory. Fixing that mismatch requires teaching
+                            // `rewrite::ty` about `MaybeUninit`-wrapped pointees.
                             v.emit_cast_adjust_lty(
                                 |desc| TypeDesc {
                                     own: Ownership::Box,
@@ -842,7 +1096,18 @@ impl<'a, 'tcx> ExprRewriteVisitor<'a, 'tcx> {
                         });
                     }
 
-                    Callee::Free => {
+                    Callee::Free | Callee::CustomFree => {
+                        // If the freed place's local is also dropped by ordinary Rust drop glue
+                        // elsewhere in this function (see `dropped_locals`), rewriting this call
+                        // to `drop(p)` as well would double-free the same allocation.  Leave the
+                        // call as a plain `free` in that case.
+                        if let Some(pl) = args[0].place() {
+                            if self.dropped_locals.contains(&pl.local) {
+                                self.err(DontRewriteFnReason::UNSUPPORTED_CONSTRUCT);
+                                return;
+                            }
+                        }
+
                         self.enter_rvalue(|v| {
                             let src_lty = v.acx.type_of(&args[0]);
                             let src_pointee = v.pointee_lty(src_lty);
@@ -973,7 +1238,11 @@ impl<'a, 'tcx> ExprRewriteVisitor<'a, 'tcx> {
             TerminatorKind::GeneratorDrop => {}
             TerminatorKind::FalseEdge { .. } => {}
             TerminatorKind::FalseUnwind { .. } => {}
-            TerminatorKind::InlineAsm { .. } => todo!("terminator {:?}", term),
+            TerminatorKind::InlineAsm { .. } => {
+                // NYI: no rewrite rule for inline asm yet.
+                error!("unsupported terminator {:?}", term);
+                self.err(DontRewriteFnReason::UNSUPPORTED_CONSTRUCT);
+            }
         }
     }
 
@@ -1036,6 +1305,22 @@ impl<'a, 'tcx> ExprRewriteVisitor<'a, 'tcx> {
                 self.enter_rvalue_place(0, |v| {
                     v.visit_place(pl, PlaceAccess::Imm, RequireSinglePointer::No)
                 });
+
+                // `Rvalue::Len` only ever applies to an already-array/slice-typed place, so
+                // `visit_place` above can't have changed its `Ownership`/`Quantity` -- the only
+                // rewrite that can affect `pl` here is a nullability change.  If `pl` becomes
+                // `Option<&[T]>`, the existing `pl.len()` call no longer type-checks (`Option`
+                // has no `.len()`), and there's currently no rewrite that turns it into
+                // `pl.map_or(0, |s| s.len())` or otherwise keeps the length read in sync with the
+                // slice's nullability, so bail out rather than emit code that won't compile.
+                //
+                // A manual C length variable that's unified with a converted slice's `.len()`
+                // (the other half of this request) isn't tracked by any existing analysis in
+                // this crate, so that case also isn't handled here.
+                let pl_lty = self.acx.type_of(pl);
+                if pl_lty.label != PointerId::NONE && self.is_nullable(pl_lty.label) {
+                    self.err(DontRewriteFnReason::UNSUPPORTED_CONSTRUCT);
+                }
             }
             Rvalue::Cast(_kind, ref op, ty) => {
                 if util::is_null_const_operand(op) && ty.is_unsafe_ptr() {
@@ -1081,26 +1366,64 @@ impl<'a, 'tcx> ExprRewriteVisitor<'a, 'tcx> {
                     }
                 }
             }
-            Rvalue::BinaryOp(_bop, ref ops) => {
+            Rvalue::BinaryOp(bop, ref ops) => {
                 self.enter_rvalue_operand(0, |v| v.visit_operand(&ops.0, None));
                 self.enter_rvalue_operand(1, |v| v.visit_operand(&ops.1, None));
+                self.emit_ptr_eq_if_needed(bop, &ops.0, &ops.1);
             }
-            Rvalue::CheckedBinaryOp(_bop, ref ops) => {
+            Rvalue::CheckedBinaryOp(bop, ref ops) => {
                 self.enter_rvalue_operand(0, |v| v.visit_operand(&ops.0, None));
                 self.enter_rvalue_operand(1, |v| v.visit_operand(&ops.1, None));
+                self.emit_ptr_eq_if_needed(bop, &ops.0, &ops.1);
             }
             Rvalue::NullaryOp(..) => {}
             Rvalue::UnaryOp(_uop, ref op) => {
                 self.enter_rvalue_operand(0, |v| v.visit_operand(op, None));
             }
             Rvalue::Discriminant(pl) => {
+                // `visit_place` here handles any pointer derefs on the path to `pl` (e.g. a
+                // `Box`/`Rc`/`Option` downgrade), the same as every other place read.  The
+                // `Cell::get` rewrite for reading an enum's tag through a CELL-flagged pointer is
+                // handled separately, alongside the analogous `Rvalue::Use` case, in
+                // `visit_statement`'s `Assign` arm.
                 self.enter_rvalue_place(0, |v| {
                     v.visit_place(pl, PlaceAccess::Imm, RequireSinglePointer::No)
                 });
             }
-            Rvalue::Aggregate(ref _kind, ref ops) => {
+            Rvalue::Aggregate(ref kind, ref ops) => {
+                // For struct construction (`Foo { p: raw_ptr, .. }`), look up each field's
+                // rewritten `LTy` so the operand gets cast to match, the same as an assignment to
+                // an existing field would.  Other aggregate kinds (tuples, arrays, closures,
+                // generators) aren't covered: tuple/array fields all alias the same `PointerId`
+                // as their parent's `lty.args`, which `visit_operand`'s normal unification
+                // already keeps in sync, so there's no expected-type mismatch to fix there.
+                let field_expect_tys: Option<Vec<LTy<'tcx>>> = match (&**kind, expect_ty) {
+                    (AggregateKind::Adt(adt_did, variant_idx, substs, _, _), Some(expect_ty)) => {
+                        let adt_def = self.acx.tcx().adt_def(*adt_did);
+                        let variant = adt_def.variant(*variant_idx);
+                        Some(
+                            variant
+                                .fields
+                                .iter()
+                                .enumerate()
+                                .map(|(field_idx, field)| {
+                                    let field_ty = field.ty(self.acx.tcx(), *substs);
+                                    self.acx.projection_lty(
+                                        expect_ty,
+                                        &PlaceElem::Field(field_idx.into(), field_ty),
+                                    )
+                                })
+                                .collect(),
+                        )
+                    }
+                    _ => None,
+                };
                 for (i, op) in ops.iter().enumerate() {
-                    self.enter_rvalue_operand(i, |v| v.visit_operand(op, None));
+                    let field_expect_ty = field_expect_tys
+                        .as_ref()
+                        .and_then(|ltys| ltys.get(i))
+                        .copied();
+                    self.enter_rvalue_operand(i, |v| v.visit_operand(op, field_expect_ty));
                 }
             }
             Rvalue::ShallowInitBox(ref op, _ty) => {
@@ -1114,6 +1437,47 @@ impl<'a, 'tcx> ExprRewriteVisitor<'a, 'tcx> {
         }
     }
 
+    /// If `bop` is `Eq`/`Ne` and either operand is a pointer whose ownership will change from raw
+    /// into something that compares by value (`&T`, `&mut T`, `Box<T>`, `Rc<T>`, or `&Cell<T>`),
+    /// emit [`RewriteKind::PtrEq`] so the comparison keeps comparing addresses instead of silently
+    /// becoming a derived `PartialEq` comparison of the pointees.
+    fn emit_ptr_eq_if_needed(&mut self, bop: BinOp, op0: &Operand<'tcx>, op1: &Operand<'tcx>) {
+        if !matches!(bop, BinOp::Eq | BinOp::Ne) {
+            return;
+        }
+        if !self.cmp_becomes_value_eq(op0) && !self.cmp_becomes_value_eq(op1) {
+            return;
+        }
+        self.emit(RewriteKind::PtrEq {
+            negate: matches!(bop, BinOp::Ne),
+        });
+    }
+
+    /// Check whether `op` is a pointer that will be rewritten into an ownership kind whose `==`
+    /// forwards to `PartialEq` on the pointee rather than comparing addresses.
+    fn cmp_becomes_value_eq(&self, op: &Operand<'tcx>) -> bool {
+        let lty = self.acx.type_of(op);
+        if lty.label.is_none() || !lty.ty.is_any_ptr() {
+            return false;
+        }
+        if self.flags[lty.label].contains(FlagSet::FIXED) {
+            // Pinned to its original raw-pointer type, so `==` still compares addresses.
+            return false;
+        }
+        let desc = type_desc::perms_to_desc(lty.ty, self.perms[lty.label], self.flags[lty.label]);
+        if desc.option {
+            // TODO: `Option<&T>`/`Option<Box<T>>` identity comparisons need something like
+            // `a.map(|r| r as *const _) == b.map(|r| r as *const _)` rather than a direct
+            // `core::ptr::eq` call, which doesn't accept `Option`.  Leave these unrewritten for
+            // now rather than emit an incorrect cast.
+            return false;
+        }
+        matches!(
+            desc.own,
+            Ownership::Imm | Ownership::Mut | Ownership::Box | Ownership::Rc | Ownership::Cell
+        )
+    }
+
     /// Visit an `Operand`.  If `expect_ty` is `Some`, also emit whatever casts are necessary to
     /// make the `Operand` produce a value of type `expect_ty`.
     fn visit_operand(&mut self, op: &Operand<'tcx>, expect_ty: Option<LTy<'tcx>>) {
@@ -1138,8 +1502,57 @@ impl<'a, 'tcx> ExprRewriteVisitor<'a, 'tcx> {
                     }
                 }
             }
-            Operand::Constant(..) => {}
+            Operand::Constant(..) => {
+                // `acx.type_of` already resolves the address of a `static` to the `PointerId` we
+                // assigned that static, so it's a real pointer like any other -- just one that's
+                // never the target of a `Place`, so there's no `visit_place`/move-tracking to do.
+                // A function item/function pointer constant, on the other hand, always gets
+                // `PointerId::NONE` here (`label_no_pointers` doesn't track pointers inside a
+                // function's signature), so there's nothing to cast for those.
+                let op_lty = self.acx.type_of(op);
+                if let Some(expect_ty) = expect_ty {
+                    if !op_lty.label.is_none() {
+                        self.emit_cast_lty_lty(op_lty, expect_ty, false);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Visit an `Operand` passed as a variadic argument, past the end of the callee's declared
+    /// parameters.  There's no `LTy` for such an argument to cast to, but it still needs to end
+    /// up with its original raw-pointer type if some other rewrite already turned it into a
+    /// reference -- a variadic argument's ABI is fixed at its original C declared type, and C
+    /// never declared it `&T`.  The mutability of the raw pointer we cast back to follows the
+    /// same `WRITE` permission the rest of this pass already uses to choose `Mut` vs `Imm`.
+    fn cast_variadic_arg_to_raw(&mut self, op: &Operand<'tcx>) {
+        let op_lty = self.acx.type_of(op);
+        if op_lty.label.is_none() {
+            self.visit_operand(op, None);
+            return;
         }
+        let pointee_lty = match self.pointee_lty(op_lty) {
+            Some(x) => x,
+            None => {
+                self.visit_operand(op, None);
+                return;
+            }
+        };
+        let own = if self.perms[op_lty.label].contains(PermissionSet::WRITE) {
+            Ownership::RawMut
+        } else {
+            Ownership::Raw
+        };
+        self.visit_operand_desc(
+            op,
+            TypeDesc {
+                own,
+                qty: Quantity::Single,
+                dyn_owned: false,
+                option: false,
+                pointee_ty: pointee_lty.ty,
+            },
+        );
     }
 
     /// Like [`Self::visit_operand`], but takes an expected `TypeDesc` instead of an expected `LTy`.
@@ -1162,7 +1575,15 @@ impl<'a, 'tcx> ExprRewriteVisitor<'a, 'tcx> {
                     self.emit_cast_lty_desc(pl_lty, expect_desc);
                 }
             }
-            Operand::Constant(..) => {}
+            Operand::Constant(..) => {
+                // See the matching arm in `visit_operand` above: only address-of-`static`
+                // constants get a real `PointerId` here, and those are the only ones that need a
+                // cast to reconcile with `expect_desc`.
+                let op_lty = self.acx.type_of(op);
+                if !op_lty.label.is_none() {
+                    self.emit_cast_lty_desc(op_lty, expect_desc);
+                }
+            }
         }
     }
 
@@ -1271,11 +1692,55 @@ impl<'a, 'tcx> ExprRewriteVisitor<'a, 'tcx> {
         }
     }
 
-    fn visit_ptr_offset(&mut self, op: &Operand<'tcx>, result_ty: LTy<'tcx>) {
+    fn visit_ptr_offset(
+        &mut self,
+        op: &Operand<'tcx>,
+        count: &Operand<'tcx>,
+        result_ty: LTy<'tcx>,
+    ) {
         // Compute the expected type for the argument, and emit a cast if needed.
         let result_ptr = result_ty.label;
         let result_desc =
             type_desc::perms_to_desc(result_ty.ty, self.perms[result_ptr], self.flags[result_ptr]);
+        self.plan.check(result_ptr, PtrDesc::from(result_desc));
+
+        // A statically-known negative count (`p.offset(-1)`, common in backward-walking parser
+        // code) can't be expressed with the `OffsetSlice`-family rewrites below, which only know
+        // how to narrow `arr` to `&arr[i..]` for `i >= 0`.  Soundly supporting it with a safe
+        // slice would require retaining a wider base slice that extends before `arr`'s current
+        // start, which isn't tracked here.  Rather than bailing out the whole function, localize
+        // the unsafety to just this expression: cast `op` down to a bare raw pointer (the same
+        // `as_ptr`/`cast_mut` cast that a whole-function raw fallback would've needed anyway),
+        // leave the `.offset(count)` call itself as a raw pointer operation wrapped in
+        // `unsafe { .. }`, then cast the (still raw) result back up to `result_desc`.
+        //
+        // This doesn't (yet) handle the case where `result_desc` itself is `Option`-typed --
+        // unifying a raw pointer offset with `Option`'s niche representation is more involved,
+        // so that case still falls back to the whole-function bailout below.
+        if util::is_negative_const_operand(count) && !result_desc.option {
+            let raw_desc = TypeDesc {
+                own: if matches!(result_desc.own, Ownership::Mut | Ownership::RawMut) {
+                    Ownership::RawMut
+                } else {
+                    Ownership::Raw
+                },
+                qty: Quantity::OffsetPtr,
+                dyn_owned: false,
+                option: false,
+                pointee_ty: result_desc.pointee_ty,
+            };
+            self.enter_rvalue(|v| {
+                v.enter_call_arg(0, |v| v.visit_operand_desc(op, raw_desc));
+                v.emit(RewriteKind::OffsetRawUnsafe);
+                v.emit_cast_desc_desc(raw_desc, result_desc);
+            });
+            return;
+        }
+
+        if util::is_negative_const_operand(count) {
+            self.err(DontRewriteFnReason::UNSUPPORTED_NEGATIVE_OFFSET);
+            return;
+        }
 
         let arg_expect_desc = TypeDesc {
             own: result_desc.own,
@@ -1293,10 +1758,30 @@ impl<'a, 'tcx> ExprRewriteVisitor<'a, 'tcx> {
         self.enter_rvalue(|v| {
             v.enter_call_arg(0, |v| v.visit_operand_desc(op, arg_expect_desc));
 
-            // Emit `OffsetSlice` for the offset itself.
+            // Emit `OffsetSlice` for the offset itself.  If the pointer itself is already
+            // `Option`-typed, we always go through `OptionMapOffsetSlice`, regardless of the
+            // configured `OffsetBoundsMode` -- that mode only selects the bounds-check form used
+            // for non-optional pointers.
             let mutbl = matches!(result_desc.own, Ownership::Mut);
             if !result_desc.option {
-                v.emit(RewriteKind::OffsetSlice { mutbl });
+                let def_id = v.mir.source.def_id();
+                match v.acx.gacx.offset_bounds_mode(def_id) {
+                    util::OffsetBoundsMode::Checked => {
+                        v.emit(RewriteKind::OffsetSlice { mutbl });
+                    }
+                    // NOTE: unlike `Checked`/`Unchecked`, this changes the static type of the
+                    // expression from a reference to an `Option` of a reference.  We don't
+                    // attempt to propagate that change through the rest of the enclosing
+                    // expression or its callers; `OffsetBoundsMode::Option` is only sound to
+                    // select for functions whose surrounding code already expects (or is
+                    // prepared to be updated for) an `Option` result here.
+                    util::OffsetBoundsMode::Option => {
+                        v.emit(RewriteKind::OffsetSliceGet { mutbl });
+                    }
+                    util::OffsetBoundsMode::Unchecked => {
+                        v.emit(RewriteKind::OffsetSliceUnchecked { mutbl });
+                    }
+                }
             } else {
                 v.emit(RewriteKind::OptionMapOffsetSlice { mutbl });
             }
@@ -1346,26 +1831,53 @@ impl<'a, 'tcx> ExprRewriteVisitor<'a, 'tcx> {
             });
     }
 
+    /// Record that a cast failed to build, instead of panicking.  A pointer-to-pointer cast that
+    /// isn't covered by the rewrite rules (such as the nested cast a `T**` out-parameter or
+    /// pointer array would need) is exactly the kind of thing `PTR_TO_PTR_CAST` exists for; this
+    /// leaves the rest of the function's analysis to run to completion instead of aborting it.
+    fn report_cast_failure(&mut self, e: &str) {
+        debug!("failed to build cast: {e}");
+        self.err(DontRewriteFnReason::PTR_TO_PTR_CAST);
+    }
+
     fn emit_cast_desc_desc(&mut self, from: TypeDesc<'tcx>, to: TypeDesc<'tcx>) {
         let perms = self.perms;
         let flags = self.flags;
-        let mut builder = CastBuilder::new(self.acx.tcx(), perms, flags, |rk| self.emit(rk));
-        builder.build_cast_desc_desc(from, to);
+        let normalize_byte_pointees = self.acx.gacx.normalize_byte_pointee_types;
+        let mut builder = CastBuilder::new(self.acx.tcx(), perms, flags, |rk| self.emit(rk))
+            .normalize_byte_pointees(normalize_byte_pointees);
+        let result = builder.try_build_cast_desc_desc(from, to);
+        drop(builder);
+        if let Err(e) = result {
+            self.report_cast_failure(&e);
+        }
     }
 
     fn emit_cast_lty_desc(&mut self, from_lty: LTy<'tcx>, to: TypeDesc<'tcx>) {
         let perms = self.perms;
         let flags = self.flags;
-        let mut builder = CastBuilder::new(self.acx.tcx(), perms, flags, |rk| self.emit(rk));
-        builder.build_cast_lty_desc(from_lty, to);
+        let normalize_byte_pointees = self.acx.gacx.normalize_byte_pointee_types;
+        let mut builder = CastBuilder::new(self.acx.tcx(), perms, flags, |rk| self.emit(rk))
+            .normalize_byte_pointees(normalize_byte_pointees);
+        let result = builder.try_build_cast_lty_desc(from_lty, to);
+        drop(builder);
+        if let Err(e) = result {
+            self.report_cast_failure(&e);
+        }
     }
 
     #[allow(dead_code)]
     fn emit_cast_desc_lty(&mut self, from: TypeDesc<'tcx>, to_lty: LTy<'tcx>) {
         let perms = self.perms;
         let flags = self.flags;
-        let mut builder = CastBuilder::new(self.acx.tcx(), perms, flags, |rk| self.emit(rk));
-        builder.build_cast_desc_lty(from, to_lty);
+        let normalize_byte_pointees = self.acx.gacx.normalize_byte_pointee_types;
+        let mut builder = CastBuilder::new(self.acx.tcx(), perms, flags, |rk| self.emit(rk))
+            .normalize_byte_pointees(normalize_byte_pointees);
+        let result = builder.try_build_cast_desc_lty(from, to_lty);
+        drop(builder);
+        if let Err(e) = result {
+            self.report_cast_failure(&e);
+        }
     }
 
     /// Emit a cast from `from_lty` to `to_lty` at the current `(loc, sub_loc)`.  `is_local` should
@@ -1374,9 +1886,15 @@ impl<'a, 'tcx> ExprRewriteVisitor<'a, 'tcx> {
     fn emit_cast_lty_lty(&mut self, from_lty: LTy<'tcx>, to_lty: LTy<'tcx>, cast_can_move: bool) {
         let perms = self.perms;
         let flags = self.flags;
+        let normalize_byte_pointees = self.acx.gacx.normalize_byte_pointee_types;
         let mut builder = CastBuilder::new(self.acx.tcx(), perms, flags, |rk| self.emit(rk))
-            .can_move(cast_can_move);
-        builder.build_cast_lty_lty(from_lty, to_lty);
+            .can_move(cast_can_move)
+            .normalize_byte_pointees(normalize_byte_pointees);
+        let result = builder.try_build_cast_lty_lty(from_lty, to_lty);
+        drop(builder);
+        if let Err(e) = result {
+            self.report_cast_failure(&e);
+        }
     }
 
     fn emit_cast_lty_lty_or_borrow(
@@ -1387,10 +1905,16 @@ impl<'a, 'tcx> ExprRewriteVisitor<'a, 'tcx> {
     ) {
         let perms = self.perms;
         let flags = self.flags;
+        let normalize_byte_pointees = self.acx.gacx.normalize_byte_pointee_types;
         let mut builder = CastBuilder::new(self.acx.tcx(), perms, flags, |rk| self.emit(rk))
             .can_move(cast_can_move)
-            .borrow(true);
-        builder.build_cast_lty_lty(from_lty, to_lty);
+            .borrow(true)
+            .normalize_byte_pointees(normalize_byte_pointees);
+        let result = builder.try_build_cast_lty_lty(from_lty, to_lty);
+        drop(builder);
+        if let Err(e) = result {
+            self.report_cast_failure(&e);
+        }
     }
 
     /// Cast `from_lty` to an adjusted version of itself.  If `from_desc` is the `TypeDesc`
@@ -1402,8 +1926,14 @@ impl<'a, 'tcx> ExprRewriteVisitor<'a, 'tcx> {
     ) {
         let perms = self.perms;
         let flags = self.flags;
-        let mut builder = CastBuilder::new(self.acx.tcx(), perms, flags, |rk| self.emit(rk));
-        builder.build_cast_lty_adjust(from_lty, to_adjust);
+        let normalize_byte_pointees = self.acx.gacx.normalize_byte_pointee_types;
+        let mut builder = CastBuilder::new(self.acx.tcx(), perms, flags, |rk| self.emit(rk))
+            .normalize_byte_pointees(normalize_byte_pointees);
+        let result = builder.try_build_cast_lty_adjust(from_lty, to_adjust);
+        drop(builder);
+        if let Err(e) = result {
+            self.report_cast_failure(&e);
+        }
     }
 
     /// Cast an adjusted version of `to_lty` to `to_lty` itself.  If `to_desc` is the `TypeDesc`
@@ -1415,8 +1945,14 @@ impl<'a, 'tcx> ExprRewriteVisitor<'a, 'tcx> {
     ) {
         let perms = self.perms;
         let flags = self.flags;
-        let mut builder = CastBuilder::new(self.acx.tcx(), perms, flags, |rk| self.emit(rk));
-        builder.build_cast_adjust_lty(from_adjust, to_lty);
+        let normalize_byte_pointees = self.acx.gacx.normalize_byte_pointee_types;
+        let mut builder = CastBuilder::new(self.acx.tcx(), perms, flags, |rk| self.emit(rk))
+            .normalize_byte_pointees(normalize_byte_pointees);
+        let result = builder.try_build_cast_adjust_lty(from_adjust, to_lty);
+        drop(builder);
+        if let Err(e) = result {
+            self.report_cast_failure(&e);
+        }
     }
 }
 
@@ -1513,6 +2049,12 @@ impl ZeroizeType {
     }
 }
 
+/// Is `ty` one of the byte-sized integer types (`i8`/`u8`, which is all `c_char` ever resolves
+/// to) that [`CastBuilder::normalize_byte_pointees`] treats as interchangeable pointees?
+fn is_byte_like_pointee(ty: Ty) -> bool {
+    matches!(ty.kind(), TyKind::Int(IntTy::I8) | TyKind::Uint(UintTy::U8))
+}
+
 pub struct CastBuilder<'a, 'tcx, PT1, PT2, F> {
     tcx: TyCtxt<'tcx>,
     perms: &'a PT1,
@@ -1526,6 +2068,10 @@ pub struct CastBuilder<'a, 'tcx, PT1, PT2, F> {
     /// If set, the cast builder will emit a downgrade/borrow operation even for no-op casts, if
     /// the thing being cast can't be moved (`!can_move`) and also can't be copied.
     borrow: bool,
+    /// If set, a pointee type mismatch between two byte-like integer types (`i8`/`u8`/`c_char`)
+    /// doesn't fail the cast; instead, a [`RewriteKind::CastBytePointee`] is emitted to bridge the
+    /// two. See [`Self::normalize_byte_pointees`].
+    normalize_byte_pointees: bool,
 }
 
 impl<'a, 'tcx, PT1, PT2, F> CastBuilder<'a, 'tcx, PT1, PT2, F>
@@ -1547,6 +2093,7 @@ where
             emit,
             can_move: false,
             borrow: false,
+            normalize_byte_pointees: false,
         }
     }
 
@@ -1560,6 +2107,16 @@ where
         self
     }
 
+    /// Treat `i8`/`u8`/`c_char` pointees as interchangeable when building a cast, per
+    /// [`crate::context::GlobalAnalysisCtxt::normalize_byte_pointee_types`].  Transpiled code
+    /// routinely mixes these for the same underlying byte buffer (a `*mut c_char` argument backed
+    /// by a `Vec<u8>`, say), and without this, [`Self::try_build_cast_desc_desc`] simply refuses
+    /// to bridge them.
+    pub fn normalize_byte_pointees(mut self, normalize: bool) -> Self {
+        self.normalize_byte_pointees = normalize;
+        self
+    }
+
     pub fn build_cast_desc_desc(&mut self, from: TypeDesc<'tcx>, to: TypeDesc<'tcx>) {
         self.try_build_cast_desc_desc(from, to).unwrap()
     }
@@ -1582,9 +2139,24 @@ where
         let from_pointee_erased = self.tcx.erase_regions(from.pointee_ty);
         let to_pointee_erased = self.tcx.erase_regions(to.pointee_ty);
         if from_pointee_erased != to_pointee_erased {
-            return Err(format!(
-                "pointee type mismatch: {from_pointee_erased:?} != {to_pointee_erased:?}"
-            ));
+            if self.normalize_byte_pointees
+                && is_byte_like_pointee(from_pointee_erased)
+                && is_byte_like_pointee(to_pointee_erased)
+                && matches!(from.own, Ownership::Raw | Ownership::RawMut)
+                && from.own == to.own
+                && from.qty == to.qty
+            {
+                let printer = FmtPrinter::new(self.tcx, Namespace::TypeNS);
+                let to_ty = to_pointee_erased.print(printer).unwrap().into_buffer();
+                (self.emit)(RewriteKind::CastBytePointee {
+                    to_ty,
+                    mutbl: from.own == Ownership::RawMut,
+                });
+            } else {
+                return Err(format!(
+                    "pointee type mismatch: {from_pointee_erased:?} != {to_pointee_erased:?}"
+                ));
+            }
         }
         // There might still be differences in lifetimes, which we don't care about here.
         // Overwriting `from.pointee_ty` allows the final `from == to` check to succeed below.
@@ -1838,11 +2410,21 @@ where
     ) -> Result<Option<Ownership>, String> {
         Ok(match from.own {
             Ownership::Box => match to.own {
-                Ownership::Raw | Ownership::Imm => {
+                // Crossing into a region that stays raw (an extern call, a `FIXED`-pinned struct
+                // field, ...) needs a real ownership transfer, not a borrow: a reborrow here would
+                // leave the `Box` to run its destructor while the raw side still thinks it owns
+                // the allocation, causing a use-after-free or double free.  `Box::into_raw` hands
+                // off ownership instead; the corresponding `Box::from_raw` lives in the `Raw`/
+                // `RawMut` arm below, for code that takes such a pointer back.
+                Ownership::Raw | Ownership::RawMut if !early => {
+                    (self.emit)(RewriteKind::IntoRawBox);
+                    Some(Ownership::RawMut)
+                }
+                Ownership::Imm => {
                     (self.emit)(RewriteKind::Reborrow { mutbl: false });
                     Some(Ownership::Imm)
                 }
-                Ownership::RawMut | Ownership::Mut | Ownership::Cell => {
+                Ownership::Mut | Ownership::Cell => {
                     (self.emit)(RewriteKind::Reborrow { mutbl: true });
                     Some(Ownership::Mut)
                 }
@@ -1885,6 +2467,12 @@ where
                 _ => None,
             },
             Ownership::RawMut => match to.own {
+                // Re-entering `Box` ownership from a pointer that came from a raw/`FIXED` region
+                // (the other side of the `Box::into_raw` above) via `Box::from_raw`.
+                Ownership::Box if !early => {
+                    (self.emit)(RewriteKind::FromRawBox);
+                    Some(Ownership::Box)
+                }
                 // For `RawMut` to `Imm`, we go through `Raw` instead of through `Mut` because
                 // `&mut` adds more implicit constraints under the Rust memory model.
                 Ownership::Raw | Ownership::Imm if !early => {
@@ -1919,6 +2507,14 @@ where
     }
 
     pub fn build_cast_lty_desc(&mut self, from_lty: LTy<'tcx>, to: TypeDesc<'tcx>) {
+        self.try_build_cast_lty_desc(from_lty, to).unwrap()
+    }
+
+    pub fn try_build_cast_lty_desc(
+        &mut self,
+        from_lty: LTy<'tcx>,
+        to: TypeDesc<'tcx>,
+    ) -> Result<(), String> {
         let from = type_desc::perms_to_desc_with_pointee(
             self.tcx,
             to.pointee_ty,
@@ -1926,10 +2522,18 @@ where
             self.perms[from_lty.label],
             self.flags[from_lty.label],
         );
-        self.build_cast_desc_desc(from, to);
+        self.try_build_cast_desc_desc(from, to)
     }
 
     pub fn build_cast_desc_lty(&mut self, from: TypeDesc<'tcx>, to_lty: LTy<'tcx>) {
+        self.try_build_cast_desc_lty(from, to_lty).unwrap()
+    }
+
+    pub fn try_build_cast_desc_lty(
+        &mut self,
+        from: TypeDesc<'tcx>,
+        to_lty: LTy<'tcx>,
+    ) -> Result<(), String> {
         let to = type_desc::perms_to_desc_with_pointee(
             self.tcx,
             from.pointee_ty,
@@ -1937,7 +2541,7 @@ where
             self.perms[to_lty.label],
             self.flags[to_lty.label],
         );
-        self.build_cast_desc_desc(from, to);
+        self.try_build_cast_desc_desc(from, to)
     }
 
     fn lty_to_desc(&self, lty: LTy<'tcx>) -> TypeDesc<'tcx> {
@@ -1945,16 +2549,24 @@ where
     }
 
     pub fn build_cast_lty_lty(&mut self, from_lty: LTy<'tcx>, to_lty: LTy<'tcx>) {
+        self.try_build_cast_lty_lty(from_lty, to_lty).unwrap()
+    }
+
+    pub fn try_build_cast_lty_lty(
+        &mut self,
+        from_lty: LTy<'tcx>,
+        to_lty: LTy<'tcx>,
+    ) -> Result<(), String> {
         if from_lty.label.is_none() && to_lty.label.is_none() {
             // Input and output are both non-pointers.
-            return;
+            return Ok(());
         }
 
         let from_raw = matches!(from_lty.ty.kind(), TyKind::RawPtr(..));
         let to_raw = matches!(to_lty.ty.kind(), TyKind::RawPtr(..));
         if !from_raw && !to_raw {
             // TODO: hack to work around issues with already-safe code
-            return;
+            return Ok(());
         }
 
         let from_fixed = self.flags[from_lty.label].contains(FlagSet::FIXED);
@@ -1964,23 +2576,24 @@ where
             (false, false) => {
                 let from = self.lty_to_desc(from_lty);
                 let to = self.lty_to_desc(to_lty);
-                self.build_cast_desc_desc(from, to);
+                self.try_build_cast_desc_desc(from, to)?;
             }
 
             (false, true) => {
                 let from = self.lty_to_desc(from_lty);
-                self.build_cast_desc_lty(from, to_lty);
+                self.try_build_cast_desc_lty(from, to_lty)?;
             }
 
             (true, false) => {
                 let to = self.lty_to_desc(to_lty);
-                self.build_cast_lty_desc(from_lty, to);
+                self.try_build_cast_lty_desc(from_lty, to)?;
             }
 
             (true, true) => {
                 // No-op.  Both sides are `FIXED`, so we assume the existing code is already valid.
             }
         }
+        Ok(())
     }
 
     pub fn build_cast_lty_adjust(
@@ -1988,21 +2601,29 @@ where
         from_lty: LTy<'tcx>,
         to_adjust: impl FnOnce(TypeDesc<'tcx>) -> TypeDesc<'tcx>,
     ) {
+        self.try_build_cast_lty_adjust(from_lty, to_adjust).unwrap()
+    }
+
+    pub fn try_build_cast_lty_adjust(
+        &mut self,
+        from_lty: LTy<'tcx>,
+        to_adjust: impl FnOnce(TypeDesc<'tcx>) -> TypeDesc<'tcx>,
+    ) -> Result<(), String> {
         if from_lty.label.is_none() {
             // Input and output are both non-pointers.
-            return;
+            return Ok(());
         }
         if !matches!(from_lty.ty.kind(), TyKind::RawPtr(..)) {
             // TODO: hack to work around issues with already-safe code
-            return;
+            return Ok(());
         }
         if self.flags[from_lty.label].contains(FlagSet::FIXED) {
-            return;
+            return Ok(());
         }
 
         let from = self.lty_to_desc(from_lty);
         let to = to_adjust(from);
-        self.build_cast_desc_desc(from, to);
+        self.try_build_cast_desc_desc(from, to)
     }
 
     pub fn build_cast_adjust_lty(
@@ -2010,21 +2631,29 @@ where
         from_adjust: impl FnOnce(TypeDesc<'tcx>) -> TypeDesc<'tcx>,
         to_lty: LTy<'tcx>,
     ) {
+        self.try_build_cast_adjust_lty(from_adjust, to_lty).unwrap()
+    }
+
+    pub fn try_build_cast_adjust_lty(
+        &mut self,
+        from_adjust: impl FnOnce(TypeDesc<'tcx>) -> TypeDesc<'tcx>,
+        to_lty: LTy<'tcx>,
+    ) -> Result<(), String> {
         if to_lty.label.is_none() {
             // Input and output are both non-pointers.
-            return;
+            return Ok(());
         }
         if !matches!(to_lty.ty.kind(), TyKind::RawPtr(..)) {
             // TODO: hack to work around issues with already-safe code
-            return;
+            return Ok(());
         }
         if self.flags[to_lty.label].contains(FlagSet::FIXED) {
-            return;
+            return Ok(());
         }
 
         let to = self.lty_to_desc(to_lty);
         let from = from_adjust(to);
-        self.build_cast_desc_desc(from, to);
+        self.try_build_cast_desc_desc(from, to)
     }
 }
 
@@ -2078,35 +2707,98 @@ impl IsPlace for Rvalue<'_> {
     }
 }
 
+/// Find all locals that are the target of a MIR `Drop`/`DropAndReplace` terminator anywhere in
+/// `mir`.  Computed as a separate pass over the whole body (rather than accumulated while
+/// visiting statements in order) so that it also covers drops that occur earlier in the CFG than
+/// the `free` call being rewritten, such as the drop of a loop-local value on a prior iteration.
+fn collect_dropped_locals(mir: &Body) -> HashSet<Local> {
+    let mut out = HashSet::new();
+    for bb in mir.basic_blocks().iter() {
+        match bb.terminator().kind {
+            TerminatorKind::Drop { place, .. } | TerminatorKind::DropAndReplace { place, .. } => {
+                out.insert(place.local);
+            }
+            _ => {}
+        }
+    }
+    out
+}
+
+/// Generate MIR-level rewrites for every statement/terminator in `mir`.
+///
+/// Each basic block is visited inside its own [`panic_detail::catch_unwind`] scope, so a panic
+/// while visiting one block (e.g. an unhandled MIR shape this pass doesn't expect) only discards
+/// the rewrites generated for that block, not the whole function's. The failed block's rewrites
+/// are dropped and it's recorded in the returned `Vec`, for the caller to fold into its
+/// skipped-work report; the block is simply left with no rewrites, so its code stays as the
+/// original raw-pointer version.
+///
+/// This function only generates rewrites from an already-finalized `asn`, so it can't feed a
+/// failed block back into `asn` itself. The caller does that instead: `analyze::run2` pins every
+/// local mentioned in a failed block `FIXED` and rejoins its own fixed-point loop, so the rest of
+/// the function's rewrites get regenerated without assuming a rewritten (non-`FIXED`)
+/// representation for pointers the failed block can't provide -- otherwise the unrewritten block
+/// and its rewritten neighbors could end up with a type mismatch between them.
 pub fn gen_mir_rewrites<'tcx>(
     acx: &AnalysisCtxt<'_, 'tcx>,
     asn: &Assignment,
+    plan: &RewritePlan,
     pointee_types: PointerTable<PointeeTypes<'tcx>>,
     last_use: &LastUse,
     mir: &Body<'tcx>,
-) -> (HashMap<Location, Vec<MirRewrite>>, DontRewriteFnReason) {
+) -> (
+    HashMap<Location, Vec<MirRewrite>>,
+    DontRewriteFnReason,
+    Vec<(BasicBlock, panic_detail::PanicDetail)>,
+) {
     let mut out = HashMap::new();
-
-    let mut v = ExprRewriteVisitor::new(acx, asn, pointee_types, last_use, &mut out, mir);
+    let mut errors = DontRewriteFnReason::empty();
+    let mut block_failures = Vec::new();
+    let dropped_locals = collect_dropped_locals(mir);
 
     for (bb_id, bb) in mir.basic_blocks().iter_enumerated() {
-        for (i, stmt) in bb.statements.iter().enumerate() {
-            let loc = Location {
-                block: bb_id,
-                statement_index: i,
-            };
-            v.visit_statement(stmt, loc);
-        }
+        let mut block_rewrites = HashMap::new();
+        let r = panic_detail::catch_unwind(AssertUnwindSafe(|| {
+            let mut v = ExprRewriteVisitor::new(
+                acx,
+                asn,
+                plan,
+                pointee_types,
+                last_use,
+                &mut block_rewrites,
+                mir,
+            );
+            v.dropped_locals = dropped_locals.clone();
+
+            for (i, stmt) in bb.statements.iter().enumerate() {
+                let loc = Location {
+                    block: bb_id,
+                    statement_index: i,
+                };
+                v.visit_statement(stmt, loc);
+            }
 
-        if let Some(ref term) = bb.terminator {
-            let loc = Location {
-                block: bb_id,
-                statement_index: bb.statements.len(),
-            };
-            v.visit_terminator(term, loc);
+            if let Some(ref term) = bb.terminator {
+                let loc = Location {
+                    block: bb_id,
+                    statement_index: bb.statements.len(),
+                };
+                v.visit_terminator(term, loc);
+            }
+
+            v.errors
+        }));
+
+        match r {
+            Ok(block_errors) => {
+                errors |= block_errors;
+                out.extend(block_rewrites);
+            }
+            Err(pd) => {
+                block_failures.push((bb_id, pd));
+            }
         }
     }
 
-    let errors = v.errors;
-    (out, errors)
+    (out, errors, block_failures)
 }
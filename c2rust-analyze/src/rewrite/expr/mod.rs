@@ -2,15 +2,17 @@ use self::mir_op::MirRewrite;
 use self::unlower::{PreciseLoc, UnlowerMap};
 use crate::context::{AnalysisCtxt, Assignment};
 use crate::last_use::LastUse;
+use crate::panic_detail::PanicDetail;
 use crate::pointee_type::PointeeTypes;
 use crate::pointer_id::PointerTable;
-use crate::rewrite::Rewrite;
+use crate::rewrite::{Rewrite, RewritePlan};
 use rustc_hir::def_id::DefId;
 use rustc_hir::BodyId;
-use rustc_middle::mir::{Body, Location};
+use rustc_middle::mir::{BasicBlock, Body, Location};
 use rustc_middle::ty::TyCtxt;
 use rustc_span::Span;
 use std::collections::HashMap;
+use std::rc::Rc;
 
 mod convert;
 mod distribute;
@@ -25,13 +27,22 @@ pub use self::mir_op::CastBuilder;
 pub fn gen_expr_rewrites<'tcx>(
     acx: &mut AnalysisCtxt<'_, 'tcx>,
     asn: &Assignment,
+    plan: &RewritePlan,
     pointee_types: PointerTable<PointeeTypes<'tcx>>,
     last_use: &LastUse,
     def_id: DefId,
     mir: &Body<'tcx>,
     hir_body_id: BodyId,
-) -> Vec<(Span, Rewrite)> {
-    let (mir_rewrites, errors) = mir_op::gen_mir_rewrites(acx, asn, pointee_types, last_use, mir);
+) -> (Vec<(Span, Rewrite)>, Vec<(BasicBlock, PanicDetail)>) {
+    // Most functions in a transpiled crate don't touch any pointers at all.  Skip straight to an
+    // empty result for those, rather than running `mir_op`'s MIR walk, unlowering the (empty) set
+    // of rewrites onto HIR, and distributing nothing.
+    if acx.num_pointers() == 0 {
+        return (Vec::new(), Vec::new());
+    }
+
+    let (mir_rewrites, errors, block_failures) =
+        mir_op::gen_mir_rewrites(acx, asn, plan, pointee_types, last_use, mir);
     if !errors.is_empty() {
         acx.gacx.dont_rewrite_fns.add(def_id, errors);
     }
@@ -53,7 +64,7 @@ pub fn gen_expr_rewrites<'tcx>(
     });
     let mut hir_rewrites = convert::convert_rewrites(acx.tcx(), hir_body_id, rewrites_by_expr);
     hir_rewrites.extend(address_of_rewrites);
-    hir_rewrites
+    (hir_rewrites, block_failures)
 }
 
 fn debug_print_unlower_map<'tcx>(
@@ -66,7 +77,7 @@ fn debug_print_unlower_map<'tcx>(
         let mut rewrites_by_subloc = HashMap::new();
         for rw in mir_rewrites.get(&loc).map_or(&[] as &[_], |x| x) {
             rewrites_by_subloc
-                .entry(&rw.sub_loc)
+                .entry(rw.sub_loc.as_slice())
                 .or_insert(Vec::new())
                 .push(&rw.kind);
         }
@@ -76,17 +87,19 @@ fn debug_print_unlower_map<'tcx>(
         }
 
         let mut found_at_least_one_origin = false;
-        for (k, v) in unlower_map
-            .origins_map()
-            .range(&PreciseLoc { loc, sub: vec![] }..)
-        {
+        for (k, v) in unlower_map.origins_map().range(
+            &PreciseLoc {
+                loc,
+                sub: Rc::from(Vec::new()),
+            }..,
+        ) {
             if k.loc != loc {
                 break;
             }
-            let sublocs = &k.sub;
+            let sublocs: &[_] = &k.sub;
             let ex = tcx.hir().expect_expr(v.hir_id);
             eprintln!("      {sublocs:?}: {:?}, {:?}", v.desc, ex.span);
-            for rw_kind in rewrites_by_subloc.remove(&sublocs).unwrap_or_default() {
+            for rw_kind in rewrites_by_subloc.remove(sublocs).unwrap_or_default() {
                 eprintln!("        {rw_kind:?}");
             }
             found_at_least_one_origin = true;
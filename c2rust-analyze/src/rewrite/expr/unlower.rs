@@ -14,11 +14,32 @@ use rustc_middle::ty::{TyCtxt, TypeckResults};
 use rustc_span::Span;
 use std::collections::btree_map::{BTreeMap, Entry};
 use std::collections::{HashMap, HashSet};
+use std::rc::Rc;
+
+/// Interns `sub_loc` paths as `Rc<[SubLoc]>`.  The same handful of short paths (`[Dest]`,
+/// `[Rvalue]`, `[Rvalue, RvalueOperand(0)]`, ...) recur for practically every MIR location in a
+/// function, so interning lets every `PreciseLoc` that shares a path also share its backing
+/// allocation instead of cloning a fresh `Vec` each time one is recorded.
+#[derive(Default)]
+struct SubLocInterner {
+    paths: HashMap<Vec<SubLoc>, Rc<[SubLoc]>>,
+}
+
+impl SubLocInterner {
+    fn intern(&mut self, sub_loc: &[SubLoc]) -> Rc<[SubLoc]> {
+        if let Some(rc) = self.paths.get(sub_loc) {
+            return Rc::clone(rc);
+        }
+        let rc: Rc<[SubLoc]> = Rc::from(sub_loc);
+        self.paths.insert(sub_loc.to_owned(), Rc::clone(&rc));
+        rc
+    }
+}
 
 #[derive(Clone, PartialEq, Eq, PartialOrd, Ord, Debug, Hash)]
 pub struct PreciseLoc {
     pub loc: Location,
-    pub sub: Vec<SubLoc>,
+    pub sub: Rc<[SubLoc]>,
 }
 
 #[derive(Clone, Copy, PartialEq, Eq, Debug, Hash)]
@@ -102,6 +123,9 @@ struct UnlowerVisitor<'a, 'tcx> {
     /// list retrieved from the `SpanIndex`.  This is used in cases where some MIR statements have
     /// their spans set to a parent expr but really belong to the child.
     append_extra_locations: HashMap<HirId, Vec<Location>>,
+
+    /// Interns `sub_loc` paths used to build `PreciseLoc` keys.  See [`SubLocInterner`].
+    sub_loc_interner: SubLocInterner,
 }
 
 impl<'a, 'tcx> UnlowerVisitor<'a, 'tcx> {
@@ -135,7 +159,7 @@ impl<'a, 'tcx> UnlowerVisitor<'a, 'tcx> {
         };
         let key = PreciseLoc {
             loc,
-            sub: sub_loc.to_owned(),
+            sub: self.sub_loc_interner.intern(sub_loc),
         };
         match self.unlower_map.origins.entry(key) {
             Entry::Vacant(e) => {
@@ -850,7 +874,7 @@ impl<'a, 'b, 'tcx> VisitExprCursor<'a, 'b, 'tcx> {
         // `sub`-location possible.
         let load_loc = PreciseLoc {
             loc: self.loc,
-            sub: self.sub_loc.clone(),
+            sub: Rc::from(self.sub_loc.clone()),
         };
 
         let pl = self.require_place()?;
@@ -875,7 +899,10 @@ impl<'a, 'b, 'tcx> VisitExprCursor<'a, 'b, 'tcx> {
             return None;
         }
 
-        let store_loc = PreciseLoc { loc, sub: vec![] };
+        let store_loc = PreciseLoc {
+            loc,
+            sub: Rc::from(Vec::new()),
+        };
         let load_desc = match self.last_adjustment {
             Some(i) => MirOriginDesc::LoadFromTempForAdjustment(i),
             None => MirOriginDesc::LoadFromTemp,
@@ -1173,6 +1200,7 @@ pub fn unlower<'tcx>(tcx: TyCtxt<'tcx>, mir: &Body<'tcx>, hir_body_id: hir::Body
         span_index,
         unlower_map: UnlowerMap::default(),
         append_extra_locations: HashMap::new(),
+        sub_loc_interner: SubLocInterner::default(),
     };
     visitor.visit_body(hir);
 
@@ -28,17 +28,27 @@ use rustc_hir::Mutability;
 use rustc_middle::ty::TyCtxt;
 use rustc_span::{FileName, Span};
 use std::collections::HashMap;
+use std::env;
 use std::fmt;
 use std::fs;
+use std::path::Path;
+use std::path::PathBuf;
 
+mod api_map;
 mod apply;
+mod dual_impl;
 mod expr;
+mod plan;
 mod shim;
 mod span_index;
+mod span_like;
 mod statics;
 mod ty;
 
+pub use self::api_map::{describe_api_change, gen_pub_api_compat_shim_rewrites};
+pub use self::dual_impl::build_dual_impl_rewrite;
 pub use self::expr::gen_expr_rewrites;
+pub use self::plan::RewritePlan;
 pub use self::shim::{gen_shim_call_rewrites, gen_shim_definition_rewrite, ManualShimCasts};
 pub use self::statics::gen_static_rewrites;
 pub use self::ty::dump_rewritten_local_tys;
@@ -50,6 +60,15 @@ pub enum LifetimeName {
     Elided,
 }
 
+/// The comment `expr::convert` attaches to the code it generates for the `OffsetRawUnsafe`
+/// rewrite kind (a raw pointer `.offset()` by a statically-known negative amount, which can't be
+/// converted to a checked slice index). Kept here, rather than as a literal in `expr::convert`,
+/// so that `crate::unsafe_helper_dedup` -- which matches on this exact text to recognize that
+/// specific non-convertible unsafe pattern across the whole crate -- can't silently drift out of
+/// sync with the text actually being emitted.
+pub(crate) const OFFSET_RAW_UNSAFE_COMMENT: &str = "TODO(c2rust): offset by a statically-known \
+     negative amount; left as a raw pointer operation instead of a checked slice index";
+
 #[derive(Clone, PartialEq, Eq, Debug, Default)]
 pub enum Rewrite<S = Span> {
     /// Take the original expression unchanged.
@@ -70,10 +89,20 @@ pub enum Rewrite<S = Span> {
     AddrOf(Box<Rewrite>, Mutability),
     /// `*e`
     Deref(Box<Rewrite>),
+    /// `!e`
+    Not(Box<Rewrite>),
+    /// `unsafe { e }`
+    Unsafe(Box<Rewrite>),
+    /// `e /* comment */`.  Used to annotate an expression-level fallback (such as
+    /// [`Unsafe`](Self::Unsafe)) with the reason a fuller rewrite wasn't possible.
+    Commented(Box<Rewrite>, String),
     /// `arr[idx]`
     Index(Box<Rewrite>, Box<Rewrite>),
     /// `arr[idx1..idx2]`.  Both `idx1` and `idx2` are optional.
     SliceRange(Box<Rewrite>, Option<Box<Rewrite>>, Option<Box<Rewrite>>),
+    /// `idx..`, as a standalone range value (e.g. to pass to `get`/`get_unchecked`), rather than
+    /// as part of an `arr[idx..]` indexing expression like [`SliceRange`](Self::SliceRange).
+    RangeFrom(Box<Rewrite>),
     /// `e as T`
     Cast(Box<Rewrite>, Box<Rewrite>),
     /// Placeholder for a redundant cast that has already been removed.  This allows
@@ -179,12 +208,16 @@ impl Rewrite {
             Ref(ref rw, mutbl) => Ref(try_subst(rw)?, mutbl),
             AddrOf(ref rw, mutbl) => AddrOf(try_subst(rw)?, mutbl),
             Deref(ref rw) => Deref(try_subst(rw)?),
+            Not(ref rw) => Not(try_subst(rw)?),
+            Unsafe(ref rw) => Unsafe(try_subst(rw)?),
+            Commented(ref rw, ref comment) => Commented(try_subst(rw)?, String::clone(comment)),
             Index(ref arr, ref idx) => Index(try_subst(arr)?, try_subst(idx)?),
             SliceRange(ref arr, ref lo, ref hi) => SliceRange(
                 try_subst(arr)?,
                 try_subst_option(lo)?,
                 try_subst_option(hi)?,
             ),
+            RangeFrom(ref lo) => RangeFrom(try_subst(lo)?),
             Cast(ref expr, ref ty) => Cast(try_subst(expr)?, try_subst(ty)?),
             RemovedCast(ref rw) => RemovedCast(try_subst(rw)?),
             LitZero => LitZero,
@@ -320,8 +353,10 @@ fn add_annotations(
     out
 }
 
-#[derive(Clone, Copy, PartialEq, Eq, Debug, Hash)]
+#[derive(Clone, PartialEq, Eq, Debug, Hash)]
 pub enum UpdateFiles {
+    /// Dry run: don't write anything to disk.  This is the default, so that running the tool
+    /// never modifies a source tree unless explicitly asked to.
     No,
     InPlace,
     Alongside,
@@ -329,6 +364,66 @@ pub enum UpdateFiles {
     /// rewriting mode is `AlongsidePointwise("bar")`, then the rewritten code is written to
     /// `foo.bar.rs`.
     AlongsidePointwise(rustc_span::symbol::Symbol),
+    /// Mirror the rewritten source tree under a separate root directory, rather than writing
+    /// alongside or in place of the original files.  The rewritten copy of a file is written to
+    /// the same path relative to this directory as the original file has relative to
+    /// `CARGO_MANIFEST_DIR` (or, failing that, the current directory); if neither prefix matches,
+    /// the original file's absolute path (with its leading `/` stripped) is used instead, so the
+    /// tree is still mirrored, just rooted one level deeper than usual.
+    OutputDir(PathBuf),
+}
+
+/// Write a copy of `path`'s current on-disk contents to `path` with an additional `.orig`
+/// extension, unless that backup already exists (so that re-running the tool repeatedly doesn't
+/// clobber the true original with an already-rewritten version of the file).
+fn backup_original(path: &Path) {
+    let backup_path = {
+        let mut s = path.as_os_str().to_owned();
+        s.push(".orig");
+        PathBuf::from(s)
+    };
+    if backup_path.exists() {
+        return;
+    }
+    match fs::read(path) {
+        Ok(orig) => fs::write(&backup_path, orig).unwrap(),
+        Err(e) => warn!("failed to back up {:?} to {:?}: {}", path, backup_path, e),
+    }
+}
+
+/// Map `path` to its mirrored location under `output_dir`, preserving its path relative to
+/// `CARGO_MANIFEST_DIR` (or the current directory, or, failing both, its own absolute path).
+fn mirror_path(output_dir: &Path, path: &Path) -> PathBuf {
+    let manifest_dir = env::var_os("CARGO_MANIFEST_DIR").map(PathBuf::from);
+    let cwd = env::current_dir().ok();
+    let rel = manifest_dir
+        .as_deref()
+        .and_then(|base| path.strip_prefix(base).ok())
+        .or_else(|| cwd.as_deref().and_then(|base| path.strip_prefix(base).ok()))
+        .or_else(|| path.strip_prefix("/").ok())
+        .unwrap_or(path);
+    output_dir.join(rel)
+}
+
+/// Returns `true` if `path` lies under the `$OUT_DIR` of the crate currently being analyzed, the
+/// standard location for code a build script generates (e.g. `bindgen` output included via
+/// `include!(concat!(env!("OUT_DIR"), "/bindings.rs"))`).
+///
+/// This is a heuristic, not a general "is this file generated" check: a build script is free to
+/// write generated code anywhere, and `OUT_DIR` is simply the one convention cargo itself
+/// establishes and that we can check without guessing at a particular code generator's layout.
+fn is_out_dir_path(path: &Path) -> bool {
+    let out_dir = match env::var_os("OUT_DIR") {
+        Some(x) => x,
+        None => return false,
+    };
+    path.starts_with(out_dir)
+}
+
+/// Whether `--backup-originals` was passed, i.e. whether an in-place rewrite should save a
+/// `.orig` copy of each file's pre-rewrite contents before overwriting it.
+fn backup_originals_enabled() -> bool {
+    env::var("C2RUST_ANALYZE_BACKUP_ORIGINALS").as_deref() == Ok("1")
 }
 
 pub fn apply_rewrites(
@@ -355,16 +450,46 @@ pub fn apply_rewrites(
             let mut path_ok = false;
             if let FileName::Real(ref rfn) = filename {
                 if let Some(path) = rfn.local_path() {
-                    let path = match update_files {
-                        UpdateFiles::InPlace => path.to_owned(),
-                        UpdateFiles::Alongside => path.with_extension("new.rs"),
-                        UpdateFiles::AlongsidePointwise(ref s) => {
-                            let ext = format!("{}.rs", s);
-                            let p = path.with_extension(&ext);
-                            debug!("writing to {:?}", p);
-                            p
+                    // Generated code under `OUT_DIR` gets regenerated by the build script on the
+                    // next build, so writing the rewrite in place would just be silently
+                    // discarded.  Write it to a separate path instead, and call it out so a user
+                    // can decide whether to fold the change back into whatever generates this
+                    // file (e.g. a `bindgen` invocation in `build.rs`).
+                    let path = if is_out_dir_path(path) {
+                        let p = path.with_extension("c2rust-generated-rewrite.rs");
+                        warn!(
+                            "{:?} is generated code under OUT_DIR; writing its rewrite to {:?} \
+                             instead of overwriting the generated file, since it would be lost on \
+                             the next build -- consider upstreaming this rewrite into whatever \
+                             generates it",
+                            path, p,
+                        );
+                        p
+                    } else {
+                        match update_files {
+                            UpdateFiles::InPlace => {
+                                if backup_originals_enabled() {
+                                    backup_original(path);
+                                }
+                                path.to_owned()
+                            }
+                            UpdateFiles::Alongside => path.with_extension("new.rs"),
+                            UpdateFiles::AlongsidePointwise(ref s) => {
+                                let ext = format!("{}.rs", s);
+                                let p = path.with_extension(&ext);
+                                debug!("writing to {:?}", p);
+                                p
+                            }
+                            UpdateFiles::OutputDir(ref output_dir) => {
+                                let p = mirror_path(output_dir, path);
+                                if let Some(parent) = p.parent() {
+                                    fs::create_dir_all(parent).unwrap();
+                                }
+                                debug!("writing to {:?}", p);
+                                p
+                            }
+                            UpdateFiles::No => unreachable!(),
                         }
-                        UpdateFiles::No => unreachable!(),
                     };
                     fs::write(path, src).unwrap();
                     path_ok = true;
@@ -400,6 +525,178 @@ pub fn apply_rewrites(
     }
 }
 
+/// Escape `s` for embedding in a JSON string literal. Only the characters JSON requires escaping
+/// (`"`, `\`, and the C0 control characters) are handled; everything else, including non-ASCII
+/// text, is passed through unchanged, since `str` is already valid UTF-8 and JSON strings are
+/// UTF-8 by definition.
+fn json_escape(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if (c as u32) < 0x20 => out.push_str(&format!("\\u{:04x}", c as u32)),
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+/// Write `rewrites` to `path` as a series of newline-delimited JSON objects, one per outermost
+/// rewrite, in the same spirit as the `spans` entries of a `rustc --error-format=json`
+/// machine-applicable suggestion: a `file_name`, a byte/line/column range, the `replacement` text,
+/// and an `applicability` (always `"MachineApplicable"`, since every rewrite this tool emits is
+/// meant to be applied as-is). This is *not* a full rustc diagnostic -- there's no wrapping
+/// `message`/`spans`/`children` diagnostic object, since these rewrites don't originate from a
+/// diagnostic and have no human-readable message attached -- just the per-span suggestion data,
+/// which is the part that a `cargo fix`-like application tool actually needs to consume.
+///
+/// Like [`apply_rewrites`], a rewrite nested inside a larger one (e.g. a cast rewrite inside a
+/// larger expression rewrite) is folded into its parent's `replacement` text rather than getting
+/// its own line; applying nested rewrites independently could otherwise double up the change.
+pub fn emit_machine_applicable_suggestions(
+    tcx: TyCtxt,
+    rewrites: Vec<(Span, Rewrite)>,
+    path: &Path,
+) {
+    let sm = tcx.sess.source_map();
+    let suggestions = apply::collect_top_level_rewrites(sm, rewrites);
+
+    let mut out = String::new();
+    for (span, replacement) in suggestions {
+        let lo = sm.lookup_byte_offset(span.lo());
+        let hi = sm.lookup_byte_offset(span.hi());
+        let lo_pos = sm.lookup_char_pos(span.lo());
+        let hi_pos = sm.lookup_char_pos(span.hi());
+        out.push_str(&format!(
+            "{{\"file_name\":\"{}\",\"byte_start\":{},\"byte_end\":{},\
+             \"line_start\":{},\"line_end\":{},\"column_start\":{},\"column_end\":{},\
+             \"replacement\":\"{}\",\"applicability\":\"MachineApplicable\"}}\n",
+            json_escape(&lo.sf.name.prefer_local().to_string()),
+            lo.pos.0,
+            hi.pos.0,
+            lo_pos.line,
+            hi_pos.line,
+            lo_pos.col.0 + 1,
+            hi_pos.col.0 + 1,
+            json_escape(&replacement),
+        ));
+    }
+
+    fs::write(path, out).unwrap_or_else(|e| panic!("failed to write {:?}: {}", path, e));
+}
+
+/// Like [`emit_machine_applicable_suggestions`], but each suggestion is wrapped in the full
+/// diagnostic envelope `rustc`'s own `--error-format=json` output uses (`message`, `level`,
+/// `spans`, `children`, `rendered`), rather than written as a bare per-span suggestion fragment.
+/// `cargo fix` and editors that already know how to apply `rustc`'s own `MachineApplicable`
+/// suggestions read this shape directly, without needing a c2rust-analyze-specific parser for the
+/// slimmer fragment the other function emits.
+///
+/// There's no real rustc `Diagnostic` to hand off here -- these rewrites don't come from a
+/// `Session`-level diagnostic -- so this builds the same JSON shape by hand, filling in a generic
+/// `message` and a `level` of `"help"` (the same level `rustc` uses for its own suggestion-only
+/// diagnostics) rather than claiming a warning or error that isn't actually being reported.
+pub fn emit_rustc_diagnostics_json(tcx: TyCtxt, rewrites: Vec<(Span, Rewrite)>, path: &Path) {
+    let sm = tcx.sess.source_map();
+    let suggestions = apply::collect_top_level_rewrites(sm, rewrites);
+
+    let mut out = String::new();
+    for (span, replacement) in suggestions {
+        let lo = sm.lookup_byte_offset(span.lo());
+        let hi = sm.lookup_byte_offset(span.hi());
+        let lo_pos = sm.lookup_char_pos(span.lo());
+        let hi_pos = sm.lookup_char_pos(span.hi());
+        let file_name = json_escape(&lo.sf.name.prefer_local().to_string());
+        out.push_str(&format!(
+            "{{\"message\":\"this expression can be rewritten\",\"code\":null,\"level\":\"help\",\
+             \"spans\":[{{\"file_name\":\"{}\",\"byte_start\":{},\"byte_end\":{},\
+             \"line_start\":{},\"line_end\":{},\"column_start\":{},\"column_end\":{},\
+             \"is_primary\":true,\"text\":[],\"label\":null,\"suggested_replacement\":\"{}\",\
+             \"suggestion_applicability\":\"MachineApplicable\",\"expansion\":null}}],\
+             \"children\":[],\"rendered\":null}}\n",
+            file_name,
+            lo.pos.0,
+            hi.pos.0,
+            lo_pos.line,
+            hi_pos.line,
+            lo_pos.col.0 + 1,
+            hi_pos.col.0 + 1,
+            json_escape(&replacement),
+        ));
+    }
+
+    fs::write(path, out).unwrap_or_else(|e| panic!("failed to write {:?}: {}", path, e));
+}
+
+/// The leading identifier-like prefix of a [`Rewrite`]'s [`Debug`] output, i.e. its variant name
+/// (`"Cast"`, `"MethodCall"`, `"Commented"`, etc.), for use as a human- and machine-readable
+/// `kind` tag. There's no dedicated name-of-variant API for a plain enum without pulling in a new
+/// dependency, but [`Rewrite`] already derives [`Debug`], and every variant's `Debug` output
+/// starts with its bare name, so this is enough to avoid hand-maintaining a parallel match.
+fn rewrite_kind_name(rw: &Rewrite) -> String {
+    format!("{:?}", rw)
+        .chars()
+        .take_while(|c| c.is_ascii_alphanumeric() || *c == '_')
+        .collect()
+}
+
+/// Export every individual rewrite in `rewrites` as one newline-delimited JSON object per
+/// rewrite, for editor tooling or review scripts to consume directly rather than scraping the
+/// debug output printed by [`apply_rewrites`]. Each object has the rewrite's span (file name,
+/// byte offsets, 1-based line/column range), the original source text at that span, the text
+/// this one rewrite alone would produce there, and a `kind` naming which [`Rewrite`] variant
+/// produced it (plus `reason`, present only for a [`Rewrite::Commented`] rewrite, holding its
+/// attached explanation).
+///
+/// Unlike [`emit_machine_applicable_suggestions`], this isn't scoped to rustc's suggestion schema
+/// or merged into non-overlapping top-level spans first -- every rewrite in `rewrites` gets its
+/// own entry, including ones nested inside another (its `replacement` is rendered as if it were
+/// applied on its own, so it won't exactly match the text the same span ends up with once its
+/// parent rewrite is also applied).
+///
+/// There's no true "`RewriteKind`" available here: [`expr::mir_op::RewriteKind`](crate::rewrite::expr::mir_op::RewriteKind)
+/// only exists at the MIR level, and `expr::convert` doesn't retain it when lowering to the
+/// HIR-level [`Rewrite`] this function sees -- the [`Rewrite`] variant name is the closest
+/// analogous "what kind of rewrite is this" available at this point in the pipeline.
+pub fn emit_rewrites_json(tcx: TyCtxt, rewrites: &[(Span, Rewrite)], path: &Path) {
+    let sm = tcx.sess.source_map();
+    let mut out = String::new();
+    for (span, rw) in rewrites {
+        let lo = sm.lookup_byte_offset(span.lo());
+        let hi = sm.lookup_byte_offset(span.hi());
+        let lo_pos = sm.lookup_char_pos(span.lo());
+        let hi_pos = sm.lookup_char_pos(span.hi());
+        let original = sm.span_to_snippet(*span).unwrap_or_default();
+        let replacement = apply::render_standalone(sm, *span, vec![(*span, rw.clone())]);
+        let reason = match rw {
+            Rewrite::Commented(_, reason) => format!(",\"reason\":\"{}\"", json_escape(reason)),
+            _ => String::new(),
+        };
+        out.push_str(&format!(
+            "{{\"file_name\":\"{}\",\"byte_start\":{},\"byte_end\":{},\
+             \"line_start\":{},\"line_end\":{},\"column_start\":{},\"column_end\":{},\
+             \"original\":\"{}\",\"replacement\":\"{}\",\"kind\":\"{}\"{}}}\n",
+            json_escape(&lo.sf.name.prefer_local().to_string()),
+            lo.pos.0,
+            hi.pos.0,
+            lo_pos.line,
+            hi_pos.line,
+            lo_pos.col.0 + 1,
+            hi_pos.col.0 + 1,
+            json_escape(&original),
+            json_escape(&replacement),
+            rewrite_kind_name(rw),
+            reason,
+        ));
+    }
+
+    fs::write(path, out).unwrap_or_else(|e| panic!("failed to write {:?}: {}", path, e));
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
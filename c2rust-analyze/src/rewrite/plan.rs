@@ -0,0 +1,67 @@
+//! A shared per-pointer "rewrite plan", consulted by both the expression-rewriting pass
+//! ([`crate::rewrite::expr`]) and the type-rewriting pass ([`crate::rewrite::ty`]), so that a
+//! pointer's target representation can't silently diverge between the two -- e.g. a local's
+//! declared type being rewritten to `&mut [T]` while an expression elsewhere still casts a value
+//! for it as if it were `Box<[T]>`.
+//!
+//! Each pointer's [`PtrDesc`] is still computed the same way it always was, via
+//! [`type_desc::perms_to_ptr_desc`](crate::type_desc::perms_to_ptr_desc) applied to that
+//! pointer's current [`PermissionSet`]/[`FlagSet`] -- this module doesn't change that
+//! computation, and `FIXED` pointers (whose representation is the existing type, not one derived
+//! from permissions) aren't covered by it. What's new is that the plan computes each `PtrDesc`
+//! once per rewrite attempt, and [`RewritePlan::check`] lets a consulting call site assert that
+//! the `PtrDesc` it independently derived for a pointer agrees with the plan, turning any future
+//! divergence between the two passes into an immediate, descriptive panic during rewrite
+//! generation instead of a type error in the rewritten output.
+//!
+//! This doesn't cover every `TypeDesc`/`PtrDesc` computation in `rewrite::expr`/`rewrite::ty` --
+//! there are many call sites, and unifying all of them to route through this plan (rather than
+//! spot-checking against it, as done here) is a larger refactor left for later.
+use crate::context::{FlagSet, PermissionSet};
+use crate::pointer_id::{GlobalPointerTable, PointerId};
+use crate::type_desc::{self, PtrDesc};
+
+pub struct RewritePlan {
+    /// The planned `PtrDesc` for each pointer, or `None` for `FIXED` pointers, which this plan
+    /// doesn't cover.
+    descs: GlobalPointerTable<Option<PtrDesc>>,
+}
+
+impl RewritePlan {
+    /// Precompute the planned `PtrDesc` for every pointer in `perms`/`flags`.
+    pub fn build(
+        perms: &GlobalPointerTable<PermissionSet>,
+        flags: &GlobalPointerTable<FlagSet>,
+    ) -> RewritePlan {
+        let descs = perms
+            .iter()
+            .map(|(ptr, &p)| {
+                let f = flags[ptr];
+                if f.contains(FlagSet::FIXED) {
+                    None
+                } else {
+                    Some(type_desc::perms_to_ptr_desc(p, f))
+                }
+            })
+            .collect();
+        RewritePlan {
+            descs: GlobalPointerTable::from_raw(descs),
+        }
+    }
+
+    /// Assert that `desc`, as independently computed at some rewrite call site for `ptr`, agrees
+    /// with this plan. Does nothing for `FIXED` pointers, which the plan doesn't cover.
+    pub fn check(&self, ptr: PointerId, desc: PtrDesc) {
+        if ptr.is_none() {
+            return;
+        }
+        if let Some(expected) = self.descs[ptr] {
+            assert_eq!(
+                expected, desc,
+                "rewrite plan mismatch for {:?}: the expr and type rewrite passes disagree on \
+                 its target representation",
+                ptr,
+            );
+        }
+    }
+}
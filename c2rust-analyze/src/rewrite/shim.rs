@@ -153,7 +153,7 @@ pub fn gen_shim_call_rewrites<'tcx>(
 
 /// Convert an `LTy` to a pair of `TypeDesc`s, one computed normally and one with `FIXED` added.
 /// Returns `None` if the input `LTy` already has `FIXED` set.
-fn lty_to_desc_pair<'tcx>(
+pub(crate) fn lty_to_desc_pair<'tcx>(
     tcx: TyCtxt<'tcx>,
     asn: &Assignment,
     lty: LTy<'tcx>,
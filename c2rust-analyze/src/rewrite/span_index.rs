@@ -1,45 +1,51 @@
 //! Defines the [`SpanIndex`] data structure for looking up MIR statements by span.
+use super::span_like::SpanLike;
 use rustc_span::Span;
 
-/// A mapping from [`Span`]s to values of type `T`.  Allows looking up all items whose span is a
+/// A mapping from spans to values of type `T`.  Allows looking up all items whose span is a
 /// subset of some target span.
+///
+/// Entries are kept sorted by `lo()`, so both kinds of lookup use a binary search to find the
+/// range of entries that could possibly match before filtering it down by `hi()`; this keeps
+/// lookups close to `O(log n + k)` instead of a full `O(n)` scan, where `k` is the number of
+/// entries whose spans start inside the queried span.
 #[derive(Clone, Debug)]
-pub struct SpanIndex<T> {
-    v: Vec<(Span, T)>,
+pub struct SpanIndex<T, S = Span> {
+    v: Vec<(S, T)>,
 }
 
-impl<T> SpanIndex<T> {
+impl<T, S: SpanLike> SpanIndex<T, S> {
     /// Construct a new [`SpanIndex`], containing the items provided by the iterator `it`.
-    pub fn new(it: impl IntoIterator<Item = (Span, T)>) -> SpanIndex<T> {
+    pub fn new(it: impl IntoIterator<Item = (S, T)>) -> SpanIndex<T, S> {
         let mut v = it.into_iter().collect::<Vec<_>>();
         v.sort_by_key(|(span, _)| span.lo());
         SpanIndex { v }
     }
 
-    /// Iterate over items whose spans are entirely contained within `span`.  The order of the
-    /// returned items is unspecified.
-    pub fn _lookup(&self, span: Span) -> impl Iterator<Item = (Span, &T)> {
-        let data = span.data();
-        let start = self.v.partition_point(|(span, _)| span.lo() < data.lo);
+    /// Iterate over items whose spans are entirely contained within `span` (i.e. `span.contains`
+    /// the item's span). The order of the returned items is unspecified.
+    pub fn lookup(&self, span: S) -> impl Iterator<Item = (S, &T)> {
+        let hi = span.hi();
+        let start = self.v.partition_point(|(s, _)| s.lo() < span.lo());
         self.v[start..]
             .iter()
             // The list is sorted by `lo`, so once we move past `span.hi()`, we won't see any more
             // overlapping spans.
-            .take_while(move |&&(s, _)| s.lo() < data.hi)
-            // Skip any spans that extend beyond `span.hi()`.
-            .filter(move |&&(s, _)| s.hi() <= data.hi)
-            .map(|&(s, ref x): &(Span, T)| (s, x))
+            .take_while(move |&&(s, _)| s.lo() < hi)
+            // Skip any spans that aren't fully contained in `span`.
+            .filter(move |&&(s, _)| span.contains(s))
+            .map(|&(s, ref x): &(S, T)| (s, x))
     }
 
     /// Iterate over items whose spans are exactly equal to `span`.  The order of the returned
     /// items is unspecified.
-    pub fn lookup_exact(&self, span: Span) -> impl Iterator<Item = &T> {
-        let data = span.data();
-        let start = self.v.partition_point(|(s, _)| s.lo() < data.lo);
+    pub fn lookup_exact(&self, span: S) -> impl Iterator<Item = &T> {
+        let lo = span.lo();
+        let start = self.v.partition_point(|(s, _)| s.lo() < lo);
         self.v[start..]
             .iter()
             // The list is sorted by `lo`, so once we move past `self.lo`, we won't see it again.
-            .take_while(move |&&(s, _)| s.lo() == data.lo)
+            .take_while(move |&&(s, _)| s.lo() == lo)
             // Only return values for the requested span.  We might see other spans that cover only
             // a prefix of `span` or have a different `SyntaxContext`; we ignore the values
             // associated with those spans.
@@ -47,3 +53,93 @@ impl<T> SpanIndex<T> {
             .map(|&(_, ref t)| t)
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use rustc_span::BytePos;
+
+    #[derive(Clone, Copy, PartialEq, Eq, Debug, Hash)]
+    struct FakeSpan {
+        lo: u32,
+        hi: u32,
+    }
+
+    impl SpanLike for FakeSpan {
+        fn lo(self) -> BytePos {
+            BytePos(self.lo)
+        }
+        fn hi(self) -> BytePos {
+            BytePos(self.hi)
+        }
+        fn contains(self, other: Self) -> bool {
+            self.lo <= other.lo && other.hi <= self.hi
+        }
+        fn overlaps(self, other: Self) -> bool {
+            self.lo < other.hi && other.lo < self.hi
+        }
+    }
+
+    fn mk(lo: u32, hi: u32) -> FakeSpan {
+        FakeSpan { lo, hi }
+    }
+
+    fn index() -> SpanIndex<&'static str, FakeSpan> {
+        SpanIndex::new([
+            (mk(0, 100), "outer"),
+            (mk(0, 40), "left"),
+            (mk(10, 20), "left-inner"),
+            (mk(50, 90), "right"),
+            (mk(60, 70), "right-inner"),
+            (mk(200, 210), "unrelated"),
+        ])
+    }
+
+    fn lookup_names(idx: &SpanIndex<&'static str, FakeSpan>, span: FakeSpan) -> Vec<&'static str> {
+        let mut names = idx.lookup(span).map(|(_, &x)| x).collect::<Vec<_>>();
+        names.sort_unstable();
+        names
+    }
+
+    #[test]
+    fn lookup_returns_contained_items_only() {
+        let idx = index();
+        assert_eq!(lookup_names(&idx, mk(0, 40)), vec!["left", "left-inner"]);
+        assert_eq!(lookup_names(&idx, mk(50, 90)), vec!["right", "right-inner"]);
+    }
+
+    #[test]
+    fn lookup_excludes_partial_overlaps() {
+        let idx = index();
+        // `[30, 60)` overlaps both `left` and `right` but contains neither.
+        assert_eq!(lookup_names(&idx, mk(30, 60)), Vec::<&str>::new());
+    }
+
+    #[test]
+    fn lookup_whole_range_returns_everything_contained() {
+        let idx = index();
+        assert_eq!(
+            lookup_names(&idx, mk(0, 100)),
+            vec!["left", "left-inner", "outer", "right", "right-inner"]
+        );
+    }
+
+    #[test]
+    fn lookup_disjoint_range_returns_nothing() {
+        let idx = index();
+        assert_eq!(lookup_names(&idx, mk(300, 400)), Vec::<&str>::new());
+    }
+
+    #[test]
+    fn lookup_exact_matches_only_identical_spans() {
+        let idx = index();
+        assert_eq!(
+            idx.lookup_exact(mk(0, 40)).copied().collect::<Vec<_>>(),
+            vec!["left"]
+        );
+        assert_eq!(
+            idx.lookup_exact(mk(0, 41)).copied().collect::<Vec<_>>(),
+            Vec::<&str>::new()
+        );
+    }
+}
@@ -0,0 +1,30 @@
+//! Defines [`SpanLike`], a small abstraction over [`Span`] used by other modules in
+//! `rewrite` that need to reason about span positions and containment, so they can be tested
+//! against a fake span type without pulling in the interner/`SourceMap` machinery that real
+//! `Span`s depend on.
+use rustc_span::{BytePos, Span};
+
+/// This trait defines the subset of the [`Span`] API that we use for rewrite bookkeeping.  It's
+/// implemented for `Span` and also for `FakeSpan` types used in tests to avoid dealing with
+/// interner/`SourceMap` machinery.
+pub trait SpanLike: Copy + Eq {
+    fn lo(self) -> BytePos;
+    fn hi(self) -> BytePos;
+    fn contains(self, other: Self) -> bool;
+    fn overlaps(self, other: Self) -> bool;
+}
+
+impl SpanLike for Span {
+    fn lo(self) -> BytePos {
+        self.lo()
+    }
+    fn hi(self) -> BytePos {
+        self.hi()
+    }
+    fn contains(self, other: Self) -> bool {
+        self.contains(other)
+    }
+    fn overlaps(self, other: Self) -> bool {
+        self.overlaps(other)
+    }
+}
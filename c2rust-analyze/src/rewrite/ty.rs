@@ -4,7 +4,7 @@
 //! with the materialization of adjustments in expr rewriting, we try to apply this transformation
 //! selectively, since we don't want to unfold all type aliases in the program.
 
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::ops::Index;
 
 use crate::borrowck::{OriginArg, OriginParam};
@@ -15,7 +15,7 @@ use crate::context::{
 use crate::labeled_ty::{LabeledTy, LabeledTyCtxt};
 use crate::pointee_type::PointeeTypes;
 use crate::pointer_id::{GlobalPointerTable, PointerId, PointerTable};
-use crate::rewrite::Rewrite;
+use crate::rewrite::{Rewrite, RewritePlan};
 use crate::type_desc::{self, Ownership, PtrDesc, Quantity, TypeDesc};
 use hir::{
     FnRetTy, GenericParamKind, Generics, ItemKind, Path, PathSegment, VariantData, WherePredicate,
@@ -86,6 +86,25 @@ fn descendant_has_rewrite(args: &[RwLTy], adt_metadata: &AdtMetadataTable) -> bo
     })
 }
 
+/// Walk `rw_lty` looking for pointers whose ownership was inferred as `Cell` and whose pointee is
+/// exactly a bare generic type parameter `T` (as opposed to some concrete type mentioning `T`).
+/// Rewriting such a pointer wraps `T` in `Cell<T>`, which requires `T: Copy` for the `Cell` to be
+/// usable the same way the raw pointer was (e.g. `Cell::get`), so every parameter found here needs
+/// that bound added to the function's generics.
+fn collect_cell_wrapped_params<'tcx>(rw_lty: RwLTy<'tcx>, out: &mut HashSet<Symbol>) {
+    for node in rw_lty.iter() {
+        if let Some(ptr_desc) = node.label.ty_desc {
+            if ptr_desc.own == Ownership::Cell {
+                if let [pointee] = node.args {
+                    if let TyKind::Param(param_ty) = *pointee.ty.kind() {
+                        out.insert(param_ty.name);
+                    }
+                }
+            }
+        }
+    }
+}
+
 fn create_rewrite_label<'tcx>(
     pointer_lty: LTy<'tcx>,
     args: &[RwLTy<'tcx>],
@@ -685,9 +704,23 @@ impl<'tcx, 'a> intravisit::Visitor<'tcx> for HirTyVisitor<'a, 'tcx> {
     }
 }
 
+/// Assert that `plan` agrees with the `PtrDesc` this pass would independently derive for every
+/// (non-`FIXED`) pointer nested in `lty`.  See [`RewritePlan`].
+fn check_plan_for_lty(lty: LTy, asn: &Assignment, plan: &RewritePlan) {
+    for nested in lty.iter() {
+        let ptr = nested.label;
+        if ptr.is_none() || asn.flags()[ptr].contains(FlagSet::FIXED) {
+            continue;
+        }
+        let desc = type_desc::perms_to_ptr_desc(asn.perms()[ptr], asn.flags()[ptr]);
+        plan.check(ptr, desc);
+    }
+}
+
 pub fn gen_ty_rewrites<'tcx>(
     acx: &AnalysisCtxt<'_, 'tcx>,
     asn: &Assignment,
+    plan: &RewritePlan,
     pointee_types: PointerTable<PointeeTypes<'tcx>>,
     mir: &Body<'tcx>,
     ldid: LocalDefId,
@@ -724,7 +757,11 @@ pub fn gen_ty_rewrites<'tcx>(
     let hir_generics = acx.tcx().hir().get_generics(ldid);
 
     let generics = hir_generics.unwrap_or(Generics::empty());
-    gen_generics_rws(&mut v.hir_rewrites, generics, origin_params.iter());
+
+    // Track generic type parameters that end up wrapped in `Cell` (e.g. a `*mut T` field
+    // rewritten to `&Cell<T>`), so we can add the `T: Copy` bound that `Cell::get` and friends
+    // need.  See `collect_cell_wrapped_params`.
+    let mut cell_wrapped_params = HashSet::new();
 
     let lty_sig = acx.gacx.fn_sigs.get(&ldid.to_def_id()).unwrap();
     assert_eq!(lty_sig.inputs.len(), hir_sig.decl.inputs.len());
@@ -734,6 +771,7 @@ pub fn gen_ty_rewrites<'tcx>(
         .zip(hir_sig.decl.inputs.iter())
         .zip(input_origin_args.iter())
     {
+        check_plan_for_lty(lty, asn, plan);
         let rw_lty =
             rw_lcx.zip_labels_with(lty, origin_args, &mut |pointer_lty, lifetime_lty, args| {
                 create_rewrite_label(
@@ -747,10 +785,12 @@ pub fn gen_ty_rewrites<'tcx>(
                 )
             });
 
+        collect_cell_wrapped_params(rw_lty, &mut cell_wrapped_params);
         v.handle_ty(rw_lty, hir_ty);
     }
 
     if let hir::FnRetTy::Return(hir_ty) = hir_sig.decl.output {
+        check_plan_for_lty(lty_sig.output, asn, plan);
         let output_rw_lty = rw_lcx.zip_labels_with(
             lty_sig.output,
             output_origin_args,
@@ -767,9 +807,19 @@ pub fn gen_ty_rewrites<'tcx>(
             },
         );
 
+        collect_cell_wrapped_params(output_rw_lty, &mut cell_wrapped_params);
         v.handle_ty(output_rw_lty, hir_ty);
     }
 
+    let mut cell_wrapped_params: Vec<_> = cell_wrapped_params.into_iter().collect();
+    cell_wrapped_params.sort();
+    gen_generics_rws(
+        &mut v.hir_rewrites,
+        generics,
+        origin_params.iter(),
+        &cell_wrapped_params,
+    );
+
     let hir_body_id = acx.tcx().hir().body_owned_by(ldid);
     let body = acx.tcx().hir().body(hir_body_id);
     intravisit::Visitor::visit_body(&mut v, body);
@@ -783,6 +833,7 @@ pub fn gen_generics_rws<'p, 'tcx>(
     hir_rewrites: &mut Vec<(Span, Rewrite)>,
     generics: &Generics<'tcx>,
     origin_params: impl Iterator<Item = &'p OriginParam>,
+    extra_copy_bound_params: &[Symbol],
 ) {
     let mut last_lifetime_span: Option<Span> = None;
     let mut first_generic_type_span: Option<Span> = None;
@@ -855,6 +906,26 @@ pub fn gen_generics_rws<'p, 'tcx>(
         };
         hir_rewrites.push((hypothetical_origin_span, Rewrite::Print(format_string)));
     }
+
+    if !extra_copy_bound_params.is_empty() {
+        let bounds_string = extra_copy_bound_params
+            .iter()
+            .map(|name| format!("{}: Copy", name))
+            .collect::<Vec<_>>()
+            .join(", ");
+        let (span, format_string) = if generics.has_where_clause_predicates {
+            (
+                generics.where_clause_span.shrink_to_hi(),
+                format!(", {}", bounds_string),
+            )
+        } else {
+            (
+                generics.where_clause_span,
+                format!(" where {}", bounds_string),
+            )
+        };
+        hir_rewrites.push((span, Rewrite::Print(format_string)));
+    }
 }
 
 pub fn gen_adt_ty_rewrites<'tcx>(
@@ -889,6 +960,7 @@ pub fn gen_adt_ty_rewrites<'tcx>(
         &mut hir_rewrites,
         generics,
         gacx.adt_metadata.table[&did].lifetime_params.iter(),
+        &[],
     );
 
     for field_def in field_defs.iter() {
@@ -0,0 +1,71 @@
+//! Opt-in detection of C function-local `static` variables (transpiled by `c2rust-transpile` into
+//! module-level `static mut` items) that are only ever accessed from a single function.
+//!
+//! A C function-local `static` has single-function access by construction; a faithfully
+//! transpiled one should too, unless some later refactor widened its visibility. A `static` that's
+//! still scoped to exactly one function is a `thread_local!` + `Cell`/`RefCell` candidate rather
+//! than a true global -- but `thread_local!` isn't a representation [`crate::type_desc::Ownership`]
+//! can describe today, so, like [`crate::refcount`] and [`crate::tokenize_loop`], this module only
+//! detects and logs candidates for a human to convert by hand rather than rewriting them itself.
+use crate::context::{const_alloc_id, find_static_for_alloc};
+use rustc_hir::def_id::{DefId, LocalDefId};
+use rustc_middle::mir::visit::Visitor;
+use rustc_middle::mir::{Constant, Location};
+use rustc_middle::ty::{TyCtxt, WithOptConstParam};
+use std::collections::HashMap;
+
+/// Collects every local `static`'s `DefId` referenced by a pointer-typed constant in a single
+/// function body.
+struct StaticRefFinder<'tcx> {
+    tcx: TyCtxt<'tcx>,
+    statics: Vec<DefId>,
+}
+
+impl<'tcx> Visitor<'tcx> for StaticRefFinder<'tcx> {
+    fn visit_constant(&mut self, constant: &Constant<'tcx>, _location: Location) {
+        if !constant.ty().is_any_ptr() {
+            return;
+        }
+        let alloc_id = match const_alloc_id(constant) {
+            Some(x) => x,
+            None => return,
+        };
+        if let Some(did) = find_static_for_alloc(&self.tcx, alloc_id) {
+            self.statics.push(did);
+        }
+    }
+}
+
+/// Returns every local `static` referenced by exactly one of `all_fn_ldids`'s bodies, mapped to
+/// that function's `LocalDefId`.
+pub fn find_single_fn_statics(
+    tcx: TyCtxt,
+    all_fn_ldids: &[LocalDefId],
+) -> HashMap<DefId, LocalDefId> {
+    // `None` means "referenced by more than one function so far"; such entries are dropped below.
+    let mut owner: HashMap<DefId, Option<LocalDefId>> = HashMap::new();
+    for &ldid in all_fn_ldids {
+        let ldid_const = WithOptConstParam::unknown(ldid);
+        let mir = tcx.mir_built(ldid_const);
+        let mir = mir.borrow();
+        let mut finder = StaticRefFinder {
+            tcx,
+            statics: Vec::new(),
+        };
+        finder.visit_body(&mir);
+        for did in finder.statics {
+            owner
+                .entry(did)
+                .and_modify(|slot| {
+                    if *slot != Some(ldid) {
+                        *slot = None;
+                    }
+                })
+                .or_insert(Some(ldid));
+        }
+    }
+    owner
+        .into_iter()
+        .filter_map(|(did, slot)| slot.map(|owner_ldid| (did, owner_ldid)))
+        .collect()
+}
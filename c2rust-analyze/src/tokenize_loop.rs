@@ -0,0 +1,68 @@
+//! Opt-in detection of `strtok`/`strsep`-based tokenization loops.
+//!
+//! `strtok` and `strsep` both tokenize a buffer by mutating it in place (writing a NUL over each
+//! delimiter) while also carrying state across calls -- `strtok` in a hidden static, `strsep` in
+//! the caller-supplied `*saveptr`.  A loop built around either call can't be converted to operate
+//! on a safe reference the naive way, since the mutation and the carried state both need to be
+//! accounted for.
+//!
+//! This module only detects such loops and reports them via `debug!`; rewriting them to iterate
+//! with `split`/`split_mut` over a byte slice instead isn't implemented here.  Doing that
+//! soundly requires recognizing the specific shape of the loop (where the result is stored, where
+//! the loop condition tests for `NULL`/empty, and how the saved position is threaded from one
+//! iteration to the next), which is a much larger rewrite-generation change.
+use crate::util::{ty_callee, Callee, UnknownDefCallee};
+use log::debug;
+use rustc_middle::mir::{BasicBlock, Body, TerminatorKind};
+use rustc_middle::ty::TyCtxt;
+use std::collections::HashSet;
+
+/// libc functions that tokenize a buffer in place while retaining state across calls.
+const TOKENIZING_FNS: &[&str] = &["strtok", "strsep"];
+
+/// Find calls to `strtok`/`strsep` that occur inside a loop in `mir`, i.e. where the call's block
+/// is reachable from one of its own successors.
+pub fn find_tokenize_loop_calls<'tcx>(tcx: TyCtxt<'tcx>, mir: &Body<'tcx>) -> HashSet<BasicBlock> {
+    let mut out = HashSet::new();
+    for (bb, bb_data) in mir.basic_blocks.iter_enumerated() {
+        let func = match bb_data.terminator().kind {
+            TerminatorKind::Call { ref func, .. } => func,
+            _ => continue,
+        };
+        let func_ty = func.ty(mir, tcx);
+        let def_id = match ty_callee(tcx, func_ty) {
+            Callee::UnknownDef(UnknownDefCallee::Direct {
+                def_id,
+                is_foreign: true,
+                ..
+            }) => def_id,
+            _ => continue,
+        };
+        let name = tcx.item_name(def_id);
+        if !TOKENIZING_FNS.contains(&name.as_str()) {
+            continue;
+        }
+        if !is_reachable_from_own_successor(mir, bb) {
+            continue;
+        }
+        debug!("found {name} call in a loop at {bb:?}");
+        out.insert(bb);
+    }
+    out
+}
+
+/// Returns `true` if `bb` can be reached again by following the CFG forward from one of its own
+/// successors, i.e. `bb` is part of a loop.
+fn is_reachable_from_own_successor(mir: &Body, bb: BasicBlock) -> bool {
+    let mut visited = HashSet::new();
+    let mut stack: Vec<BasicBlock> = mir.basic_blocks[bb].terminator().successors().collect();
+    while let Some(cur) = stack.pop() {
+        if cur == bb {
+            return true;
+        }
+        if visited.insert(cur) {
+            stack.extend(mir.basic_blocks[cur].terminator().successors());
+        }
+    }
+    false
+}
@@ -100,9 +100,30 @@ impl Ownership {
             Ownership::Mut | Ownership::Rc | Ownership::Box => false,
         }
     }
+
+    /// Parse the name used for this variant in `C2RUST_ANALYZE_DISABLED_OWNERSHIP` and the
+    /// `C2RUST_ANALYZE_DISABLE_*_LIST` env vars.
+    pub fn from_str(s: &str) -> Option<Self> {
+        match s {
+            "raw" => Some(Ownership::Raw),
+            "raw-mut" => Some(Ownership::RawMut),
+            "imm" => Some(Ownership::Imm),
+            "cell" => Some(Ownership::Cell),
+            "mut" => Some(Ownership::Mut),
+            "rc" => Some(Ownership::Rc),
+            "box" => Some(Ownership::Box),
+            _ => None,
+        }
+    }
 }
 
-fn perms_to_ptr_desc(perms: PermissionSet, flags: FlagSet) -> PtrDesc {
+pub(crate) fn perms_to_ptr_desc(perms: PermissionSet, flags: FlagSet) -> PtrDesc {
+    // NOTE: `FlagSet::NUL_TERMINATED`/`FlagSet::UTF8` aren't consulted here yet.  Picking a
+    // string-flavored target type (`&CStr`, `&str`) instead of a plain `&[T]` would need a new
+    // `Quantity`-like axis here, plus corresponding `CastBuilder` rules and indexing/length
+    // rewrites in `rewrite::expr` for every `Ownership`/`Quantity` combination that type can
+    // appear in -- there's no existing scaffolding for that, so for now the flags are only
+    // tracked on `Assignment::flags` for future use.
     let mut dyn_owned = false;
 
     let own = if perms.contains(PermissionSet::FREE) {
@@ -0,0 +1,90 @@
+//! Opt-in consolidation of a specific repeated non-convertible unsafe pattern --
+//! [`OffsetRawUnsafe`](crate::rewrite::OFFSET_RAW_UNSAFE_COMMENT)'s "offset a raw pointer by a
+//! statically-known negative amount" rewrite -- into calls to a single macro, instead of
+//! repeating `unsafe { p.offset(i) }` (with its explanatory comment) at every call site. This
+//! doesn't make any of those sites safe; it just collects the one unavoidably-unsafe operation
+//! into one place, so a reviewer auditing `unsafe` usage only has to look at it once.
+//!
+//! A real helper *function* would need either a `*mut T`/`*const T` pair of monomorphic
+//! overloads selected by receiver mutability, or an unsound blanket cast between them -- and the
+//! rewrite this matches against doesn't carry which kind of pointer it was applied to, so neither
+//! option is available without threading extra type information through the whole rewrite
+//! pipeline just for this. A `macro_rules!` macro sidesteps the issue, since it operates on
+//! unevaluated token trees rather than on a concrete pointer type, so a single definition already
+//! covers both cases.
+//!
+//! Run only when `C2RUST_ANALYZE_DEDUP_UNSAFE_HELPERS=1`. Like the other opt-in, crate-wide
+//! code-shape rewrites in this tool, this hasn't seen the same amount of testing across
+//! real-world inputs as the main per-statement rewrite pipeline.
+use crate::rewrite::{Rewrite, OFFSET_RAW_UNSAFE_COMMENT};
+use rustc_span::Span;
+use std::env;
+
+pub fn enabled() -> bool {
+    env::var("C2RUST_ANALYZE_DEDUP_UNSAFE_HELPERS").as_deref() == Ok("1")
+}
+
+/// Minimum number of crate-wide occurrences before introducing the macro; below this, the macro
+/// definition itself would add more lines than it saves.
+const MIN_OCCURRENCES: usize = 2;
+
+const MACRO_NAME: &str = "c2rust_offset_raw";
+
+/// If `rw` is the HIR rewrite `expr::convert` emits for the `OffsetRawUnsafe` rewrite kind,
+/// return the receiver and offset-argument rewrites it wraps.
+fn match_offset_raw_unsafe(rw: &Rewrite) -> Option<(&Rewrite, &Rewrite)> {
+    let (inner, comment) = match rw {
+        Rewrite::Commented(inner, comment) => (inner, comment),
+        _ => return None,
+    };
+    if comment != OFFSET_RAW_UNSAFE_COMMENT {
+        return None;
+    }
+    let call = match &**inner {
+        Rewrite::Unsafe(call) => call,
+        _ => return None,
+    };
+    let (method, receiver, args) = match &**call {
+        Rewrite::MethodCall(method, receiver, args) => (method, receiver, args),
+        _ => return None,
+    };
+    if method != "offset" || args.len() != 1 {
+        return None;
+    }
+    Some((receiver, &args[0]))
+}
+
+/// Replace every occurrence of the `OffsetRawUnsafe` pattern in `rewrites` with a call to
+/// `c2rust_offset_raw!`, and append one macro definition to `rewrites` at `insert_span` if at
+/// least `MIN_OCCURRENCES` were replaced. `insert_span` must be a location where inserting a new
+/// item is syntactically valid, e.g. the start of some function item -- which function doesn't
+/// matter, since `macro_rules!` makes the macro visible to every later call site in the same
+/// file via ordinary textual scoping.
+pub fn dedup_offset_raw_unsafe(rewrites: &mut Vec<(Span, Rewrite)>, insert_span: Span) {
+    let matches: Vec<usize> = rewrites
+        .iter()
+        .enumerate()
+        .filter(|(_, (_, rw))| match_offset_raw_unsafe(rw).is_some())
+        .map(|(i, _)| i)
+        .collect();
+    if matches.len() < MIN_OCCURRENCES {
+        return;
+    }
+
+    for i in matches {
+        let (receiver, idx) = match match_offset_raw_unsafe(&rewrites[i].1) {
+            Some((receiver, idx)) => (receiver.clone(), idx.clone()),
+            None => unreachable!(),
+        };
+        rewrites[i].1 = Rewrite::Call(format!("{MACRO_NAME}!"), vec![receiver, idx]);
+    }
+
+    let macro_def = format!(
+        "\nmacro_rules! {MACRO_NAME} {{\n    \
+         ($p:expr, $i:expr) => {{\n        \
+         // {OFFSET_RAW_UNSAFE_COMMENT}\n        \
+         unsafe {{ ($p).offset($i) }}\n    \
+         }};\n}}\n"
+    );
+    rewrites.push((insert_span, Rewrite::Print(macro_def)));
+}
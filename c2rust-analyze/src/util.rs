@@ -14,7 +14,11 @@ use rustc_middle::ty::{
 };
 use rustc_span::symbol::{sym, Symbol};
 use rustc_type_ir::IntTy;
+use std::collections::HashMap;
+use std::env;
 use std::fmt::Debug;
+use std::fs;
+use std::io;
 
 #[derive(Debug)]
 pub enum RvalueDesc<'tcx> {
@@ -197,12 +201,27 @@ pub enum Callee<'tcx> {
     /// libc::memcpy
     Memcpy,
 
+    /// libc::memmove.  Rewritten the same way as [`Memcpy`](Self::Memcpy): once a pointer has
+    /// been inferred to uniquely own its allocation, `src` and `dest` can't alias, so the
+    /// overlap-tolerant semantics that distinguish `memmove` from `memcpy` never actually come
+    /// into play for a rewritten call.
+    Memmove,
+
     /// libc::free
     Free,
 
     /// libc::realloc
     Realloc,
 
+    /// A project-specific allocator wrapper declared via `C2RUST_ANALYZE_CUSTOM_ALLOC_LIST`.
+    /// Treated identically to [`Malloc`](Self::Malloc) everywhere that matches on it, except that
+    /// the size argument may not be at index 0.
+    CustomMalloc { size_arg_idx: usize },
+
+    /// A project-specific deallocator wrapper declared via `C2RUST_ANALYZE_CUSTOM_ALLOC_LIST`.
+    /// Treated identically to [`Free`](Self::Free) everywhere that matches on it.
+    CustomFree,
+
     /// core::ptr::is_null
     IsNull,
 
@@ -211,6 +230,24 @@ pub enum Callee<'tcx> {
 
     /// `core::mem::size_of<T>`
     SizeOf { ty: Ty<'tcx> },
+
+    /// `core::mem::transmute::<T, U>`.  Unlike an ordinary pointer cast, there's no general way to
+    /// know whether `T` and `U` agree on pointee type/representation, so this is always treated as
+    /// an unconstrained reinterpretation -- see `analyze::mark_transmute_ptrs_fixed`.
+    Transmute { from_ty: Ty<'tcx>, to_ty: Ty<'tcx> },
+
+    /// `core::ptr::read_volatile`/`write_volatile`.  See `analyze::mark_volatile_ptrs_fixed`.
+    Volatile {
+        op: VolatileOp,
+        pointee_ty: Ty<'tcx>,
+    },
+}
+
+/// Which of `core::ptr::read_volatile`/`write_volatile` a [`Callee::Volatile`] call is.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum VolatileOp {
+    Read,
+    Write,
 }
 
 pub fn ty_callee<'tcx>(tcx: TyCtxt<'tcx>, ty: Ty<'tcx>) -> Callee<'tcx> {
@@ -343,6 +380,13 @@ fn builtin_callee<'tcx>(tcx: TyCtxt<'tcx>, did: DefId, substs: SubstsRef<'tcx>)
             None
         }
 
+        "memmove" => {
+            if matches!(tcx.def_kind(tcx.parent(did)), DefKind::ForeignMod) {
+                return Some(Callee::Memmove);
+            }
+            None
+        }
+
         "is_null" => {
             // The `offset` inherent method of `*const T` and `*mut T`.
             let parent_did = tcx.parent(did);
@@ -404,13 +448,130 @@ fn builtin_callee<'tcx>(tcx: TyCtxt<'tcx>, did: DefId, substs: SubstsRef<'tcx>)
             Some(Callee::SizeOf { ty })
         }
 
-        _ => {
-            debug!("name: {name:?}");
-            None
+        "transmute" => {
+            // The `core::intrinsics::transmute` intrinsic, re-exported as `core::mem::transmute`.
+            let mut parent_did = tcx.parent(did);
+            // The intrinsic is declared inside an `extern "rust-intrinsic"` block; step past it.
+            if tcx.def_kind(parent_did) == DefKind::ForeignMod {
+                parent_did = tcx.parent(parent_did);
+            }
+            if tcx.def_kind(parent_did) != DefKind::Mod {
+                return None;
+            }
+            if tcx.item_name(parent_did).as_str() != "intrinsics" {
+                return None;
+            }
+            let grandparent_did = tcx.parent(parent_did);
+            if grandparent_did.index != CRATE_DEF_INDEX {
+                return None;
+            }
+            if tcx.crate_name(grandparent_did.krate).as_str() != "core" {
+                return None;
+            }
+            Some(Callee::Transmute {
+                from_ty: substs.type_at(0),
+                to_ty: substs.type_at(1),
+            })
+        }
+
+        name @ "read_volatile" | name @ "write_volatile" => {
+            // The `core::ptr::read_volatile`/`write_volatile` functions.
+            let parent_did = tcx.parent(did);
+            if tcx.def_kind(parent_did) != DefKind::Mod {
+                return None;
+            }
+            if tcx.item_name(parent_did).as_str() != "ptr" {
+                return None;
+            }
+            let grandparent_did = tcx.parent(parent_did);
+            if grandparent_did.index != CRATE_DEF_INDEX {
+                return None;
+            }
+            if tcx.crate_name(grandparent_did.krate).as_str() != "core" {
+                return None;
+            }
+            let op = match name {
+                "read_volatile" => VolatileOp::Read,
+                "write_volatile" => VolatileOp::Write,
+                _ => unreachable!(),
+            };
+            Some(Callee::Volatile {
+                op,
+                pointee_ty: substs.type_at(0),
+            })
         }
+
+        _ => match custom_allocators().get(name.as_str()) {
+            Some(&CustomAllocKind::Malloc { size_arg_idx }) => {
+                Some(Callee::CustomMalloc { size_arg_idx })
+            }
+            Some(&CustomAllocKind::Free) => Some(Callee::CustomFree),
+            None => {
+                debug!("name: {name:?}");
+                None
+            }
+        },
     }
 }
 
+/// A custom allocator or deallocator function, declared via
+/// [`C2RUST_ANALYZE_CUSTOM_ALLOC_LIST`](custom_allocators), that should be treated like
+/// [`Callee::Malloc`]/[`Callee::Free`].
+#[derive(Clone, Copy, Debug)]
+enum CustomAllocKind {
+    Malloc { size_arg_idx: usize },
+    Free,
+}
+
+/// Custom allocator/deallocator function names declared via the `C2RUST_ANALYZE_CUSTOM_ALLOC_LIST`
+/// env var, for projects that wrap `malloc`/`free` in their own functions (e.g. `xmalloc`,
+/// `my_pool_alloc`) -- the tool never sees a call to the real `malloc`/`free`, so without this it
+/// can't infer ownership for anything allocated through the wrapper.
+///
+/// The env var names a file with one declaration per non-empty, non-`#`-prefixed line:
+/// `alloc <name> <size_arg_idx>` for an allocator (`size_arg_idx` is the 0-based index of the
+/// argument giving the allocation size, mirroring `malloc`'s single size argument at index 0), or
+/// `free <name>` for a deallocator. A function matching `<name>` is then recognized as
+/// [`Callee::CustomMalloc`]/[`Callee::CustomFree`], which every place that matches on
+/// [`Callee::Malloc`]/[`Callee::Free`] also matches.
+///
+/// This re-reads and re-parses the list on every call rather than caching it, since this crate has
+/// no existing lazy-static-style caching infrastructure to hang a cache off of; this is only ever
+/// hit for call sites whose callee isn't one of the builtins already matched above, so it's not a
+/// hot path in practice, and the env var lookup below is the only cost at all when the feature is
+/// unused.
+fn custom_allocators() -> HashMap<String, CustomAllocKind> {
+    let path = match env::var("C2RUST_ANALYZE_CUSTOM_ALLOC_LIST") {
+        Ok(path) => path,
+        Err(_) => return HashMap::new(),
+    };
+    let file = fs::File::open(&path).unwrap_or_else(|e| panic!("failed to open {:?}: {}", path, e));
+    let mut map = HashMap::new();
+    for line in io::BufRead::lines(io::BufReader::new(file)) {
+        let line = line.unwrap();
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        match line.split_whitespace().collect::<Vec<_>>().as_slice() {
+            [kind @ "alloc", name, size_arg_idx] => {
+                let size_arg_idx = size_arg_idx.parse().unwrap_or_else(|_| {
+                    panic!(
+                        "{kind} {name}: bad size-argument index {:?} in {:?}",
+                        size_arg_idx, path
+                    )
+                });
+                map.insert(name.to_string(), CustomAllocKind::Malloc { size_arg_idx });
+            }
+            ["free", name] => {
+                map.insert(name.to_string(), CustomAllocKind::Free);
+            }
+            _ => panic!("{:?}: bad line {:?}", path, line),
+        }
+    }
+    map
+}
+
 pub fn lty_project<'tcx, L: Debug>(
     lty: LabeledTy<'tcx, L>,
     proj: &PlaceElem<'tcx>,
@@ -449,6 +610,35 @@ pub fn is_null_const_operand(op: &Operand) -> bool {
     op.constant().copied().map_or(false, is_null_const)
 }
 
+/// Returns `true` if `constant` is a statically-known negative integer, such as the `-1` in
+/// `ptr.offset(-1)`.  Used to detect backward pointer offsets, which the current `OffsetSlice`
+/// rewrite rules can't express (see [`OffsetBoundsMode`] and the rewrite pass's handling of
+/// `Callee::PtrOffset`).
+pub fn is_negative_const(constant: Constant) -> bool {
+    match constant.literal.try_to_scalar() {
+        Some(Scalar::Int(i)) => i.try_to_int(i.size()).map_or(false, |v| v < 0),
+        _ => false,
+    }
+}
+
+pub fn is_negative_const_operand(op: &Operand) -> bool {
+    op.constant().copied().map_or(false, is_negative_const)
+}
+
+/// Returns `true` if `constant` is the integer constant `1`, such as the step in `x = x + 1`.
+/// Used to recognize increment/decrement idioms like manual reference counting (see
+/// [`crate::refcount`]).
+pub fn is_one_const(constant: Constant) -> bool {
+    match constant.literal.try_to_scalar() {
+        Some(Scalar::Int(i)) => i.try_to_int(i.size()).map_or(false, |v| v == 1),
+        _ => false,
+    }
+}
+
+pub fn is_one_const_operand(op: &Operand) -> bool {
+    op.constant().copied().map_or(false, is_one_const)
+}
+
 pub trait PhantomLifetime<'a> {}
 impl<'a, T: ?Sized> PhantomLifetime<'a> for T {}
 
@@ -536,6 +726,113 @@ pub fn is_transmutable_ptr_cast<'tcx>(from: Ty<'tcx>, to: Ty<'tcx>) -> Option<bo
     Some(is_transmutable_to(from, to))
 }
 
+/// Returns `true` if `ty` is `c_void` (from `libc` or `std::ffi`).  A cast to or from a pointer to
+/// `c_void` is the common C idiom for an untyped "generic" pointer (as produced by `malloc` or
+/// accepted by callback-registration APIs), and doesn't by itself indicate that the code is doing
+/// anything unsound with the pointee type.
+pub fn is_c_void<'tcx>(tcx: TyCtxt<'tcx>, ty: Ty<'tcx>) -> bool {
+    match ty.ty_adt_def() {
+        Some(adt_def) => tcx.item_name(adt_def.did()).as_str() == "c_void",
+        None => false,
+    }
+}
+
+/// Returns `true` if `from as to` is a pointer cast where either the source or target pointee is
+/// `c_void` (see [`is_c_void`]).  Such casts are the common C "generic pointer" idiom and are
+/// benign: once the concrete pointee type is known on one side, the `c_void` side carries no type
+/// information worth preserving.
+pub fn is_benign_void_ptr_cast<'tcx>(tcx: TyCtxt<'tcx>, from: Ty<'tcx>, to: Ty<'tcx>) -> bool {
+    let from_pointee = from.builtin_deref(true).map(|tm| tm.ty);
+    let to_pointee = to.builtin_deref(true).map(|tm| tm.ty);
+    match (from_pointee, to_pointee) {
+        (Some(from_pointee), Some(to_pointee)) => {
+            is_c_void(tcx, from_pointee) || is_c_void(tcx, to_pointee)
+        }
+        _ => false,
+    }
+}
+
+/// Controls the form of the bounds check emitted for an `OffsetSlice` rewrite (`ptr.offset(i)` ->
+/// some indexing expression).  The default, [`Checked`](Self::Checked), panics on out-of-bounds
+/// access just like today's unconditional slice indexing.  [`Option`](Self::Option) and
+/// [`Unchecked`](Self::Unchecked) trade that panic for, respectively, an `Option`-returning
+/// `get`/`get_mut` call or an `unsafe` `get_unchecked`/`get_unchecked_mut` call, for release
+/// pipelines that can't tolerate the panic or that need to avoid the bounds check entirely.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Hash)]
+pub enum OffsetBoundsMode {
+    /// `&ptr[i..]` / `&mut ptr[i..]` — out-of-bounds access panics.
+    Checked,
+    /// `ptr.get(i..)` / `ptr.get_mut(i..)` — out-of-bounds access yields `None`, which the
+    /// caller is responsible for handling.
+    Option,
+    /// `unsafe { ptr.get_unchecked(i..) }` / `unsafe { ptr.get_unchecked_mut(i..) }` — no bounds
+    /// check at all; out-of-bounds access is undefined behavior.
+    Unchecked,
+}
+
+impl Default for OffsetBoundsMode {
+    fn default() -> Self {
+        OffsetBoundsMode::Checked
+    }
+}
+
+impl OffsetBoundsMode {
+    pub fn from_str(s: &str) -> Option<Self> {
+        match s {
+            "checked" => Some(OffsetBoundsMode::Checked),
+            "option" => Some(OffsetBoundsMode::Option),
+            "unchecked" => Some(OffsetBoundsMode::Unchecked),
+            _ => None,
+        }
+    }
+}
+
+/// A strategy profile controlling how aggressively the analysis is allowed to trade safety for
+/// performance or for a smaller diff, on a per-function basis (see
+/// [`rewrite_strategy_overrides`](crate::context::GlobalAnalysisCtxt::rewrite_strategy_overrides)).
+/// Currently this only selects the default [`OffsetBoundsMode`]; it doesn't yet affect the choice
+/// of `RewriteKind` or `Ownership` more broadly.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Hash)]
+pub enum RewriteStrategy {
+    /// Prefer the safest available rewrite, even at the cost of a runtime check or an added
+    /// `Option`/`Rc`.  This is the default, and is suitable for glue code that isn't
+    /// performance-sensitive.
+    SafetyFirst,
+    /// Prefer the fastest available rewrite, such as an unchecked bounds access, for hot inner
+    /// loops where the cost of a panic-on-out-of-bounds check or an extra indirection matters.
+    PerformanceFirst,
+    /// Prefer whichever rewrite produces the smallest diff from the original code, for code
+    /// that's risky to touch (e.g. code with no test coverage).
+    MinimalChurn,
+}
+
+impl Default for RewriteStrategy {
+    fn default() -> Self {
+        RewriteStrategy::SafetyFirst
+    }
+}
+
+impl RewriteStrategy {
+    pub fn from_str(s: &str) -> Option<Self> {
+        match s {
+            "safety-first" => Some(RewriteStrategy::SafetyFirst),
+            "performance-first" => Some(RewriteStrategy::PerformanceFirst),
+            "minimal-churn" => Some(RewriteStrategy::MinimalChurn),
+            _ => None,
+        }
+    }
+
+    /// The [`OffsetBoundsMode`] implied by this strategy, absent a more specific
+    /// `OffsetBoundsMode` override for the same function.
+    pub fn default_offset_bounds_mode(&self) -> OffsetBoundsMode {
+        match self {
+            RewriteStrategy::SafetyFirst => OffsetBoundsMode::Checked,
+            RewriteStrategy::PerformanceFirst => OffsetBoundsMode::Unchecked,
+            RewriteStrategy::MinimalChurn => OffsetBoundsMode::Checked,
+        }
+    }
+}
+
 #[derive(Clone, Copy, PartialEq, Eq, Debug, Hash)]
 pub enum TestAttr {
     /// `#[c2rust_analyze_test::fixed_signature]`: Mark all pointers in the function signature as
@@ -0,0 +1,95 @@
+//! Detection of C "vtable" struct shapes -- a struct whose fields are mostly or entirely
+//! function pointers, often transpiled from a C struct that was used to simulate an interface
+//! (dispatch through a struct of `fn` pointers, optionally alongside one opaque `void*` used as
+//! a per-instance context argument). Such a struct is usually a good candidate for conversion
+//! into a Rust trait, with each concrete initializer becoming a `impl Trait for ConcreteType`
+//! and each indirect call through the struct becoming an ordinary trait method call on a
+//! `Box<dyn Trait>`.
+//!
+//! This is detection only. Actually performing that conversion needs: generating a trait
+//! definition with one method per function-pointer field, generating an impl for every distinct
+//! initializer of the struct found in the crate, and rewriting every indirect call site (which
+//! first has to be matched up with the struct instance it's calling through) into a trait method
+//! call -- that's a much larger change than a single heuristic pass can safely make without a
+//! human checking that the initializers found really are the complete set of implementations.
+//! For now, candidates are only logged, so a human can decide whether the conversion is worth
+//! doing by hand.
+//!
+//! Run only when `C2RUST_ANALYZE_DETECT_VTABLE_STRUCTS=1`.
+use rustc_hir::def::DefKind;
+use rustc_middle::ty::{self, TyCtxt};
+use std::env;
+
+pub fn enabled() -> bool {
+    env::var("C2RUST_ANALYZE_DETECT_VTABLE_STRUCTS").as_deref() == Ok("1")
+}
+
+/// A struct found to have a vtable-like shape: at least [`MIN_FN_PTR_FIELDS`] function-pointer
+/// fields, plus at most one other field (the presumed context/`self` pointer).
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct VtableStructCandidate {
+    pub adt_name: String,
+    pub fn_ptr_fields: Vec<String>,
+    pub context_field: Option<String>,
+}
+
+/// Minimum number of function-pointer fields before a struct is considered vtable-shaped rather
+/// than just "happens to have a callback field".
+const MIN_FN_PTR_FIELDS: usize = 2;
+
+/// Is `ty` a function pointer, or the `Option<fn ptr>` shape `c2rust-transpile` generates for a
+/// C function pointer field that may be null?
+fn is_fn_ptr_like(tcx: TyCtxt<'_>, ty: ty::Ty<'_>) -> bool {
+    match ty.kind() {
+        ty::FnPtr(_) => true,
+        // `Option<fn(..)>`: a two-variant enum whose only field (in `Some`) is a function pointer.
+        ty::Adt(adt_def, _)
+            if adt_def.is_enum()
+                && adt_def.variants().len() == 2
+                && adt_def.all_fields().count() == 1 =>
+        {
+            let inner = adt_def.all_fields().next().unwrap();
+            is_fn_ptr_like(tcx, tcx.type_of(inner.did))
+        }
+        _ => false,
+    }
+}
+
+/// Find all local structs that look like a C vtable: a majority of their fields are function
+/// pointers, with at most one non-function-pointer field left over (the presumed context
+/// pointer).
+pub fn find_vtable_struct_candidates(tcx: TyCtxt) -> Vec<VtableStructCandidate> {
+    let mut out = Vec::new();
+    for ldid in tcx.hir_crate_items(()).definitions() {
+        if tcx.def_kind(ldid) != DefKind::Struct {
+            continue;
+        }
+        let adt_def = tcx.adt_def(ldid);
+        let variant = adt_def.non_enum_variant();
+        if variant.fields.is_empty() {
+            continue;
+        }
+
+        let mut fn_ptr_fields = Vec::new();
+        let mut other_fields = Vec::new();
+        for field in &variant.fields {
+            let ty = tcx.type_of(field.did);
+            if is_fn_ptr_like(tcx, ty) {
+                fn_ptr_fields.push(field.name.to_string());
+            } else {
+                other_fields.push(field.name.to_string());
+            }
+        }
+
+        if fn_ptr_fields.len() < MIN_FN_PTR_FIELDS || other_fields.len() > 1 {
+            continue;
+        }
+
+        out.push(VtableStructCandidate {
+            adt_name: tcx.item_name(ldid.to_def_id()).to_string(),
+            fn_ptr_fields,
+            context_field: other_fields.into_iter().next(),
+        });
+    }
+    out
+}
@@ -283,6 +283,66 @@ impl FileCheck {
     }
 }
 
+/// Snapshot-tests the rewritten source that `c2rust-analyze` produces for a fixture, as an
+/// alternative to matching `CHECK` directives against the debug output.
+///
+/// The fixture must request `--env C2RUST_ANALYZE_REWRITE_MODE=alongside` so that a `.new.rs`
+/// file is produced.  [`Snapshot::check`] runs the analyzer on a scratch copy of the fixture (so
+/// the `.new.rs` artifact doesn't land next to the checked-in `.rs` file) and compares it against
+/// a `.expected` file committed alongside the fixture.  Run with `BLESS=1` to write or update the
+/// `.expected` file from the current output instead of checking it.
+pub struct Snapshot;
+
+impl Snapshot {
+    pub fn check(rs_path: impl AsRef<Path>) {
+        let rs_path = rs_path.as_ref();
+
+        let scratch_dir = env::temp_dir().join(format!(
+            "c2rust-analyze-snapshot-{}",
+            rs_path.file_stem().unwrap().to_string_lossy()
+        ));
+        fs::create_dir_all(&scratch_dir).unwrap();
+        let scratch_rs_path = scratch_dir.join(rs_path.file_name().unwrap());
+        fs::copy(rs_path, &scratch_rs_path).unwrap();
+
+        Analyze::resolve().run(&scratch_rs_path);
+
+        let expected_path = {
+            let mut file_name = rs_path.file_name().unwrap().to_owned();
+            file_name.push(".expected");
+            rs_path.with_file_name(file_name)
+        };
+        let new_path = scratch_rs_path.with_extension("new.rs");
+
+        if !new_path.exists() {
+            assert!(
+                !expected_path.exists(),
+                "expected a rewritten snapshot at {expected_path:?}, but c2rust-analyze \
+                 produced no rewrites for {rs_path:?}"
+            );
+            return;
+        }
+        let actual = fs::read_to_string(&new_path).unwrap();
+
+        if env::var_os("BLESS").is_some() {
+            fs::write(&expected_path, &actual).unwrap();
+            return;
+        }
+
+        let expected = fs::read_to_string(&expected_path).unwrap_or_else(|e| {
+            panic!(
+                "failed to read expected snapshot {expected_path:?}: {e} \
+                 (run with BLESS=1 to create it)"
+            )
+        });
+        assert_eq!(
+            actual, expected,
+            "rewritten output for {rs_path:?} doesn't match {expected_path:?} \
+             (run with BLESS=1 to update it)"
+        );
+    }
+}
+
 fn list_all_tests<C: FromIterator<String>>() -> C {
     let current_exe = env::current_exe().unwrap();
     let output = Command::new(&current_exe)
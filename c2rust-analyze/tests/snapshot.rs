@@ -0,0 +1,32 @@
+pub mod common;
+
+use crate::common::{check_for_missing_tests_for, test_dir_for, Snapshot};
+
+#[test]
+fn check_for_missing_tests() {
+    check_for_missing_tests_for(file!());
+}
+
+fn test(file_name: &str) {
+    let path = test_dir_for(file!(), true).join(file_name);
+    Snapshot::check(path);
+}
+
+macro_rules! define_test {
+    ($name:ident) => {
+        #[test]
+        fn $name() {
+            test(concat!(stringify!($name), ".rs"));
+        }
+    };
+}
+
+macro_rules! define_tests {
+    ($($name:ident,)*) => {
+        $(define_test! { $name })*
+    }
+}
+
+define_tests! {
+    pointer_free,
+}
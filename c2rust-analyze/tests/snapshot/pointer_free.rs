@@ -0,0 +1,8 @@
+//! --env C2RUST_ANALYZE_REWRITE_MODE=alongside
+
+// This function has no pointer-typed locals or operations at all, so `gen_expr_rewrites`'s
+// pointer-free pre-scan should skip it entirely and the analyzer should produce no rewrites (and
+// therefore no `.new.rs` file) for this crate.
+pub fn add(a: i32, b: i32) -> i32 {
+    a + b
+}
@@ -1621,6 +1621,57 @@ impl Builder {
         }))
     }
 
+    pub fn trait_impl_item(
+        self,
+        trait_path: Path,
+        ty: Box<Type>,
+        items: Vec<ImplItem>,
+    ) -> Box<Item> {
+        Box::new(Item::Impl(ItemImpl {
+            attrs: self.attrs,
+            unsafety: self.unsafety.to_token(),
+            defaultness: Defaultness::Final.to_token(),
+            generics: self.generics,
+            trait_: Some((None, trait_path, Token![for](self.span))),
+            self_ty: ty,
+            impl_token: Token![impl](self.span),
+            brace_token: token::Brace(self.span),
+            items,
+        }))
+    }
+
+    pub fn method_impl_item<S>(self, sig: S, block: Block) -> ImplItem
+    where
+        S: Make<Signature>,
+    {
+        let sig = sig.make(&self);
+        ImplItem::Fn(ImplItemFn {
+            attrs: self.attrs,
+            vis: self.vis,
+            defaultness: None,
+            sig,
+            block,
+        })
+    }
+
+    pub fn assoc_type_impl_item<I>(self, name: I, ty: Box<Type>) -> ImplItem
+    where
+        I: Make<Ident>,
+    {
+        let name = name.make(&self);
+        ImplItem::Type(ImplItemType {
+            attrs: self.attrs,
+            vis: self.vis,
+            defaultness: None,
+            type_token: Token![type](self.span),
+            ident: name,
+            generics: self.generics,
+            eq_token: Token![=](self.span),
+            ty: *ty,
+            semi_token: Token![;](self.span),
+        })
+    }
+
     pub fn extern_crate_item<I>(self, name: I, rename: Option<I>) -> Box<Item>
     where
         I: Make<Ident>,
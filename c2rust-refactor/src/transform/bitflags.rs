@@ -0,0 +1,369 @@
+//! Detect clusters of related integer `#define`-derived constants and
+//! rewrite them into a single `bitflags!`-style type or a plain Rust enum,
+//! updating comparisons, matches, and bitwise operations in place.
+
+use rustc_ast::mut_visit::{self, MutVisitor};
+use rustc_ast::ptr::P;
+use rustc_ast::*;
+use rustc_ast_pretty::pprust;
+use rustc_session::Session;
+use rustc_span::symbol::{Ident, Symbol};
+use smallvec::smallvec;
+
+use std::collections::HashMap;
+
+use crate::ast_manip::{FlatMapNodes, MutVisitNodes};
+use crate::command::{CommandState, Registry};
+use crate::driver::{parse_expr, parse_items};
+use crate::transform::Transform;
+use crate::RefactorCtxt;
+
+/// A single `const` item that is a candidate for inclusion in a generated
+/// flags/enum type, along with the integer value it was initialized to.
+struct FlagConst {
+    ident: Ident,
+    value: u128,
+    /// Source text of the const's own declared type (`u32`, `libc::c_uint`,
+    /// ...), used as the generated wrapper type's representation so use
+    /// sites that mix the wrapper with the original-typed C variable keep
+    /// type-checking.
+    ty: String,
+}
+
+/// Group `const` items that share a common name prefix (e.g. `FOO_BAR_A`,
+/// `FOO_BAR_B`), dropping groups with fewer than two members. Values are not
+/// inspected here; [`build_cluster`] decides between a flags type and an enum.
+fn group_by_prefix(consts: &[FlagConst]) -> HashMap<String, Vec<usize>> {
+    let mut groups: HashMap<String, Vec<usize>> = HashMap::new();
+    for (idx, c) in consts.iter().enumerate() {
+        let name = c.ident.as_str();
+        let prefix = match name.rfind('_') {
+            Some(pos) if pos > 0 => name[..pos].to_string(),
+            _ => continue,
+        };
+        groups.entry(prefix).or_default().push(idx);
+    }
+    groups.retain(|_, members| members.len() >= 2);
+    groups
+}
+
+fn is_power_of_two_or_zero(v: u128) -> bool {
+    v == 0 || (v & (v - 1)) == 0
+}
+
+/// Extract a literal integer value from a `const` item's initializer, if
+/// it's a plain integer literal (no further evaluation is attempted; consts
+/// built from arithmetic on other consts are left alone).
+fn const_int_value(expr: &Expr) -> Option<u128> {
+    match &expr.kind {
+        ExprKind::Lit(Lit {
+            kind: LitKind::Int(value, _),
+            ..
+        }) => Some(*value),
+        _ => None,
+    }
+}
+
+/// The declared type shared by every member of a cluster, or `None` if the
+/// members don't all agree (in which case the caller falls back to a type
+/// wide enough to hold every member's value).
+fn common_member_ty(members: &[usize], consts: &[FlagConst]) -> Option<&str> {
+    let mut tys = members.iter().map(|&idx| consts[idx].ty.as_str());
+    let first = tys.next()?;
+    if tys.all(|ty| ty == first) {
+        Some(first)
+    } else {
+        None
+    }
+}
+
+/// `u32` if the largest member value `max` fits in it, otherwise `u64`;
+/// used as a fallback when a cluster's members don't all share one declared
+/// type, and as the `#[repr(..)]` type for a generated enum (which, unlike a
+/// newtype's field, can't be an arbitrary type alias like `libc::c_uint`).
+fn narrow_int_ty(max: u128) -> &'static str {
+    if max <= u32::MAX as u128 {
+        "u32"
+    } else {
+        "u64"
+    }
+}
+
+/// The primitive integer type to use in a generated enum's `#[repr(..)]`
+/// attribute for a cluster whose members are declared `member_ty`. `repr`
+/// only accepts primitive integer type names, not aliases such as
+/// `libc::c_uint`, so common libc aliases are mapped to their underlying
+/// primitive and anything else falls back to [`narrow_int_ty`].
+fn repr_int_ty(member_ty: &str, max: u128) -> &'static str {
+    match member_ty {
+        "u8" => "u8",
+        "u16" => "u16",
+        "u32" => "u32",
+        "u64" => "u64",
+        "usize" => "usize",
+        "i8" => "i8",
+        "i16" => "i16",
+        "i32" => "i32",
+        "i64" => "i64",
+        "isize" => "isize",
+        "libc::c_uchar" => "u8",
+        "libc::c_ushort" => "u16",
+        "libc::c_uint" => "u32",
+        "libc::c_ulong" | "libc::c_ulonglong" | "libc::size_t" => "u64",
+        "libc::c_char" | "libc::c_schar" => "i8",
+        "libc::c_short" => "i16",
+        "libc::c_int" => "i32",
+        "libc::c_long" | "libc::c_longlong" => "i64",
+        _ => narrow_int_ty(max),
+    }
+}
+
+/// Turn a `SCREAMING_SNAKE_CASE` prefix like `FOO_BAR` into `FooBar`, for use
+/// as part of a generated type name.
+fn camel_case(prefix: &str) -> String {
+    prefix
+        .split('_')
+        .filter(|part| !part.is_empty())
+        .map(|part| {
+            let mut chars = part.chars();
+            match chars.next() {
+                Some(first) => first.to_uppercase().collect::<String>() + chars.as_str(),
+                None => String::new(),
+            }
+        })
+        .collect()
+}
+
+/// A cluster of constants, together with the generated type that replaces
+/// them and how to rewrite a use of each member in expression position.
+struct Cluster {
+    /// Name of the generated type.
+    ty_name: String,
+    /// Source text for the generated type (and its trait impls, for a flags
+    /// cluster).
+    item_text: String,
+    /// `(original const name, expression to replace a use of it with)`.
+    member_rewrites: Vec<(Symbol, String)>,
+    /// Name of the first member, used to pick where to splice `item_text`
+    /// in relative to the original `const` items (which are left in place,
+    /// so that any use this pass doesn't know how to rewrite -- notably,
+    /// `match` patterns, since a pattern can't name an arbitrary expression
+    /// -- still compiles against the original `const`).
+    first_member: Symbol,
+}
+
+fn build_cluster(prefix: &str, members: &[usize], consts: &[FlagConst]) -> Cluster {
+    let all_flaggy = members
+        .iter()
+        .all(|&idx| is_power_of_two_or_zero(consts[idx].value));
+    let ty_base = camel_case(prefix);
+    let max_value = members.iter().map(|&idx| consts[idx].value).max().unwrap();
+    let member_ty = common_member_ty(members, consts)
+        .map(str::to_string)
+        .unwrap_or_else(|| narrow_int_ty(max_value).to_string());
+
+    let mut member_rewrites = Vec::with_capacity(members.len());
+    let item_text = if all_flaggy {
+        let ty = format!("{}Flags", ty_base);
+        let mut consts_text = String::new();
+        for &idx in members {
+            let name = consts[idx].ident.name;
+            let value = consts[idx].value;
+            consts_text.push_str(&format!("    pub const {name}: {ty} = {ty}({value});\n"));
+            member_rewrites.push((name, format!("{ty}::{name}.0")));
+        }
+        format!(
+            "#[derive(Clone, Copy, PartialEq, Eq, Debug)]\n\
+             pub struct {ty}(pub {member_ty});\n\
+             \n\
+             impl {ty} {{\n{consts_text}}}\n\
+             \n\
+             impl std::ops::BitOr for {ty} {{\n\
+             \x20   type Output = {ty};\n\
+             \x20   fn bitor(self, rhs: {ty}) -> {ty} {{\n\
+             \x20       {ty}(self.0 | rhs.0)\n\
+             \x20   }}\n\
+             }}\n\
+             \n\
+             impl std::ops::BitAnd for {ty} {{\n\
+             \x20   type Output = {ty};\n\
+             \x20   fn bitand(self, rhs: {ty}) -> {ty} {{\n\
+             \x20       {ty}(self.0 & rhs.0)\n\
+             \x20   }}\n\
+             }}\n",
+            ty = ty,
+            member_ty = member_ty,
+            consts_text = consts_text,
+        )
+    } else {
+        let ty = format!("{}Kind", ty_base);
+        let repr_ty = repr_int_ty(&member_ty, max_value);
+        let mut variants_text = String::new();
+        for &idx in members {
+            let name = consts[idx].ident.name;
+            let value = consts[idx].value;
+            variants_text.push_str(&format!("    {name} = {value},\n"));
+            member_rewrites.push((name, format!("({ty}::{name} as {member_ty})")));
+        }
+        format!(
+            "#[derive(Clone, Copy, PartialEq, Eq, Debug)]\n\
+             #[repr({repr_ty})]\n\
+             pub enum {ty} {{\n{variants_text}}}\n",
+            ty = ty,
+            repr_ty = repr_ty,
+            variants_text = variants_text,
+        )
+    };
+
+    let ty_name = if all_flaggy {
+        format!("{}Flags", ty_base)
+    } else {
+        format!("{}Kind", ty_base)
+    };
+
+    Cluster {
+        ty_name,
+        item_text,
+        member_rewrites,
+        first_member: consts[members[0]].ident.name,
+    }
+}
+
+/// Rewrite every bare use of a clustered constant in expression position
+/// (comparisons, bitwise operations, plain references, ...) to go through
+/// its generated type instead. `match` patterns are left alone, since a
+/// pattern can only name a `const` item, not an arbitrary expression.
+struct UseRewriter<'a> {
+    rewrites: &'a HashMap<Symbol, String>,
+    sess: &'a Session,
+}
+
+impl MutVisitor for UseRewriter<'_> {
+    fn visit_expr(&mut self, e: &mut P<Expr>) {
+        let replacement = match &e.kind {
+            ExprKind::Path(None, path) => match path.segments.as_slice() {
+                [segment] => self.rewrites.get(&segment.ident.name),
+                _ => None,
+            },
+            _ => None,
+        };
+        if let Some(replacement) = replacement {
+            e.kind = parse_expr(self.sess, replacement).into_inner().kind;
+            return;
+        }
+        mut_visit::noop_visit_expr(e, self);
+    }
+}
+
+/// # `int_flags_to_bitflags` Command
+///
+/// Usage: `int_flags_to_bitflags`
+///
+/// Find groups of two or more top-level integer `const` items that share a
+/// name prefix, generate a `bitflags!`-style newtype for each group whose
+/// values are all powers of two (or zero) and a plain `enum` for every
+/// other group, and rewrite every use
+/// of a grouped constant in expression position -- comparisons, bitwise
+/// tests, plain references -- to go through the generated type. Use
+/// `list_bitflags_candidates` first to preview the groups this command
+/// would act on.
+///
+/// The original `const` items are left in place rather than removed, so
+/// that `match` arms naming one of them (which can't be rewritten, since a
+/// pattern can't name an arbitrary expression) keep compiling unchanged.
+pub struct IntFlagsToBitflags;
+
+impl Transform for IntFlagsToBitflags {
+    fn transform(&self, krate: &mut Crate, _st: &CommandState, cx: &RefactorCtxt) {
+        let mut consts = Vec::new();
+        FlatMapNodes::visit(krate, |item: P<Item>| {
+            if let ItemKind::Const(_, ty, Some(expr)) = &item.kind {
+                if let Some(value) = const_int_value(expr) {
+                    consts.push(FlagConst {
+                        ident: item.ident,
+                        value,
+                        ty: pprust::ty_to_string(ty),
+                    });
+                }
+            }
+            smallvec![item]
+        });
+
+        let groups = group_by_prefix(&consts);
+        let mut clusters = Vec::with_capacity(groups.len());
+        for (prefix, members) in &groups {
+            let cluster = build_cluster(prefix, members, &consts);
+            log::info!(
+                "int_flags_to_bitflags: generating `{}` for cluster `{}` ({} members)",
+                cluster.ty_name,
+                prefix,
+                members.len()
+            );
+            clusters.push(cluster);
+        }
+        if clusters.is_empty() {
+            return;
+        }
+
+        let mut generated_items: HashMap<Symbol, Vec<P<Item>>> = HashMap::new();
+        let mut rewrites: HashMap<Symbol, String> = HashMap::new();
+        for cluster in clusters {
+            let items = parse_items(cx.session(), &cluster.item_text);
+            generated_items.insert(cluster.first_member, items);
+            rewrites.extend(cluster.member_rewrites);
+        }
+
+        FlatMapNodes::visit(krate, |item: P<Item>| {
+            let mut out = smallvec![];
+            if let Some(extra) = generated_items.remove(&item.ident.name) {
+                out.extend(extra);
+            }
+            out.push(item);
+            out
+        });
+
+        krate.visit(&mut UseRewriter {
+            rewrites: &rewrites,
+            sess: cx.session(),
+        });
+    }
+}
+
+/// # `list_bitflags_candidates` Command
+///
+/// Usage: `list_bitflags_candidates`
+///
+/// Print the flag-constant clusters that `int_flags_to_bitflags` would
+/// operate on, without modifying the crate. Useful for reviewing a
+/// transpiled crate before committing to the rewrite.
+pub struct ListBitflagsCandidates;
+
+impl Transform for ListBitflagsCandidates {
+    fn transform(&self, krate: &mut Crate, _st: &CommandState, _cx: &RefactorCtxt) {
+        let mut consts = Vec::new();
+        MutVisitNodes::visit(krate, |item: &mut P<Item>| {
+            if let ItemKind::Const(_, ty, Some(expr)) = &item.kind {
+                if let Some(value) = const_int_value(expr) {
+                    consts.push(FlagConst {
+                        ident: item.ident,
+                        value,
+                        ty: pprust::ty_to_string(ty),
+                    });
+                }
+            }
+        });
+
+        for (prefix, members) in group_by_prefix(&consts) {
+            let names: Vec<Symbol> = members.iter().map(|&i| consts[i].ident.name).collect();
+            println!("{}: {:?}", prefix, names);
+        }
+    }
+}
+
+pub fn register_commands(reg: &mut Registry) {
+    use super::mk;
+    reg.register("int_flags_to_bitflags", |_args| mk(IntFlagsToBitflags));
+    reg.register("list_bitflags_candidates", |_args| {
+        mk(ListBitflagsCandidates)
+    });
+}
@@ -367,10 +367,99 @@ impl Transform for RemoveUnusedLabels {
     }
 }
 
+/// # `reconstruct_single_exit_loop` Command
+///
+/// Usage: `reconstruct_single_exit_loop`
+///
+/// The relooper sometimes emits a labeled `loop { ...; break 'label; }`
+/// purely to give a block a single exit point, as part of emulating a
+/// `goto`-based state machine. When such a loop's body runs straight
+/// through to a `break` of its own label with no other `break`/`continue`
+/// of that label anywhere in the body, the loop contributes no control
+/// flow at all: it executes its body exactly once. This command replaces
+/// the loop with a plain block containing the same body, dropping the
+/// `break`.
+///
+/// Since the pattern's `$body:MultiStmt` would otherwise happily match a
+/// body that itself contains a nested `break 'label`/`continue 'label`
+/// (e.g. inside an `if`), this command checks the body for any such nested
+/// use of the label before firing, the same way `remove_unused_labels`
+/// checks for the absence of one.
+///
+/// This is a conservative special case of the general reducibility
+/// analysis the relooper itself uses; it only fires on loops that are
+/// already in exactly this trivial shape; the rest of
+/// `GOTO`/label-emulation that the relooper could not reduce is not
+/// touched.
+pub struct ReconstructSingleExitLoop;
+
+impl Transform for ReconstructSingleExitLoop {
+    fn transform(&self, krate: &mut Crate, st: &CommandState, cx: &RefactorCtxt) {
+        let mut mcx = MatchCtxt::new(st, cx);
+        let pat = mcx.parse_expr(
+            r#"
+                $'label:Ident: loop {
+                    $body:MultiStmt;
+                    break $'label;
+                }
+            "#,
+        );
+        let repl = mcx.parse_expr(
+            r#"
+                {
+                    $body;
+                }
+            "#,
+        );
+
+        let find_continue = mcx.parse_expr("continue $'label");
+        let find_break = mcx.parse_expr("break $'label");
+        let find_break_expr = mcx.parse_expr("break $'label $bv:Expr");
+
+        mut_visit_match_with(mcx, pat, krate, |orig, mcx| {
+            let body = mcx.bindings.get::<_, Vec<Stmt>>("$body").unwrap();
+            // Same clone-per-check approach as `remove_unused_labels_from_loop_kind`; see the
+            // `TODO` there about avoiding the clones.
+            let has_nested_use_of_label = find_first(
+                st,
+                cx,
+                find_continue.clone().subst(st, cx, &mcx.bindings),
+                &mut body.clone(),
+            )
+            .is_some()
+                || find_first(
+                    st,
+                    cx,
+                    find_break.clone().subst(st, cx, &mcx.bindings),
+                    &mut body.clone(),
+                )
+                .is_some()
+                || find_first(
+                    st,
+                    cx,
+                    find_break_expr.clone().subst(st, cx, &mcx.bindings),
+                    &mut body.clone(),
+                )
+                .is_some();
+            if has_nested_use_of_label {
+                debug!(
+                    "reconstruct_single_exit_loop: body has a nested break/continue of its own \
+                     label, leaving loop in place"
+                );
+                return;
+            }
+            *orig = repl.clone().subst(st, cx, &mcx.bindings);
+        });
+    }
+}
+
 pub fn register_commands(reg: &mut Registry) {
     use super::mk;
 
     reg.register("reconstruct_while", |_args| mk(ReconstructWhile));
     reg.register("reconstruct_for_range", |_args| mk(ReconstructForRange));
     reg.register("remove_unused_labels", |_args| mk(RemoveUnusedLabels));
+    reg.register("reconstruct_single_exit_loop", |_args| {
+        mk(ReconstructSingleExitLoop)
+    });
 }
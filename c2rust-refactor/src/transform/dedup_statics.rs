@@ -0,0 +1,120 @@
+//! Deduplicate `static` items whose initializer is an identical byte-string
+//! (or string) literal, folding repeats into a single shared `static` and
+//! rewriting references to point at it.
+
+use rustc_ast::mut_visit::{self, MutVisitor};
+use rustc_ast::ptr::P;
+use rustc_ast::*;
+use rustc_ast_pretty::pprust;
+use rustc_span::symbol::Ident;
+use std::collections::HashMap;
+
+use crate::command::{CommandState, Registry};
+use crate::transform::Transform;
+use crate::RefactorCtxt;
+
+/// A literal key that two `static` items' initializers can be compared by.
+/// Only byte-string and string literals are considered; anything else
+/// (including identical-looking but non-literal initializers) is left
+/// alone, since proving two arbitrary initializer expressions are
+/// equivalent is out of scope for a purely syntactic pass.
+///
+/// The key also includes the static's declared type, so that e.g. a
+/// `[u8; 4]` and a `[u8; 8]` whose literal happens to print the same way
+/// (shouldn't happen in practice, but the type is cheap insurance) are
+/// never merged into the same storage.
+fn literal_key(ty: &Ty, expr: &Expr) -> Option<(&'static str, String, String)> {
+    let ty_str = pprust::ty_to_string(ty);
+    match &expr.kind {
+        ExprKind::Lit(Lit {
+            kind: LitKind::ByteStr(bytes),
+            ..
+        }) => Some((
+            "bytestr",
+            ty_str,
+            format!("{:?}", &bytes[..]),
+        )),
+        ExprKind::Lit(Lit {
+            kind: LitKind::Str(sym, _),
+            ..
+        }) => Some(("str", ty_str, sym.as_str().to_string())),
+        _ => None,
+    }
+}
+
+/// # `dedup_static_literals` Command
+///
+/// Usage: `dedup_static_literals`
+///
+/// Find top-level `static` items with identical byte-string or string
+/// literal initializers, keep the first one of each group, delete the
+/// rest, and rewrite every use of a deleted `static`'s name to refer to
+/// the surviving one instead.
+///
+/// Two statics are only considered duplicates if their initializers are
+/// textually identical literals and they declare the same type; this
+/// intentionally misses duplicates that differ only in how the transpiler
+/// formatted the same bytes (e.g. differing escape styles), since
+/// canonicalizing that is a separate concern from deduplication.
+///
+/// `static mut` items are never merged, even when they happen to share an
+/// initial value with another static: folding them into one shared
+/// storage location would make writes through one alias visible through
+/// the other, which is a real behavior change for two C globals that
+/// merely started out holding the same bytes.
+pub struct DedupStaticLiterals;
+
+impl Transform for DedupStaticLiterals {
+    fn transform(&self, krate: &mut Crate, _st: &CommandState, _cx: &RefactorCtxt) {
+        // Map from literal key -> the `Ident` of the surviving static.
+        let mut survivors: HashMap<(&'static str, String, String), Ident> = HashMap::new();
+        // Map from a duplicate static's name -> the survivor's name.
+        let mut renames: HashMap<Ident, Ident> = HashMap::new();
+
+        krate.items.retain(|item| {
+            let ItemKind::Static(ty, mutbl, Some(expr)) = &item.kind else {
+                return true;
+            };
+            if *mutbl == Mutability::Mut {
+                return true;
+            }
+            let Some(key) = literal_key(ty, expr) else {
+                return true;
+            };
+            match survivors.get(&key) {
+                Some(&survivor) => {
+                    renames.insert(item.ident, survivor);
+                    false
+                }
+                None => {
+                    survivors.insert(key, item.ident);
+                    true
+                }
+            }
+        });
+
+        if renames.is_empty() {
+            return;
+        }
+
+        struct Renamer<'a>(&'a HashMap<Ident, Ident>);
+        impl<'a> MutVisitor for Renamer<'a> {
+            fn visit_expr(&mut self, e: &mut P<Expr>) {
+                if let ExprKind::Path(None, path) = &mut e.kind {
+                    if let [seg] = &mut path.segments[..] {
+                        if let Some(&new_name) = self.0.get(&seg.ident) {
+                            seg.ident = new_name;
+                        }
+                    }
+                }
+                mut_visit::noop_visit_expr(e, self);
+            }
+        }
+        krate.visit(&mut Renamer(&renames));
+    }
+}
+
+pub fn register_commands(reg: &mut Registry) {
+    use super::mk;
+    reg.register("dedup_static_literals", |_args| mk(DedupStaticLiterals));
+}
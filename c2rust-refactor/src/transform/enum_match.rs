@@ -0,0 +1,158 @@
+//! Turn `if`/`else if` chains that compare the same scrutinee against a
+//! series of C-enum-like integer constants into a single `match`.
+
+use rustc_ast::mut_visit::{self, MutVisitor};
+use rustc_ast::ptr::P;
+use rustc_ast::*;
+use rustc_hir::def::{CtorKind, CtorOf, DefKind, Res};
+
+use crate::ast_builder::mk;
+use crate::ast_manip::AstEquiv;
+use crate::command::{CommandState, Registry};
+use crate::transform::Transform;
+use crate::RefactorCtxt;
+
+/// If `cond` has the shape `$scrutinee == $pat`, return the two sides.
+fn as_eq_arm(cond: &Expr) -> Option<(&Expr, &Expr)> {
+    match &cond.kind {
+        ExprKind::Binary(op, lhs, rhs) if op.node == BinOpKind::Eq => Some((lhs, rhs)),
+        _ => None,
+    }
+}
+
+/// Build a `match` pattern out of the right-hand side of an `==`
+/// comparison. Only integer/bool literals and paths that resolve to a
+/// `const`/`const` parameter/enum variant (the two shapes a transpiled C
+/// enum comparison can take) convert cleanly to a pattern; anything else
+/// means the chain isn't a genuine enum-style switch. In particular, a path
+/// to a plain local variable or function parameter must be rejected here --
+/// as a pattern it would be an irrefutable binding, not an equality test,
+/// which would silently make the first arm always match.
+fn expr_to_pat(e: &Expr, cx: &RefactorCtxt) -> Option<P<Pat>> {
+    match &e.kind {
+        ExprKind::Lit(_) => Some(P(Pat {
+            id: DUMMY_NODE_ID,
+            kind: PatKind::Lit(P(e.clone())),
+            span: e.span,
+            tokens: None,
+        })),
+        ExprKind::Path(qself, path) => {
+            let res = cx.try_resolve_expr_hir(e)?;
+            if !matches!(
+                res,
+                Res::Def(
+                    DefKind::Const
+                        | DefKind::ConstParam
+                        | DefKind::Variant
+                        | DefKind::Ctor(CtorOf::Variant, CtorKind::Const),
+                    _
+                )
+            ) {
+                return None;
+            }
+            Some(P(Pat {
+                id: DUMMY_NODE_ID,
+                kind: PatKind::Path(qself.clone(), path.clone()),
+                span: e.span,
+                tokens: None,
+            }))
+        }
+        _ => None,
+    }
+}
+
+/// Walk a chain of `if scrutinee == pat_i { body_i } else if ... else { default }`,
+/// collecting `(pat, body)` arms as long as every comparison is against the
+/// same scrutinee expression. Returns `None` if the chain isn't uniform
+/// (e.g. it compares different scrutinees, or a non-`else` tail appears).
+fn collect_chain(
+    expr: &Expr,
+    cx: &RefactorCtxt,
+) -> Option<(P<Expr>, Vec<(P<Pat>, P<Block>)>, Option<P<Block>>)> {
+    let mut arms = Vec::new();
+    let mut scrutinee: Option<P<Expr>> = None;
+    let mut cur = expr;
+    loop {
+        let ExprKind::If(cond, body, rest) = &cur.kind else {
+            return None;
+        };
+        let (lhs, rhs) = as_eq_arm(cond)?;
+        let pat = expr_to_pat(rhs, cx)?;
+        match &scrutinee {
+            Some(s) if !(**s).ast_equiv(lhs) => return None,
+            Some(_) => {}
+            None => scrutinee = Some(P(lhs.clone())),
+        }
+        arms.push((pat, body.clone()));
+        match rest {
+            None => return Some((scrutinee.unwrap(), arms, None)),
+            Some(tail) => match &tail.kind {
+                ExprKind::Block(b, _) => return Some((scrutinee.unwrap(), arms, Some(b.clone()))),
+                ExprKind::If(..) => cur = tail,
+                _ => return None,
+            },
+        }
+    }
+}
+
+fn build_match(
+    scrutinee: P<Expr>,
+    arms: Vec<(P<Pat>, P<Block>)>,
+    default: Option<P<Block>>,
+) -> P<Expr> {
+    let mut match_arms: Vec<Arm> = arms
+        .into_iter()
+        .map(|(pat, body)| mk().arm(pat, None::<P<Expr>>, mk().block_expr(body)))
+        .collect();
+    let default_body = default.unwrap_or_else(|| mk().block(Vec::<Stmt>::new()));
+    match_arms.push(mk().arm(
+        mk().wild_pat(),
+        None::<P<Expr>>,
+        mk().block_expr(default_body),
+    ));
+    mk().match_expr(scrutinee, match_arms)
+}
+
+struct Folder<'a, 'tcx> {
+    cx: &'a RefactorCtxt<'a, 'tcx>,
+}
+
+impl MutVisitor for Folder<'_, '_> {
+    fn visit_expr(&mut self, e: &mut P<Expr>) {
+        // Convert before visiting children, so the tail of a longer chain isn't
+        // turned into a `match` of its own first.
+        if matches!(e.kind, ExprKind::If(..)) {
+            if let Some((scrutinee, arms, default)) = collect_chain(e, self.cx) {
+                // Only bother when there are at least 3 comparisons against
+                // the same scrutinee; shorter chains read fine as `if`/`else`.
+                if arms.len() >= 3 {
+                    **e = *build_match(scrutinee, arms, default);
+                }
+            }
+        }
+        mut_visit::noop_visit_expr(e, self);
+    }
+}
+
+/// # `enum_ifs_to_match` Command
+///
+/// Usage: `enum_ifs_to_match`
+///
+/// Rewrite `if x == A { .. } else if x == B { .. } else { .. }` chains of
+/// three or more arms, all comparing the same scrutinee `x` with `==`
+/// against a literal or a path to a constant, into a single
+/// `match x { A => { .. }, B => { .. }, _ => { .. } }`. Shorter chains (one
+/// or two comparisons) are left as `if`/`else`, since a `match` doesn't
+/// read any better there.
+pub struct EnumIfsToMatch;
+
+impl Transform for EnumIfsToMatch {
+    fn transform(&self, krate: &mut Crate, _st: &CommandState, cx: &RefactorCtxt) {
+        krate.visit(&mut Folder { cx });
+    }
+}
+
+pub fn register_commands(reg: &mut Registry) {
+    use super::mk as mk_cmd;
+    reg.register("enum_ifs_to_match", |_args| mk_cmd(EnumIfsToMatch));
+}
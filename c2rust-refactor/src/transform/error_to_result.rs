@@ -0,0 +1,130 @@
+//! Convert C-style "return an error code, write the real result through an
+//! out-param" functions into idiomatic `Result`-returning functions.
+//!
+//! Unlike the ownership analysis's own error-code lowering (which only
+//! fires while it still has MIR-level dataflow facts about a function
+//! being actively rewritten), this transform works purely at the AST level
+//! on code that is already safe Rust, so it can be run as a cleanup pass
+//! well after transpilation and analysis are done.
+
+use rustc_ast::mut_visit::{self, MutVisitor};
+use rustc_ast::ptr::P;
+use rustc_ast::*;
+use rustc_ast_pretty::pprust;
+
+use crate::command::{CommandState, Registry};
+use crate::driver::parse_ty;
+use crate::matcher::replace_stmts;
+use crate::transform::Transform;
+use crate::RefactorCtxt;
+
+fn is_mut_out_ptr(param: &Param) -> bool {
+    matches!(
+        &param.ty.kind,
+        TyKind::Ptr(MutTy {
+            mutbl: Mutability::Mut,
+            ..
+        })
+    )
+}
+
+struct ConvertFolder<'a, 'tcx> {
+    st: &'a CommandState,
+    cx: &'a RefactorCtxt<'a, 'tcx>,
+    /// Integer literal that marks success (commonly `0`); only `return`
+    /// statements returning this literal from the old `i32` result are
+    /// turned into `Ok(..)`, everything else becomes `Err(..)`.
+    success: &'a str,
+}
+
+impl<'a, 'tcx> ConvertFolder<'a, 'tcx> {
+    fn convert_body(&self, body: &mut P<Block>) {
+        // Wrap every `return` in `Err(..)` first, then narrow the
+        // out-param-write success case back down to `Ok(..)`. Doing it in
+        // this order (rather than the reverse) means the success-case
+        // pattern below only has to recognize its own already-`Err`-wrapped
+        // shape, so it can never accidentally re-match a `return Ok($val);`
+        // this same pass just produced and double-wrap it as `Err(Ok($val))`.
+        replace_stmts(
+            self.st,
+            self.cx,
+            body,
+            "return $err:Expr;",
+            "return Err($err);",
+        );
+        replace_stmts(
+            self.st,
+            self.cx,
+            body,
+            &format!("*$out:Expr = $val:Expr; return Err({});", self.success),
+            "return Ok($val);",
+        );
+    }
+}
+
+impl<'a, 'tcx> MutVisitor for ConvertFolder<'a, 'tcx> {
+    fn flat_map_item(&mut self, mut i: P<Item>) -> SmallVec<[P<Item>; 1]> {
+        if self.st.marked(i.id, "target") {
+            i = i.map(|mut i| {
+                if let ItemKind::Fn(box Fn { sig, body, .. }) = &mut i.kind {
+                    if let Some(out) = sig.decl.inputs.last() {
+                        if is_mut_out_ptr(out) {
+                            let TyKind::Ptr(MutTy { ty: out_ty, .. }) = &out.ty.kind else {
+                                unreachable!()
+                            };
+                            let result_ty = parse_ty(
+                                self.cx.session(),
+                                &format!("Result<{}, i32>", pprust::ty_to_string(out_ty)),
+                            );
+                            sig.decl.inputs.pop();
+                            sig.decl.output = FnRetTy::Ty(result_ty);
+                            if let Some(body) = body {
+                                self.convert_body(body);
+                            }
+                        }
+                    }
+                }
+                i
+            });
+        }
+        mut_visit::noop_flat_map_item(i, self)
+    }
+}
+
+/// # `error_code_to_result` Command
+///
+/// Usage: `error_code_to_result SUCCESS_LITERAL`
+///
+/// Marks: `target`
+///
+/// For functions marked `target` whose last parameter is a `*mut T`
+/// out-param and whose body writes the real result through it before
+/// returning an integer status, drop the out-param, change the return type
+/// to `Result<T, i32>`, and rewrite `*out = val; return SUCCESS_LITERAL;`
+/// to `return Ok(val);` and any other `return err;` to `return Err(err);`.
+///
+/// Call sites are not updated by this command; run it together with a
+/// matching rewrite of callers (or use `select`/`rewrite_expr`) once the
+/// signature change has been reviewed.
+pub struct ErrorCodeToResult {
+    success: String,
+}
+
+impl Transform for ErrorCodeToResult {
+    fn transform(&self, krate: &mut Crate, st: &CommandState, cx: &RefactorCtxt) {
+        krate.visit(&mut ConvertFolder {
+            st,
+            cx,
+            success: &self.success,
+        });
+    }
+}
+
+pub fn register_commands(reg: &mut Registry) {
+    use super::mk;
+    reg.register("error_code_to_result", |args| {
+        mk(ErrorCodeToResult {
+            success: args.get(0).cloned().unwrap_or_else(|| "0".to_string()),
+        })
+    });
+}
@@ -0,0 +1,144 @@
+//! Recognize `foo_init(&mut Foo)`-style initializer functions left over
+//! from C `foo_init()` calling conventions and add a corresponding
+//! `impl Default`, without touching the original function (in case other
+//! code still calls it directly). No `new` constructor is generated, and
+//! calls to the init function are not rewritten.
+
+use rustc_ast::ptr::P;
+use rustc_ast::*;
+use rustc_ast_pretty::pprust;
+use std::collections::HashMap;
+
+use crate::command::{CommandState, Registry};
+use crate::driver::parse_items;
+use crate::transform::Transform;
+use crate::RefactorCtxt;
+
+/// Pull the struct name and binding mode out of an init function's first
+/// parameter, if it's a pointer or reference to a named type.
+fn self_struct_name(param: &Param) -> Option<(&str, bool)> {
+    match &param.ty.kind {
+        TyKind::Ptr(MutTy { ty, mutbl: Mutability::Mut }) => path_name(ty).map(|n| (n, true)),
+        TyKind::Ref(_, MutTy { ty, mutbl: Mutability::Mut }) => path_name(ty).map(|n| (n, false)),
+        _ => None,
+    }
+}
+
+fn path_name(ty: &Ty) -> Option<&str> {
+    match &ty.kind {
+        TyKind::Path(None, path) => path.segments.last().map(|s| s.ident.as_str()),
+        _ => None,
+    }
+}
+
+/// Extract `(field, value)` from a statement of the shape
+/// `(*self).field = value;` or `self.field = value;`.
+fn field_assignment<'a>(stmt: &'a Stmt, self_name: &str, deref: bool) -> Option<(&'a str, &'a Expr)> {
+    let (StmtKind::Expr(e) | StmtKind::Semi(e)) = &stmt.kind else {
+        return None;
+    };
+    let ExprKind::Assign(lhs, rhs, _) = &e.kind else {
+        return None;
+    };
+    let ExprKind::Field(base, field) = &lhs.kind else {
+        return None;
+    };
+    let base = if deref {
+        match &base.kind {
+            ExprKind::Unary(UnOp::Deref, inner) => inner,
+            _ => return None,
+        }
+    } else {
+        base
+    };
+    let ExprKind::Path(None, path) = &base.kind else {
+        return None;
+    };
+    if path.segments.len() != 1 || path.segments[0].ident.as_str() != self_name {
+        return None;
+    }
+    Some((field.as_str(), rhs))
+}
+
+/// # `init_fn_to_default` Command
+///
+/// Usage: `init_fn_to_default`
+///
+/// For each top-level `fn` whose name ends in `_init`, whose first
+/// parameter is a `&mut`/`*mut` reference to a struct `Foo`, and whose
+/// body consists only of `self.field = value;` (or `(*self).field =
+/// value;`) assignments covering every field of `Foo` with no parameters
+/// besides `self` read from, append `impl Default for Foo` to the end of
+/// the crate's top-level items. The original `_init` function is left untouched,
+/// since other code may still call it directly.
+pub struct InitFnToDefault;
+
+impl Transform for InitFnToDefault {
+    fn transform(&self, krate: &mut Crate, _st: &CommandState, cx: &RefactorCtxt) {
+        let mut struct_fields: HashMap<String, Vec<String>> = HashMap::new();
+        for item in &krate.items {
+            if let ItemKind::Struct(VariantData::Struct(fields, _), _) = &item.kind {
+                struct_fields.insert(
+                    item.ident.to_string(),
+                    fields.iter().map(|f| f.ident.unwrap().to_string()).collect(),
+                );
+            }
+        }
+
+        let mut new_items: Vec<P<Item>> = Vec::new();
+        for item in &krate.items {
+            let ItemKind::Fn(box Fn { sig, body: Some(body), .. }) = &item.kind else {
+                continue;
+            };
+            if !item.ident.as_str().ends_with("_init") || sig.decl.inputs.len() != 1 {
+                continue;
+            }
+            let Some((struct_name, deref)) = self_struct_name(&sig.decl.inputs[0]) else {
+                continue;
+            };
+            let Some(fields) = struct_fields.get(struct_name) else {
+                continue;
+            };
+            let self_name = sig.decl.inputs[0].pat.kind.clone();
+            let PatKind::Ident(_, self_ident, _) = self_name else {
+                continue;
+            };
+            let self_name = self_ident.as_str();
+
+            let mut assignments: HashMap<&str, String> = HashMap::new();
+            let mut ok = true;
+            for stmt in &body.stmts {
+                match field_assignment(stmt, self_name, deref) {
+                    Some((field, value)) => {
+                        assignments.insert(field, pprust::expr_to_string(value));
+                    }
+                    None => {
+                        ok = false;
+                        break;
+                    }
+                }
+            }
+            if !ok || fields.iter().any(|f| !assignments.contains_key(f.as_str())) {
+                continue;
+            }
+
+            let field_inits: Vec<String> = fields
+                .iter()
+                .map(|f| format!("{}: {}", f, assignments[f.as_str()]))
+                .collect();
+            let src = format!(
+                "impl Default for {name} {{\n    fn default() -> Self {{\n        {name} {{ {fields} }}\n    }}\n}}\n",
+                name = struct_name,
+                fields = field_inits.join(", "),
+            );
+            new_items.extend(parse_items(cx.session(), &src));
+        }
+
+        krate.items.extend(new_items);
+    }
+}
+
+pub fn register_commands(reg: &mut Registry) {
+    use super::mk;
+    reg.register("init_fn_to_default", |_args| mk(InitFnToDefault));
+}
@@ -0,0 +1,77 @@
+//! Replace calls to a fixed set of `libc` functions with their `std`
+//! equivalents, for crates that no longer need raw libc access after the
+//! pointer/ownership rewrites have landed.
+
+use rustc_ast::Crate;
+
+use crate::command::{CommandState, Registry};
+use crate::matcher::replace_expr;
+use crate::transform::Transform;
+use crate::RefactorCtxt;
+
+/// `(libc call pattern, std replacement)` pairs, tried in order. Patterns
+/// are kept conservative: only calls whose argument shape matches exactly
+/// are rewritten, so a left-over `libc::` call after running this command
+/// is a sign the call site needs a human look rather than a silent miss.
+///
+/// `$s`/`$name` arguments are assumed to still be raw `*const c_char` at
+/// the point this pass runs (it's meant to run before any pointer-to-slice
+/// or pointer-to-`String` rewrites), so the `strlen` and `getenv`
+/// replacements go through `CStr::from_ptr` rather than assuming a
+/// `&str`/slice is already in hand; `memchr`'s `$s` is read as raw bytes
+/// through `slice::from_raw_parts` instead.
+///
+/// `memcmp` is deliberately not mapped here: a faithful replacement needs
+/// to both slice its operands down to `$n` bytes and turn the resulting
+/// `Ordering` back into the signed `int` callers compare against zero,
+/// and there's no single textual pattern that does that correctly across
+/// the different ways callers use the result. `qsort` is left unmapped
+/// for the same reason — its comparator and element size are erased to
+/// `*const c_void`/`size_t`, so there's no sound way to recover the
+/// element type needed to build a `&mut [T]` from a fixed pattern.
+const REPLACEMENTS: &[(&str, &str)] = &[
+    (
+        "libc::strlen($s:Expr)",
+        "unsafe { std::ffi::CStr::from_ptr($s) }.to_bytes().len()",
+    ),
+    ("libc::abs($x:Expr)", "$x.abs()"),
+    ("libc::exit($code:Expr)", "std::process::exit($code)"),
+    (
+        "libc::getenv($name:Expr)",
+        "std::env::var(unsafe { std::ffi::CStr::from_ptr($name) }.to_str().unwrap()).ok()",
+    ),
+    (
+        "libc::memchr($s:Expr, $c:Expr, $n:Expr)",
+        "unsafe { std::slice::from_raw_parts($s as *const u8, $n as usize) }.iter().position(|&b| b == $c as u8)",
+    ),
+];
+
+/// # `libc_to_std` Command
+///
+/// Usage: `libc_to_std`
+///
+/// Rewrite a fixed list of common `libc` calls (`strlen`, `abs`, `exit`,
+/// `getenv`, `memchr`) to their `std` equivalents wherever the call's
+/// argument shape matches exactly. This is intentionally a small, curated
+/// list rather than a general FFI-to-std mapping; add patterns to
+/// `REPLACEMENTS` as they come up in practice.
+///
+/// `getenv` becomes an `Option<String>` (via `Result::ok`) rather than a
+/// nullable pointer, and `memchr` becomes an `Option<usize>` offset rather
+/// than a nullable pointer — callers that compared the original libc
+/// result against a null pointer need to switch to matching on the
+/// `Option` instead.
+pub struct LibcToStd;
+
+impl Transform for LibcToStd {
+    fn transform(&self, krate: &mut Crate, st: &CommandState, cx: &RefactorCtxt) {
+        for (pat, repl) in REPLACEMENTS {
+            replace_expr(st, cx, krate, pat, repl);
+        }
+    }
+}
+
+pub fn register_commands(reg: &mut Registry) {
+    use super::mk;
+    reg.register("libc_to_std", |_args| mk(LibcToStd));
+}
@@ -10,31 +10,39 @@ use crate::RefactorCtxt;
 // `cargo fmt` doesn't expand macros, so if we declare modules inside a macro,
 // they won't be formatted because `cargo fmt` won't find them.
 
+pub mod bitflags;
 pub mod canonicalize_refs;
 pub mod casts;
+pub mod dedup_statics;
 pub mod char_literals;
 pub mod control_flow;
+pub mod enum_match;
+pub mod error_to_result;
 pub mod exits;
 pub mod externs;
 pub mod format;
 pub mod funcs;
 pub mod generics;
+pub mod init_to_default;
 // TODO: this is disabled because it uses Subst for AssocItem
 // pub mod ionize;
 pub mod items;
 // TODO: this is disabled for now because it depends on analysis/runtime
 // pub mod lifetime_analysis;
+pub mod libc_to_std;
 pub mod linkage;
 pub mod literals;
 pub mod math;
 pub mod ownership;
 pub mod paths;
+pub mod rename_mangled;
 pub mod reorganize_definitions;
 pub mod retype;
 pub mod rewrite;
 pub mod statics;
 pub mod structs;
 pub mod test;
+pub mod unsafe_shrink;
 pub mod vars;
 
 /// An AST transformation that can be applied to a crate.
@@ -77,24 +85,31 @@ macro_rules! transform_modules {
 }
 
 transform_modules! {
+    bitflags,
     canonicalize_refs,
     casts,
     char_literals,
+    dedup_statics,
     control_flow,
+    enum_match,
+    error_to_result,
     exits,
     externs,
     format,
     funcs,
     generics,
+    init_to_default,
     // TODO: this is disabled because it uses Subst for AssocItem
     // ionize,
     items,
     // TODO: this is disabled for now because it depends on analysis/runtime
     // lifetime_analysis,
+    libc_to_std,
     linkage,
     literals,
     math,
     reorganize_definitions,
+    rename_mangled,
     ownership,
     paths,
     retype,
@@ -102,5 +117,6 @@ transform_modules! {
     statics,
     structs,
     test,
+    unsafe_shrink,
     vars,
 }
@@ -0,0 +1,144 @@
+//! Restore readable names for identifiers that the transpiler mangled to
+//! keep them unique, such as shadowed-local suffixes (`foo_0`, `foo_1`).
+
+use rustc_ast::mut_visit::{self, MutVisitor};
+use rustc_ast::ptr::P;
+use rustc_ast::*;
+use rustc_span::symbol::Symbol;
+use std::collections::{HashMap, HashSet};
+
+use crate::command::{CommandState, Registry};
+use crate::transform::Transform;
+use crate::RefactorCtxt;
+
+/// Strip a transpiler-added numeric suffix from a mangled name and return
+/// the name it was derived from, if the mangling pattern is recognized.
+///
+/// Recognized pattern: `<name>_0`, `<name>_1`, ... — shadowed locals the
+/// transpiler renamed to avoid collisions, where the original name is
+/// `<name>`. Names like `fresh3` that have no recorded original are left
+/// alone, since there is nothing to restore them to.
+fn original_name(mangled: &str) -> Option<&str> {
+    if mangled.starts_with("fresh") {
+        return None;
+    }
+    let pos = mangled.rfind('_')?;
+    let (base, suffix) = (&mangled[..pos], &mangled[pos + 1..]);
+    if base.is_empty() || suffix.is_empty() || !suffix.chars().all(|c| c.is_ascii_digit()) {
+        return None;
+    }
+    Some(base)
+}
+
+/// Renames shadowed locals back to their original name within a single
+/// function body, tracking which names are already bound in the current
+/// lexical scope so a rename never introduces a new collision.
+///
+/// Scoping is approximated at block granularity: a name is considered "in
+/// scope" for the rest of the innermost enclosing block once bound, which
+/// matches how the transpiler itself shadows locals.
+struct Renamer {
+    /// Names currently in scope, one `HashSet` per enclosing block.
+    scopes: Vec<HashSet<Symbol>>,
+    /// Renames (mangled name -> restored name) that are live in each
+    /// enclosing block, so use sites textually following the declaration
+    /// can be rewritten to match.
+    renames: Vec<HashMap<Symbol, Symbol>>,
+}
+
+impl Renamer {
+    fn new() -> Self {
+        Renamer {
+            scopes: vec![HashSet::new()],
+            renames: vec![HashMap::new()],
+        }
+    }
+
+    fn is_bound(&self, name: Symbol) -> bool {
+        self.scopes.iter().any(|s| s.contains(&name))
+    }
+
+    fn bind(&mut self, name: Symbol) {
+        self.scopes.last_mut().unwrap().insert(name);
+    }
+
+    /// Look up the innermost live rename for `name`, if any.
+    fn renamed(&self, name: Symbol) -> Option<Symbol> {
+        self.renames
+            .iter()
+            .rev()
+            .find_map(|m| m.get(&name).copied())
+    }
+}
+
+impl MutVisitor for Renamer {
+    fn visit_block(&mut self, b: &mut P<Block>) {
+        self.scopes.push(HashSet::new());
+        self.renames.push(HashMap::new());
+        mut_visit::noop_visit_block(b, self);
+        self.renames.pop();
+        self.scopes.pop();
+    }
+
+    fn visit_local(&mut self, l: &mut P<Local>) {
+        // Visit the init expression (and any use sites it contains) before
+        // this binding takes effect, so a self-referential `let x_0 = x_0;`
+        // still rewrites the right-hand side under the *outer* binding.
+        mut_visit::noop_visit_local(l, self);
+        if let PatKind::Ident(_, ident, _) = &mut l.pat.kind {
+            let mangled = ident.name;
+            if let Some(orig) = original_name(ident.as_str()) {
+                let orig = Symbol::intern(orig);
+                if !self.is_bound(orig) {
+                    ident.name = orig;
+                    self.renames.last_mut().unwrap().insert(mangled, orig);
+                }
+            }
+            self.bind(ident.name);
+        }
+    }
+
+    fn visit_expr(&mut self, e: &mut P<Expr>) {
+        if let ExprKind::Path(None, path) = &mut e.kind {
+            if let [segment] = path.segments.as_mut_slice() {
+                if let Some(new_name) = self.renamed(segment.ident.name) {
+                    segment.ident.name = new_name;
+                }
+            }
+        }
+        mut_visit::noop_visit_expr(e, self);
+    }
+}
+
+/// # `rename_mangled_idents` Command
+///
+/// Usage: `rename_mangled_idents`
+///
+/// Within each function body, rename locals whose name looks like a
+/// transpiler-mangled shadow (`foo_0`, `foo_1`, ...) back to their original
+/// name (`foo`), as long as doing so doesn't collide with another binding
+/// already in scope. Bindings that would collide keep their mangled name,
+/// so the rewrite never changes which declaration a use refers to.
+///
+/// Both the declaring `Pat` and every `ExprKind::Path` use site textually
+/// following it within the same block are rewritten, so a renamed binding
+/// and its uses stay consistent. This is function-local: identifiers
+/// declared in one function are never renamed based on uses in another.
+pub struct RenameMangledIdents;
+
+impl Transform for RenameMangledIdents {
+    fn transform(&self, krate: &mut Crate, _st: &CommandState, _cx: &RefactorCtxt) {
+        struct FnVisitor;
+        impl MutVisitor for FnVisitor {
+            fn visit_block(&mut self, b: &mut P<Block>) {
+                Renamer::new().visit_block(b);
+            }
+        }
+        krate.visit(&mut FnVisitor);
+    }
+}
+
+pub fn register_commands(reg: &mut Registry) {
+    use super::mk;
+    reg.register("rename_mangled_idents", |_args| mk(RenameMangledIdents));
+}
@@ -0,0 +1,159 @@
+//! Recompute which operations actually require `unsafe`, and use that to
+//! drop `unsafe` from `fn` signatures whose bodies no longer need it.
+//! `unsafe` blocks are left as they are: this pass neither shrinks nor
+//! removes them. Complements [`funcs::FixUnusedUnsafe`], which only removes
+//! blocks that rustc's own `unused_unsafe` lint has already flagged; this
+//! pass instead looks at whole `unsafe fn` bodies, which rustc never lints
+//! for unused unsafe since the function header already grants the context.
+//!
+//! [`funcs::FixUnusedUnsafe`]: super::funcs::FixUnusedUnsafe
+
+use rustc_ast::mut_visit::{self, MutVisitor};
+use rustc_ast::ptr::P;
+use rustc_ast::visit::{self, Visitor};
+use rustc_ast::*;
+use rustc_hir::def::{DefKind, Res};
+use rustc_hir::Unsafety;
+use rustc_middle::ty::TyKind;
+
+use crate::command::{CommandState, Registry};
+use crate::transform::Transform;
+use crate::RefactorCtxt;
+
+/// Walks an expression tree and reports whether it contains any operation
+/// that genuinely requires an `unsafe` context: a raw-pointer dereference,
+/// a call to an `unsafe fn`, a union field access, a `static mut` access, or
+/// inline assembly.
+struct NeedsUnsafe<'a, 'tcx> {
+    cx: &'a RefactorCtxt<'a, 'tcx>,
+    found: bool,
+}
+
+impl<'a, 'tcx> Visitor<'a> for NeedsUnsafe<'a, 'tcx> {
+    fn visit_expr(&mut self, e: &'a Expr) {
+        if self.found {
+            return;
+        }
+        match &e.kind {
+            ExprKind::Unary(UnOp::Deref, inner) => {
+                if matches!(self.cx.node_type(inner.id).kind(), TyKind::RawPtr(..)) {
+                    self.found = true;
+                    return;
+                }
+            }
+            ExprKind::InlineAsm(..) => {
+                self.found = true;
+                return;
+            }
+            ExprKind::Call(..) | ExprKind::MethodCall(..) => {
+                if let Some(sig) = self.cx.opt_callee_fn_sig(e) {
+                    if sig.unsafety == Unsafety::Unsafe {
+                        self.found = true;
+                        return;
+                    }
+                }
+            }
+            ExprKind::Field(base, _) => {
+                if let Some(ty) = self.cx.opt_node_type(base.id) {
+                    if matches!(ty.kind(), TyKind::Adt(adt_def, _) if adt_def.is_union()) {
+                        self.found = true;
+                        return;
+                    }
+                }
+            }
+            ExprKind::Path(..) => {
+                if let Some(Res::Def(DefKind::Static(mutbl), _)) = self.cx.try_resolve_expr_hir(e) {
+                    if mutbl == Mutability::Mut {
+                        self.found = true;
+                        return;
+                    }
+                }
+            }
+            _ => {}
+        }
+        visit::walk_expr(self, e);
+    }
+
+    fn visit_block(&mut self, b: &'a Block) {
+        // A nested explicit `unsafe { ... }` block always counts as still
+        // needing unsafe from the point of view of this pass: removing the
+        // outer `unsafe fn` qualifier must not strand an inner block with
+        // nothing to justify it, so we conservatively bail out rather than
+        // try to merge the two.
+        if let BlockCheckMode::Unsafe(UnsafeSource::UserProvided) = b.rules {
+            self.found = true;
+            return;
+        }
+        visit::walk_block(self, b);
+    }
+}
+
+fn body_needs_unsafe<'a, 'tcx>(cx: &'a RefactorCtxt<'a, 'tcx>, block: &'a Block) -> bool {
+    let mut v = NeedsUnsafe { cx, found: false };
+    v.visit_block(block);
+    v.found
+}
+
+struct DropUnneededUnsafeFnFolder<'a, 'tcx> {
+    cx: &'a RefactorCtxt<'a, 'tcx>,
+}
+
+impl<'a, 'tcx> DropUnneededUnsafeFnFolder<'a, 'tcx> {
+    fn maybe_drop(&self, header: &mut FnHeader, body: &Option<P<Block>>) {
+        let Unsafe::Yes(_) = header.unsafety else {
+            return;
+        };
+        let Some(body) = body else {
+            // No body to inspect (e.g. a trait method declaration); leave
+            // the qualifier alone since we can't tell whether callers rely
+            // on it.
+            return;
+        };
+        if !body_needs_unsafe(self.cx, body) {
+            header.unsafety = Unsafe::No;
+        }
+    }
+}
+
+impl<'a, 'tcx> MutVisitor for DropUnneededUnsafeFnFolder<'a, 'tcx> {
+    fn flat_map_item(&mut self, mut i: P<Item>) -> SmallVec<[P<Item>; 1]> {
+        if let ItemKind::Fn(box Fn { sig, body, .. }) = &mut i.kind {
+            self.maybe_drop(&mut sig.header, body);
+        }
+        mut_visit::noop_flat_map_item(i, self)
+    }
+
+    fn flat_map_impl_item(&mut self, mut i: P<AssocItem>) -> SmallVec<[P<AssocItem>; 1]> {
+        if let AssocItemKind::Fn(box Fn { sig, body, .. }) = &mut i.kind {
+            self.maybe_drop(&mut sig.header, body);
+        }
+        mut_visit::noop_flat_map_assoc_item(i, self)
+    }
+}
+
+/// # `drop_unneeded_unsafe_fn` Command
+///
+/// Usage: `drop_unneeded_unsafe_fn`
+///
+/// Remove the `unsafe` qualifier from `fn` items and associated functions
+/// whose bodies contain no raw-pointer dereference, call to an `unsafe fn`,
+/// union field access, `static mut` access, inline assembly, or nested
+/// `unsafe` block. Trait method declarations without a body are left
+/// untouched, since we cannot tell from the declaration alone whether
+/// implementers rely on the `unsafe` contract.
+///
+/// Run this after [`Transform`]s that have already removed the pointer
+/// operations responsible for most `unsafe` markers (e.g. pointer-to-
+/// reference rewrites), so it sees the final, safe body.
+pub struct DropUnneededUnsafeFn;
+
+impl Transform for DropUnneededUnsafeFn {
+    fn transform(&self, krate: &mut Crate, _st: &CommandState, cx: &RefactorCtxt) {
+        krate.visit(&mut DropUnneededUnsafeFnFolder { cx });
+    }
+}
+
+pub fn register_commands(reg: &mut Registry) {
+    use super::mk;
+    reg.register("drop_unneeded_unsafe_fn", |_args| mk(DropUnneededUnsafeFn));
+}
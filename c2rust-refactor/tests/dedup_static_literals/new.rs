@@ -0,0 +1,5 @@
+static A: &[u8] = b"hello";
+
+fn use_both() -> usize {
+    A.len() + A.len()
+}
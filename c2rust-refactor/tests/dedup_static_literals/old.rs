@@ -0,0 +1,6 @@
+static A: &[u8] = b"hello";
+static B: &[u8] = b"hello";
+
+fn use_both() -> usize {
+    A.len() + B.len()
+}
@@ -0,0 +1,7 @@
+fn add(a: i32, b: i32) -> i32 {
+    a + b
+}
+
+unsafe fn read_raw(p: *const i32) -> i32 {
+    *p
+}
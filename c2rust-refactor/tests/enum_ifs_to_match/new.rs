@@ -0,0 +1,20 @@
+const RED: i32 = 0;
+const GREEN: i32 = 1;
+const BLUE: i32 = 2;
+
+fn name_of(c: i32) -> i32 {
+    match c {
+        RED => {
+            return 0;
+        }
+        GREEN => {
+            return 1;
+        }
+        BLUE => {
+            return 2;
+        }
+        _ => {
+            return -1;
+        }
+    }
+}
@@ -0,0 +1,15 @@
+const RED: i32 = 0;
+const GREEN: i32 = 1;
+const BLUE: i32 = 2;
+
+fn name_of(c: i32) -> i32 {
+    if c == RED {
+        return 0;
+    } else if c == GREEN {
+        return 1;
+    } else if c == BLUE {
+        return 2;
+    } else {
+        return -1;
+    }
+}
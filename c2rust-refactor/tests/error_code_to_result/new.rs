@@ -0,0 +1,6 @@
+fn compute(x: i32) -> Result<i32, i32> {
+    if x < 0 {
+        return Err(1);
+    }
+    return Ok(x * 2);
+}
@@ -0,0 +1,7 @@
+fn compute(x: i32, out: *mut i32) -> i32 {
+    if x < 0 {
+        return 1;
+    }
+    *out = x * 2;
+    return 0;
+}
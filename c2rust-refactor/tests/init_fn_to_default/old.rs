@@ -0,0 +1,9 @@
+struct Point {
+    x: i32,
+    y: i32,
+}
+
+fn point_init(self_: &mut Point) {
+    self_.x = 0;
+    self_.y = 0;
+}
@@ -0,0 +1,29 @@
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct FooFlags(pub u32);
+
+impl FooFlags {
+    pub const FOO_READ: FooFlags = FooFlags(1);
+    pub const FOO_WRITE: FooFlags = FooFlags(2);
+    pub const FOO_EXEC: FooFlags = FooFlags(4);
+}
+
+impl std::ops::BitOr for FooFlags {
+    type Output = FooFlags;
+    fn bitor(self, rhs: FooFlags) -> FooFlags {
+        FooFlags(self.0 | rhs.0)
+    }
+}
+
+impl std::ops::BitAnd for FooFlags {
+    type Output = FooFlags;
+    fn bitand(self, rhs: FooFlags) -> FooFlags {
+        FooFlags(self.0 & rhs.0)
+    }
+}
+pub const FOO_READ: u32 = 1;
+pub const FOO_WRITE: u32 = 2;
+pub const FOO_EXEC: u32 = 4;
+
+fn has_read(x: u32) -> bool {
+    x == FooFlags::FOO_READ.0
+}
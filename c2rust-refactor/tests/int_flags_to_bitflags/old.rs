@@ -0,0 +1,7 @@
+pub const FOO_READ: u32 = 1;
+pub const FOO_WRITE: u32 = 2;
+pub const FOO_EXEC: u32 = 4;
+
+fn has_read(x: u32) -> bool {
+    x == FOO_READ
+}
@@ -0,0 +1,3 @@
+fn magnitude(x: i32) -> i32 {
+    libc::abs(x)
+}
@@ -0,0 +1,7 @@
+fn once() -> i32 {
+    let result;
+    {
+        result = 5;
+    }
+    result
+}
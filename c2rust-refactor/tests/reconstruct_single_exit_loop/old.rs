@@ -0,0 +1,8 @@
+fn once() -> i32 {
+    let result;
+    'a: loop {
+        result = 5;
+        break 'a;
+    }
+    result
+}
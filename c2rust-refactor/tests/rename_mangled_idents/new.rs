@@ -0,0 +1,4 @@
+fn example() -> i32 {
+    let y = 5;
+    y + 1
+}
@@ -0,0 +1,4 @@
+fn example() -> i32 {
+    let y_0 = 5;
+    y_0 + 1
+}
@@ -17,7 +17,7 @@
 
 use crate::c_ast::iterators::{DFExpr, SomeId};
 use crate::c_ast::CLabelId;
-use crate::diagnostics::TranslationResult;
+use crate::diagnostics::{diag, Diagnostic, TranslationResult};
 use crate::rust_ast::SpanExt;
 use c2rust_ast_printer::pprust;
 use proc_macro2::Span;
@@ -562,6 +562,63 @@ pub enum ImplicitReturnType {
     StmtExprVoid,
 }
 
+/// Look for the common `if (err) goto cleanup; ... cleanup: ...` idiom -- a label with exactly
+/// one `goto` to it, where that `goto` is the (braces optional) entire body of an `if` with no
+/// `else` -- and log a candidate for each one found under [`Diagnostic::GotoCleanup`].
+///
+/// This is detection only: actually rewriting the idiom into early returns plus a cleanup
+/// closure or `Drop` guard would mean restructuring the CFG this function is about to build
+/// rather than the raw statement tree we have here, and would need to prove the cleanup code
+/// is safe to duplicate (or hoist into a guard) at every one of the pattern's `if` sites -- a
+/// much larger, riskier change than logging where a human could apply it by hand.
+fn detect_goto_cleanup_candidates(
+    translator: &Translation,
+    stmt_ids: &[CStmtId],
+    c_label_to_goto: &IndexMap<CLabelId, IndexSet<CStmtId>>,
+) {
+    // Whether `stmt_id` is a `goto goto_id;`, possibly wrapped in a braces-only `{ goto goto_id; }`.
+    fn is_goto_or_wraps_goto(translator: &Translation, stmt_id: CStmtId, goto_id: CStmtId) -> bool {
+        if stmt_id == goto_id {
+            return true;
+        }
+        match translator.ast_context[stmt_id].kind {
+            CStmtKind::Compound(ref stmts) => {
+                matches!(stmts[..], [only] if is_goto_or_wraps_goto(translator, only, goto_id))
+            }
+            _ => false,
+        }
+    }
+
+    for (&label, gotos) in c_label_to_goto {
+        let goto_id = match gotos.len() {
+            1 => *gotos.iter().next().unwrap(),
+            _ => continue,
+        };
+        let is_single_guarded_goto = stmt_ids
+            .iter()
+            .flat_map(|&stmt_id| DFExpr::new(&translator.ast_context, stmt_id.into()))
+            .flat_map(SomeId::stmt)
+            .any(|candidate| match translator.ast_context[candidate].kind {
+                CStmtKind::If {
+                    true_variant,
+                    false_variant: None,
+                    ..
+                } => is_goto_or_wraps_goto(translator, true_variant, goto_id),
+                _ => false,
+            });
+        if is_single_guarded_goto {
+            diag!(
+                Diagnostic::GotoCleanup,
+                "found a single-forward-goto cleanup pattern: only one `goto` (at AST node {:?}) \
+                 reaches label {:?}, guarded by an `if` with no `else` -- this could likely be \
+                 restructured into an early return",
+                goto_id,
+                label,
+            );
+        }
+    }
+}
+
 /// A complete control-flow graph
 impl Cfg<Label, StmtOrDecl> {
     /// Completely process a statement into a control flow graph.
@@ -585,6 +642,8 @@ impl Cfg<Label, StmtOrDecl> {
             c_label_to_goto.entry(target).or_default().insert(x);
         }
 
+        detect_goto_cleanup_candidates(translator, stmt_ids, &c_label_to_goto);
+
         let mut cfg_builder = CfgBuilder::new(c_label_to_goto);
         let entry = cfg_builder.entry.clone();
         cfg_builder.per_stmt_stack.push(PerStmt::new(
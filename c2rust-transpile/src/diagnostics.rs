@@ -20,6 +20,9 @@ pub enum Diagnostic {
     All,
     Comments,
     ClangAst,
+    GotoCleanup,
+    AbiFidelity,
+    ArithmeticSanitizer,
 }
 
 macro_rules! diag {
@@ -61,6 +61,29 @@ pub enum TranslateMacros {
     Experimental,
 }
 
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ArithmeticSanitizer {
+    /// Translate C arithmetic and shifts exactly as-is, using Rust's native operators. This
+    /// reproduces C's unchecked behavior bit-for-bit (including its UB on signed overflow and
+    /// out-of-range shifts), which is what most callers want from a 1:1 port.
+    #[default]
+    BitExact,
+
+    /// Translate every arithmetic and shift operator (not just the unsigned ones we already
+    /// wrap for correctness) using its `wrapping_*` method, so a C program that happened to rely
+    /// on two's-complement wraparound keeps behaving the same way no matter what the optimizer
+    /// does with the now-defined-everywhere operation.
+    Wrapping,
+
+    /// Translate every signed arithmetic operator and every shift operator using its `checked_*`
+    /// method and panic with a message naming the site if the checked operation fails (unsigned
+    /// arithmetic keeps its `wrapping_*` translation), turning what was a silent
+    /// miscompile risk in C into a loud, attributable runtime error. Each rewritten site is also
+    /// logged via the `Diagnostic::ArithmeticSanitizer` warning category so the sites relying on
+    /// wraparound or overflow can be triaged one by one.
+    Checked,
+}
+
 /// Configuration settings for the translation process
 #[derive(Debug)]
 pub struct TranspilerConfig {
@@ -102,6 +125,16 @@ pub struct TranspilerConfig {
     pub output_dir: Option<PathBuf>,
     pub translate_const_macros: TranslateMacros,
     pub translate_fn_macros: TranslateMacros,
+    /// Emit real Rust `enum`s for C `enum` declarations (with a `#[repr(C)]` attribute plus
+    /// `From`/`TryFrom` impls for converting to/from the underlying integer type) instead of a
+    /// type alias to the integer type plus one `const` per enumerator. Off by default because it
+    /// changes the translated type's representation: code that relies on the old type alias
+    /// accepting any integer of that type without a cast will no longer compile.
+    pub translate_enums: bool,
+    /// Policy for translating C arithmetic and shift operators whose overflow/shift-amount
+    /// behavior is either UB (signed) or otherwise easy to silently get wrong. Defaults to
+    /// [`ArithmeticSanitizer::BitExact`], which keeps today's behavior of a faithful 1:1 port.
+    pub arithmetic_sanitizer: ArithmeticSanitizer,
     pub disable_rustfmt: bool,
     pub disable_refactoring: bool,
     pub preserve_unused_functions: bool,
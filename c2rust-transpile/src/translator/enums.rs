@@ -1,6 +1,6 @@
 use c2rust_ast_builder::mk;
 use proc_macro2::Span;
-use syn::Expr;
+use syn::{BinOp, Expr, ReturnType};
 
 use crate::{
     c_ast,
@@ -23,10 +23,127 @@ impl<'c> Translation<'c> {
             .borrow()
             .resolve_decl_name(enum_id)
             .expect("Enums should already be renamed");
-        let ty = self.convert_type(integral_type.ctype)?;
-        Ok(ConvertedDecl::Item(
-            mk().span(span).pub_().type_item(enum_name, ty),
-        ))
+        let repr_ty = self.convert_type(integral_type.ctype)?;
+
+        if !self.tcfg.translate_enums {
+            return Ok(ConvertedDecl::Item(
+                mk().span(span).pub_().type_item(enum_name, repr_ty),
+            ));
+        }
+
+        let variants = match self.ast_context[enum_id].kind {
+            CDeclKind::Enum { ref variants, .. } => variants.clone(),
+            _ => panic!("{:?} does not point to an `enum` declaration", enum_id),
+        };
+
+        let mut rust_variants = Vec::with_capacity(variants.len());
+        // `(variant_name, discriminant_value_expr)`, used below to build the `TryFrom` arms.
+        let mut discriminants = Vec::with_capacity(variants.len());
+        for &variant_id in &variants {
+            let value = match self.ast_context[variant_id].kind {
+                CDeclKind::EnumConstant { value, .. } => value,
+                _ => panic!("{:?} does not point to an enum variant", variant_id),
+            };
+            let variant_name = self
+                .renamer
+                .borrow()
+                .get(&variant_id)
+                .expect("Enum constant not named");
+            let disc = match value {
+                ConstIntExpr::I(value) => signed_int_expr(value),
+                ConstIntExpr::U(value) => mk().lit_expr(mk().int_unsuffixed_lit(value as u128)),
+            };
+            rust_variants.push(mk().unit_variant(&variant_name, Some(disc.clone())));
+            discriminants.push((variant_name, disc));
+        }
+
+        let enum_item = mk()
+            .span(span)
+            .pub_()
+            .call_attr("repr", vec!["C"])
+            .enum_item(enum_name, rust_variants);
+
+        // `impl From<Name> for ReprTy { fn from(x: Name) -> ReprTy { x as ReprTy } }`: the cast is
+        // always exact since every variant's discriminant was given its original C value above.
+        let from_body = mk().block(vec![
+            mk().expr_stmt(mk().cast_expr(mk().ident_expr("x"), repr_ty.clone()))
+        ]);
+        let from_sig = mk().fn_decl(
+            "from",
+            vec![mk().arg(mk().ident_ty(enum_name), mk().ident_pat("x"))],
+            None,
+            ReturnType::Type(Default::default(), repr_ty.clone()),
+        );
+        let from_trait_path = mk().abs_path(vec![
+            mk().path_segment("core"),
+            mk().path_segment("convert"),
+            mk().path_segment_with_args(
+                "From",
+                mk().angle_bracketed_args(vec![mk().ident_ty(enum_name)]),
+            ),
+        ]);
+        let from_impl = mk().trait_impl_item(
+            from_trait_path,
+            repr_ty.clone(),
+            vec![mk().method_impl_item(from_sig, from_body)],
+        );
+
+        // `impl TryFrom<ReprTy> for Name`: reject any integer that isn't one of the enum's known
+        // discriminant values, rather than reinterpreting arbitrary bits as a variant -- C callers
+        // can construct an out-of-range value (e.g. from untrusted input), and a real Rust `enum`
+        // must never hold one.
+        let mut try_from_body = mk().return_expr(Some(
+            mk().call_expr(mk().path_expr(vec!["Err"]), vec![mk().ident_expr("x")]),
+        ));
+        for (variant_name, disc) in discriminants.into_iter().rev() {
+            let cond = mk().binary_expr(
+                BinOp::Eq(Default::default()),
+                mk().ident_expr("x"),
+                mk().cast_expr(disc, repr_ty.clone()),
+            );
+            let then_branch = mk().block(vec![mk().expr_stmt(mk().return_expr(Some(
+                mk().call_expr(
+                    mk().path_expr(vec!["Ok"]),
+                    vec![mk().path_expr(vec![enum_name.as_str(), variant_name.as_str()])],
+                ),
+            )))]);
+            try_from_body = mk().ifte_expr(cond, then_branch, Some(try_from_body));
+        }
+        let try_from_block = mk().block(vec![mk().expr_stmt(try_from_body)]);
+        let try_from_sig = mk().fn_decl(
+            "try_from",
+            vec![mk().arg(repr_ty.clone(), mk().ident_pat("x"))],
+            None,
+            ReturnType::Type(
+                Default::default(),
+                mk().path_ty(vec![mk().path_segment_with_args(
+                    "Result",
+                    mk().angle_bracketed_args(vec![mk().ident_ty(enum_name), repr_ty.clone()]),
+                )]),
+            ),
+        );
+        let try_from_trait_path = mk().abs_path(vec![
+            mk().path_segment("core"),
+            mk().path_segment("convert"),
+            mk().path_segment_with_args(
+                "TryFrom",
+                mk().angle_bracketed_args(vec![repr_ty.clone()]),
+            ),
+        ]);
+        let try_from_impl = mk().trait_impl_item(
+            try_from_trait_path,
+            mk().ident_ty(enum_name),
+            vec![
+                mk().assoc_type_impl_item("Error", repr_ty),
+                mk().method_impl_item(try_from_sig, try_from_block),
+            ],
+        );
+
+        Ok(ConvertedDecl::Items(vec![
+            enum_item,
+            from_impl,
+            try_from_impl,
+        ]))
     }
 
     pub fn convert_enum_constant(
@@ -48,10 +165,16 @@ impl<'c> Translation<'c> {
             .expect("Enums should already be renamed");
         self.add_import(enum_id, &enum_name);
 
-        let ty = mk().ident_ty(enum_name);
-        let val = match value {
-            ConstIntExpr::I(value) => signed_int_expr(value),
-            ConstIntExpr::U(value) => mk().lit_expr(mk().int_unsuffixed_lit(value as u128)),
+        let ty = mk().ident_ty(&enum_name);
+        let val = if self.tcfg.translate_enums {
+            // The variant itself now carries the discriminant (see `convert_enum`); this const is
+            // just a convenient alias so existing references to the bare constant name still work.
+            mk().path_expr(vec![enum_name.as_str(), name.as_str()])
+        } else {
+            match value {
+                ConstIntExpr::I(value) => signed_int_expr(value),
+                ConstIntExpr::U(value) => mk().lit_expr(mk().int_unsuffixed_lit(value as u128)),
+            }
         };
 
         Ok(ConvertedDecl::Item(
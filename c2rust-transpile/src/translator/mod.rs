@@ -1928,6 +1928,7 @@ impl<'c> Translation<'c> {
                 manual_alignment,
                 max_field_alignment,
                 platform_byte_size,
+                platform_alignment,
                 ..
             } => self.convert_struct(
                 decl_id,
@@ -1935,6 +1936,7 @@ impl<'c> Translation<'c> {
                 fields,
                 is_packed,
                 platform_byte_size,
+                platform_alignment,
                 manual_alignment,
                 max_field_alignment,
             ),
@@ -2383,6 +2385,19 @@ impl<'c> Translation<'c> {
         // common type to minimize casts.
     }
 
+    /// `restrict`-qualified parameters (tracked in `typ.qualifiers.is_restrict`, parsed straight
+    /// off the Clang AST) are annotated here with a `#[c2rust::noalias(...)]` attribute naming
+    /// the Rust identifiers the qualifier applies to, rather than being silently dropped as
+    /// before. Likewise, a pointer-to-`const` parameter is annotated with
+    /// `#[c2rust::readonly(...)]`. `c2rust-analyze` doesn't read either attribute yet -- seeding
+    /// permissions from them is a separate follow-up -- but the qualifier information C gave us is
+    /// at least preserved in the output instead of being lost at this translation step.
+    ///
+    /// We don't attempt to upgrade a `const`-pointer parameter's type to `&T` here: proving that a
+    /// function body never casts away the constness it was given (e.g. via another cast, or by
+    /// passing the pointer somewhere that itself casts it away) needs whole-body analysis this
+    /// per-parameter translation step doesn't have, and getting it wrong would produce unsound
+    /// output, not just suboptimal output.
     fn convert_function(
         &self,
         ctx: ExprContext,
@@ -2403,10 +2418,30 @@ impl<'c> Translation<'c> {
 
         self.with_scope(|| {
             let mut args: Vec<FnArg> = vec![];
+            // Names of parameters C declared `restrict`-qualified, by their final (possibly
+            // renamed) Rust identifier.  `restrict` promises the analyzer would otherwise have
+            // no way to recover from a raw pointer parameter alone: within this function, no
+            // other pointer aliases the memory it points to.  We can't enforce that promise (C
+            // doesn't either), but we can at least pass it along instead of silently dropping it,
+            // via a `#[c2rust::noalias(...)]` attribute below. `c2rust-analyze` does not read
+            // this attribute; no permission is seeded from it.
+            let mut noalias_params: Vec<String> = vec![];
+            // Names of parameters whose C type is a pointer to `const`-qualified data, by their
+            // final Rust identifier. The pointee's constness already becomes a `*const` (rather
+            // than `*mut`) pointer type, but `c2rust-analyze` doesn't look at the generated
+            // pointer's own mutability when seeding permissions for it -- it derives `WRITE` from
+            // how the function body actually uses the pointer instead. A `#[c2rust::readonly(...)]`
+            // attribute records the qualifier in the output; `c2rust-analyze` does not read it
+            // either.
+            let mut readonly_params: Vec<String> = vec![];
 
             // handle regular (non-variadic) arguments
             for &(decl_id, ref var, typ) in arguments {
                 let ConvertedFunctionParam { ty, mutbl } = self.convert_function_param(ctx, typ)?;
+                let param_is_const_ptr = matches!(
+                    self.ast_context.resolve_type(typ.ctype).kind,
+                    CTypeKind::Pointer(pointee) if pointee.qualifiers.is_const
+                );
 
                 let pat = if var.is_empty() {
                     mk().wild_pat()
@@ -2429,6 +2464,13 @@ impl<'c> Translation<'c> {
                             )
                         });
 
+                    if typ.qualifiers.is_restrict {
+                        noalias_params.push(new_var.clone());
+                    }
+                    if param_is_const_ptr {
+                        readonly_params.push(new_var.clone());
+                    }
+
                     mk().set_mutbl(mutbl).ident_pat(new_var)
                 };
 
@@ -2534,9 +2576,23 @@ impl<'c> Translation<'c> {
                 }
 
                 // c99 extern inline functions should be pub, but not gnu_inline attributed
-                // extern inlines, which become subject to their gnu89 visibility (private)
-                let is_extern_inline =
-                    is_inline && is_extern && !attrs.contains(&c_ast::Attribute::GnuInline);
+                // extern inlines, which become subject to their gnu89 visibility (private).
+                //
+                // gnu89 inline semantics (selected by the `gnu_inline` attribute) swap the roles
+                // `extern` plays relative to C99: under gnu89, a plain `inline` definition (no
+                // `extern`) is the one that's externally visible and provides the out-of-line
+                // copy other translation units link against, while `extern inline` is the one
+                // that's assumed to be defined elsewhere and is never itself emitted as a
+                // standalone symbol. Without this, a gnu89 header-defined `inline` function
+                // would end up private to its own module, leaving every other translation unit
+                // that calls it with a missing-symbol error.
+                let is_gnu_inline = attrs.contains(&c_ast::Attribute::GnuInline);
+                let is_externally_visible_inline = is_inline
+                    && if is_gnu_inline {
+                        is_global && !is_extern
+                    } else {
+                        is_extern
+                    };
 
                 // Only add linkage attributes if the function is `extern`
                 let mut mk_ = if is_main {
@@ -2544,7 +2600,7 @@ impl<'c> Translation<'c> {
                     // FIXME: pass in a vector of NestedMetaItem elements,
                     // but strings have to do for now
                     self.mk_cross_check(mk(), vec!["entry(djb2=\"main\")", "exit(djb2=\"main\")"])
-                } else if (is_global && !is_inline) || is_extern_inline {
+                } else if (is_global && !is_inline) || is_externally_visible_inline {
                     mk_linkage(false, new_name, name, self.tcfg.edition)
                         .extern_("C")
                         .pub_()
@@ -2563,6 +2619,27 @@ impl<'c> Translation<'c> {
                     };
                 }
 
+                if !noalias_params.is_empty() {
+                    self.use_feature("register_tool");
+                    mk_ = mk_.call_attr(
+                        vec!["c2rust", "noalias"],
+                        noalias_params
+                            .iter()
+                            .map(String::as_str)
+                            .collect::<Vec<&str>>(),
+                    );
+                }
+                if !readonly_params.is_empty() {
+                    self.use_feature("register_tool");
+                    mk_ = mk_.call_attr(
+                        vec!["c2rust", "readonly"],
+                        readonly_params
+                            .iter()
+                            .map(String::as_str)
+                            .collect::<Vec<&str>>(),
+                    );
+                }
+
                 // If this function is just a regular inline
                 if is_inline && !attrs.contains(&c_ast::Attribute::AlwaysInline) {
                     mk_ = mk_.single_attr("inline");
@@ -2580,7 +2657,7 @@ impl<'c> Translation<'c> {
                     //   even if the `inline` keyword isn't present
                     // * gnu_inline instead applies gnu89 rules. extern inline will not emit an
                     //   externally visible function.
-                    if is_global && is_extern && !attrs.contains(&c_ast::Attribute::GnuInline) {
+                    if is_global && is_extern && !is_gnu_inline {
                         self.use_feature("linkage");
                         // ensures that public inlined rust function can be used in other modules
                         mk_ = mk_.str_attr("linkage", "external");
@@ -2608,6 +2685,27 @@ impl<'c> Translation<'c> {
                     };
                 }
 
+                if !noalias_params.is_empty() {
+                    self.use_feature("register_tool");
+                    mk_ = mk_.call_attr(
+                        vec!["c2rust", "noalias"],
+                        noalias_params
+                            .iter()
+                            .map(String::as_str)
+                            .collect::<Vec<&str>>(),
+                    );
+                }
+                if !readonly_params.is_empty() {
+                    self.use_feature("register_tool");
+                    mk_ = mk_.call_attr(
+                        vec!["c2rust", "readonly"],
+                        readonly_params
+                            .iter()
+                            .map(String::as_str)
+                            .collect::<Vec<&str>>(),
+                    );
+                }
+
                 let mk_ = mk_.unsafety(extern_block_unsafety(self.tcfg.edition));
                 let function_decl = mk_.fn_foreign_item(decl);
 
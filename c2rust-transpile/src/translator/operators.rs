@@ -2,6 +2,9 @@
 
 use super::*;
 
+use crate::diagnostics::{diag, Diagnostic};
+use crate::ArithmeticSanitizer;
+
 fn neg_expr(arg: Box<Expr>) -> Box<Expr> {
     mk().unary_expr(UnOp::Neg(Default::default()), arg)
 }
@@ -38,6 +41,80 @@ impl From<c_ast::BinOp> for BinOp {
 }
 
 impl<'c> Translation<'c> {
+    /// Apply the configured [`ArithmeticSanitizer`] policy to an arithmetic operator whose C
+    /// behavior on overflow is undefined (i.e. signed `+`/`-`/`*`/`/`/`%`). Unsigned arithmetic
+    /// is wrapped unconditionally elsewhere in this module regardless of this policy, since C
+    /// itself guarantees unsigned wraparound -- that's well-defined behavior, not a bug to flag.
+    fn sanitize_arith(
+        &self,
+        method: &'static str,
+        native: BinOp,
+        lhs: Box<Expr>,
+        rhs: Box<Expr>,
+    ) -> Box<Expr> {
+        match self.tcfg.arithmetic_sanitizer {
+            ArithmeticSanitizer::BitExact => mk().binary_expr(native, lhs, rhs),
+            ArithmeticSanitizer::Wrapping => {
+                mk().method_call_expr(lhs, format!("wrapping_{}", method), vec![rhs])
+            }
+            ArithmeticSanitizer::Checked => {
+                diag!(
+                    Diagnostic::ArithmeticSanitizer,
+                    "translating a signed `{}` whose overflow behavior was undefined in C as a \
+                     checked operation that panics instead",
+                    method,
+                );
+                let checked = mk().method_call_expr(lhs, format!("checked_{}", method), vec![rhs]);
+                mk().method_call_expr(
+                    checked,
+                    "expect",
+                    vec![mk().lit_expr(mk().str_lit(format!(
+                        "C2Rust: `{}` would have overflowed; the original C code's behavior here \
+                         was undefined",
+                        method,
+                    )))],
+                )
+            }
+        }
+    }
+
+    /// Like [`sanitize_arith`](Self::sanitize_arith), but for the shift operators, whose
+    /// `wrapping_*`/`checked_*` methods take a `u32` shift amount regardless of the operand type.
+    fn sanitize_shift(
+        &self,
+        method: &'static str,
+        native: BinOp,
+        lhs: Box<Expr>,
+        rhs: Box<Expr>,
+    ) -> Box<Expr> {
+        match self.tcfg.arithmetic_sanitizer {
+            ArithmeticSanitizer::BitExact => mk().binary_expr(native, lhs, rhs),
+            ArithmeticSanitizer::Wrapping => {
+                let rhs = cast_int(rhs, "u32", false);
+                mk().method_call_expr(lhs, format!("wrapping_{}", method), vec![rhs])
+            }
+            ArithmeticSanitizer::Checked => {
+                diag!(
+                    Diagnostic::ArithmeticSanitizer,
+                    "translating a `{}` whose shift amount was unchecked in C as a checked \
+                     operation that panics instead",
+                    method,
+                );
+                let rhs = cast_int(rhs, "u32", false);
+                let checked = mk().method_call_expr(lhs, format!("checked_{}", method), vec![rhs]);
+                mk().method_call_expr(
+                    checked,
+                    "expect",
+                    vec![mk().lit_expr(mk().str_lit(format!(
+                        "C2Rust: `{}` had an out-of-range shift amount; the original C code's \
+                         behavior here was undefined",
+                        method,
+                    )))],
+                )
+            }
+        }
+    }
+
     pub fn convert_binary_expr(
         &self,
         mut ctx: ExprContext,
@@ -211,8 +288,33 @@ impl<'c> Translation<'c> {
         lhs_type_id: CQualTypeId,
         rhs_type_id: CQualTypeId,
     ) -> TranslationResult<WithStmts<Box<Expr>>> {
-        if self.ast_context.resolve_type_id(compute_lhs_type_id.ctype)
-            == self.ast_context.resolve_type_id(lhs_type_id.ctype)
+        // The native `+=`/`-=`/etc. Rust operators below are just as unchecked in release builds
+        // as C's, so an active `arithmetic_sanitizer` policy needs to route signed arithmetic and
+        // shift compound-assignments through `convert_binary_operator` (and thus
+        // `sanitize_arith`/`sanitize_shift`) too, rather than taking this fast native-operator
+        // path, even when no type promotion is otherwise needed.
+        let is_unchecked_in_c = match bin_op {
+            c_ast::BinOp::Add
+            | c_ast::BinOp::Subtract
+            | c_ast::BinOp::Multiply
+            | c_ast::BinOp::Divide
+            | c_ast::BinOp::Modulus => !self
+                .ast_context
+                .resolve_type(compute_lhs_type_id.ctype)
+                .kind
+                .is_unsigned_integral_type(),
+            c_ast::BinOp::ShiftLeft | c_ast::BinOp::ShiftRight => true,
+            _ => false,
+        };
+        let needs_sanitizing = is_unchecked_in_c
+            && !matches!(
+                self.tcfg.arithmetic_sanitizer,
+                ArithmeticSanitizer::BitExact
+            );
+
+        if !needs_sanitizing
+            && self.ast_context.resolve_type_id(compute_lhs_type_id.ctype)
+                == self.ast_context.resolve_type_id(lhs_type_id.ctype)
         {
             Ok(WithStmts::new_val(mk().assign_op_expr(
                 bin_op_kind,
@@ -599,22 +701,32 @@ impl<'c> Translation<'c> {
             c_ast::BinOp::Multiply if is_unsigned_integral_type => {
                 mk().method_call_expr(lhs, "wrapping_mul", vec![rhs])
             }
-            c_ast::BinOp::Multiply => mk().binary_expr(BinOp::Mul(Default::default()), lhs, rhs),
+            c_ast::BinOp::Multiply => {
+                self.sanitize_arith("mul", BinOp::Mul(Default::default()), lhs, rhs)
+            }
 
             c_ast::BinOp::Divide if is_unsigned_integral_type => {
                 mk().method_call_expr(lhs, "wrapping_div", vec![rhs])
             }
-            c_ast::BinOp::Divide => mk().binary_expr(BinOp::Div(Default::default()), lhs, rhs),
+            c_ast::BinOp::Divide => {
+                self.sanitize_arith("div", BinOp::Div(Default::default()), lhs, rhs)
+            }
 
             c_ast::BinOp::Modulus if is_unsigned_integral_type => {
                 mk().method_call_expr(lhs, "wrapping_rem", vec![rhs])
             }
-            c_ast::BinOp::Modulus => mk().binary_expr(BinOp::Rem(Default::default()), lhs, rhs),
+            c_ast::BinOp::Modulus => {
+                self.sanitize_arith("rem", BinOp::Rem(Default::default()), lhs, rhs)
+            }
 
             c_ast::BinOp::BitXor => mk().binary_expr(BinOp::BitXor(Default::default()), lhs, rhs),
 
-            c_ast::BinOp::ShiftRight => mk().binary_expr(BinOp::Shr(Default::default()), lhs, rhs),
-            c_ast::BinOp::ShiftLeft => mk().binary_expr(BinOp::Shl(Default::default()), lhs, rhs),
+            c_ast::BinOp::ShiftRight => {
+                self.sanitize_shift("shr", BinOp::Shr(Default::default()), lhs, rhs)
+            }
+            c_ast::BinOp::ShiftLeft => {
+                self.sanitize_shift("shl", BinOp::Shl(Default::default()), lhs, rhs)
+            }
 
             c_ast::BinOp::EqualEqual | c_ast::BinOp::NotEqual => {
                 // Using `.is_none()` and `.is_some()` for null comparison means
@@ -678,7 +790,8 @@ impl<'c> Translation<'c> {
                 vec![rhs],
             )))
         } else {
-            Ok(WithStmts::new_val(mk().binary_expr(
+            Ok(WithStmts::new_val(self.sanitize_arith(
+                "add",
                 BinOp::Add(Default::default()),
                 lhs,
                 rhs,
@@ -715,7 +828,8 @@ impl<'c> Translation<'c> {
                 vec![rhs],
             )))
         } else {
-            Ok(WithStmts::new_val(mk().binary_expr(
+            Ok(WithStmts::new_val(self.sanitize_arith(
+                "sub",
                 BinOp::Sub(Default::default()),
                 lhs,
                 rhs,
@@ -11,7 +11,8 @@ use crate::c_ast::{
     BinOp, CDeclId, CDeclKind, CExprId, CExprKind, CFieldId, CQualTypeId, CRecordId, CTypeId,
     MemberKind,
 };
-use crate::diagnostics::TranslationResult;
+use crate::diagnostics::{diag, Diagnostic, TranslationResult};
+use crate::format_translation_err;
 use crate::translator::variadic::mk_va_list_ty;
 use crate::translator::{ConvertedDecl, ExprContext, Translation, PADDING_SUFFIX};
 use crate::with_stmts::WithStmts;
@@ -35,6 +36,7 @@ impl<'a> Translation<'a> {
         fields: &[CDeclId],
         is_packed: bool,
         platform_byte_size: u64,
+        platform_alignment: u64,
         manual_alignment: Option<u64>,
         max_field_alignment: Option<u64>,
     ) -> TranslationResult<ConvertedDecl> {
@@ -100,6 +102,23 @@ impl<'a> Translation<'a> {
             _ => {}
         }
 
+        // `platform_alignment` is Clang's own resolved alignment for this record under the
+        // target triple it was parsed for, so it's ground truth for what the struct's alignment
+        // actually is. Whenever we're about to bake a specific numeric alignment into the
+        // generated `repr` attributes (as opposed to falling through to `repr(C)`'s natural,
+        // per-field alignment, which mirrors Clang's own default rule by construction), make
+        // sure the number we're emitting doesn't silently diverge from it: `packed(N)` can't
+        // raise a struct's alignment past what its fields would naturally need, and a requested
+        // `aligned(N)` can't lower it below that either, so Clang's alignment landing above a
+        // `packed` bound or below an `aligned` one means our repr choice would give the struct a
+        // different alignment than Clang computed for it.
+        self.check_struct_abi_alignment(
+            &name,
+            manual_alignment,
+            max_field_alignment,
+            platform_alignment,
+        )?;
+
         if let Some(alignment) = manual_alignment {
             // This is the most complicated case: we have `align(N)` which
             // might be mixed with or included into a `packed` structure,
@@ -179,6 +198,67 @@ impl<'a> Translation<'a> {
         }
     }
 
+    /// Cross-check the alignment we're about to bake into a struct's `repr` attributes against
+    /// `platform_alignment`, the alignment Clang itself computed for this record under the
+    /// target triple it was parsed for. We only have an independent number to compare against
+    /// when we're overriding the struct's alignment explicitly (`aligned(N)` or `packed`/
+    /// `packed(N)`); in the common case of plain `repr(C)` with no override, Rust's layout
+    /// algorithm derives the same natural, per-field alignment Clang does, so there's nothing to
+    /// verify. Clang's alignment landing above a `packed` bound or below an `aligned` one means
+    /// the attribute we're about to emit would give the generated
+    /// type a different alignment than its C counterpart actually has -- a real ABI bug, not
+    /// just a style choice -- so we report it the same way other irrecoverable translation
+    /// problems are reported, respecting `fail_on_error` for whether that's fatal.
+    fn check_struct_abi_alignment(
+        &self,
+        name: &str,
+        manual_alignment: Option<u64>,
+        max_field_alignment: Option<u64>,
+        platform_alignment: u64,
+    ) -> TranslationResult<()> {
+        let declared = manual_alignment.or(max_field_alignment);
+        let declared = match declared {
+            Some(declared) => declared,
+            None => return Ok(()),
+        };
+        // `packed(N)` only lowers and `aligned(N)` only raises the fields' natural alignment, so
+        // Clang's number may legitimately sit below a `packed` bound or above an `aligned` one.
+        let diverges = if manual_alignment.is_some() {
+            platform_alignment < declared
+        } else {
+            platform_alignment > declared
+        };
+        if !diverges {
+            return Ok(());
+        }
+        let attr = if manual_alignment.is_some() {
+            "an `aligned` attribute"
+        } else {
+            "a `packed` attribute"
+        };
+        if self.tcfg.fail_on_error {
+            return Err(format_translation_err!(
+                None,
+                "struct `{}` would be translated with alignment {} (from {}), but Clang \
+                 computed alignment {} for it under the target triple it was parsed for",
+                name,
+                declared,
+                attr,
+                platform_alignment,
+            ));
+        }
+        diag!(
+            Diagnostic::AbiFidelity,
+            "struct `{}` would be translated with alignment {} (from {}), but Clang computed \
+             alignment {} for it under the target triple it was parsed for",
+            name,
+            declared,
+            attr,
+            platform_alignment,
+        );
+        Ok(())
+    }
+
     pub fn convert_union(
         &self,
         decl_id: CDeclId,
@@ -51,6 +51,8 @@ fn config(edition: RustEdition) -> TranspilerConfig {
         output_dir: None,
         translate_const_macros: Default::default(),
         translate_fn_macros: Default::default(),
+        translate_enums: false,
+        arithmetic_sanitizer: Default::default(),
         disable_rustfmt: false,
         disable_refactoring: false,
         preserve_unused_functions: false,
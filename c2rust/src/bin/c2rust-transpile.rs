@@ -54,6 +54,17 @@ struct Args {
     #[clap(long, value_enum, default_value_t)]
     translate_fn_macros: TranslateMacros,
 
+    /// Policy for translating arithmetic and shift operators whose overflow/shift-amount
+    /// behavior was undefined in C: reproduce it as-is, force wrapping semantics everywhere, or
+    /// force checked semantics that panic (and are logged) at each such site.
+    #[clap(long, value_enum, default_value_t)]
+    arithmetic_sanitizer: ArithmeticSanitizer,
+
+    /// Emit real Rust enums (with From/TryFrom impls) for C enums instead of
+    /// a type alias plus integer consts.
+    #[clap(long)]
+    translate_enums: bool,
+
     /// Disable relooping function bodies incrementally
     #[clap(long)]
     no_incremental_relooper: bool,
@@ -221,6 +232,32 @@ impl From<TranslateMacros> for c2rust_transpile::TranslateMacros {
     }
 }
 
+#[derive(Default, Debug, ValueEnum, Clone)]
+pub enum ArithmeticSanitizer {
+    /// Translate arithmetic and shifts exactly as-is, matching C's unchecked behavior bit for
+    /// bit. This is the default.
+    #[default]
+    BitExact,
+
+    /// Force every arithmetic and shift operator whose C behavior was unchecked to use its
+    /// `wrapping_*` method.
+    Wrapping,
+
+    /// Force every arithmetic and shift operator whose C behavior was unchecked to use its
+    /// `checked_*` method, panicking (and logging a diagnostic naming the site) if it fails.
+    Checked,
+}
+
+impl From<ArithmeticSanitizer> for c2rust_transpile::ArithmeticSanitizer {
+    fn from(this: ArithmeticSanitizer) -> Self {
+        match this {
+            ArithmeticSanitizer::BitExact => c2rust_transpile::ArithmeticSanitizer::BitExact,
+            ArithmeticSanitizer::Wrapping => c2rust_transpile::ArithmeticSanitizer::Wrapping,
+            ArithmeticSanitizer::Checked => c2rust_transpile::ArithmeticSanitizer::Checked,
+        }
+    }
+}
+
 #[derive(Default, Debug, ValueEnum, Clone)]
 pub enum CrossCheckBackend {
     DynamicDlsym,
@@ -290,6 +327,8 @@ fn main() {
 
         translate_const_macros: args.translate_const_macros.into(),
         translate_fn_macros: args.translate_fn_macros.into(),
+        translate_enums: args.translate_enums,
+        arithmetic_sanitizer: args.arithmetic_sanitizer.into(),
         disable_rustfmt: args.disable_rustfmt,
         disable_refactoring: args.disable_refactoring,
         preserve_unused_functions: args.preserve_unused_functions,
@@ -0,0 +1,52 @@
+#[macro_use]
+extern crate lazy_static;
+extern crate libc;
+
+use std::env;
+use std::io::{BufWriter, Write};
+use std::net::TcpStream;
+use std::sync::Mutex;
+
+type XCheckWriter = BufWriter<TcpStream>;
+
+lazy_static! {
+    static ref RB_XCHECK_MUTEX: Mutex<Option<XCheckWriter>> = {
+        extern "C" fn cleanup() {
+            // Flush any buffered checks before the socket closes, so the
+            // peer sees every check we sent, not just the ones that
+            // happened to fill a buffer.
+            let mut guard = RB_XCHECK_MUTEX.lock().unwrap();
+            if let Some(mut out) = guard.take() {
+                out.flush().expect("Failed to flush cross-checks to peer");
+            }
+        }
+        unsafe { libc::atexit(cleanup) };
+
+        let xchecks_addr = env::var("CROSS_CHECKS_TCP_ADDR")
+            .expect("Expected a host:port address in CROSS_CHECKS_TCP_ADDR variable");
+        let stream = TcpStream::connect(&xchecks_addr).unwrap_or_else(|e| {
+            panic!(
+                "Failed to connect cross-checks TCP backend to {}: {}",
+                xchecks_addr, e
+            )
+        });
+        Mutex::new(Some(BufWriter::new(stream)))
+    };
+}
+
+// Cross-checking backend that streams each check to a TCP peer as it
+// happens, instead of batching everything up in a local file. This lets
+// another process (e.g. the other replica in an MVEE-style setup, or a
+// monitor process) observe and compare checks online, so a divergence can
+// be reported as soon as it occurs instead of after the fact.
+#[no_mangle]
+pub extern "C" fn rb_xcheck(tag: u8, val: u64) {
+    let mut guard = RB_XCHECK_MUTEX.lock().unwrap();
+    let out = guard.as_mut().unwrap();
+    out.write_all(&[tag]).expect("Failed to write tag to peer");
+    out.write_all(&val.to_le_bytes())
+        .expect("Failed to write value to peer");
+    // Flush immediately: buffering would delay divergence detection on
+    // the peer's side until our buffer happened to fill up.
+    out.flush().expect("Failed to flush check to peer");
+}
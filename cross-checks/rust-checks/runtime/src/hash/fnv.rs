@@ -0,0 +1,50 @@
+use super::CrossCheckHasher;
+use core::hash::Hasher;
+
+#[derive(Debug)]
+pub struct FnvHasher(u64);
+
+// FNV-1a constants for the 64-bit variant
+const FNV_OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325_u64;
+const FNV_PRIME: u64 = 0x0000_0100_0000_01b3_u64;
+
+impl Default for FnvHasher {
+    #[inline]
+    fn default() -> FnvHasher {
+        FnvHasher(FNV_OFFSET_BASIS)
+    }
+}
+
+impl Hasher for FnvHasher {
+    #[inline]
+    fn finish(&self) -> u64 {
+        self.0
+    }
+
+    #[inline]
+    fn write(&mut self, bytes: &[u8]) {
+        self.0 = bytes.iter().fold(self.0, |h, byte| {
+            (h ^ u64::from(*byte)).wrapping_mul(FNV_PRIME)
+        });
+    }
+}
+
+impl CrossCheckHasher for FnvHasher {}
+
+#[cfg(test)]
+mod tests {
+    use super::{FnvHasher, Hasher};
+
+    fn fnv_string(s: &str) -> u64 {
+        let mut h = FnvHasher::default();
+        h.write(s.as_bytes());
+        h.finish()
+    }
+
+    #[test]
+    fn test_fnv() {
+        assert_eq!(fnv_string(""), 0xcbf2_9ce4_8422_2325_u64);
+        assert_eq!(fnv_string("a"), 0xaf63_dc4c_8601_ec8c_u64);
+        assert_eq!(fnv_string("foobar"), 0x85944171_f73967e8_u64);
+    }
+}
@@ -5,6 +5,7 @@ use core::mem;
 use libc;
 
 pub mod djb2;
+pub mod fnv;
 pub mod jodyhash;
 pub mod simple;
 
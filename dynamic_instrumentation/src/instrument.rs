@@ -37,6 +37,46 @@ use crate::point::{
 };
 use crate::util::Convert;
 
+/// Restricts which functions get instrumented, configured via the
+/// `C2RUST_INSTRUMENT_ONLY`/`C2RUST_INSTRUMENT_SKIP` environment variables
+/// (comma-separated function names). If `C2RUST_INSTRUMENT_ONLY` is set,
+/// `C2RUST_INSTRUMENT_SKIP` is ignored; if neither is set, every function
+/// is instrumented.
+enum FunctionFilter {
+    AllowAll,
+    Only(std::collections::HashSet<String>),
+    Skip(std::collections::HashSet<String>),
+}
+
+impl FunctionFilter {
+    fn allows(&self, name: &str) -> bool {
+        match self {
+            Self::AllowAll => true,
+            Self::Only(names) => names.contains(name),
+            Self::Skip(names) => !names.contains(name),
+        }
+    }
+}
+
+fn parse_name_list(var: &str) -> Option<std::collections::HashSet<String>> {
+    let value = std::env::var(var).ok()?;
+    Some(value.split(',').map(|s| s.trim().to_string()).collect())
+}
+
+fn function_filter() -> &'static FunctionFilter {
+    use once_cell::sync::OnceCell;
+    static FILTER: OnceCell<FunctionFilter> = OnceCell::new();
+    FILTER.get_or_init(|| {
+        if let Some(names) = parse_name_list("C2RUST_INSTRUMENT_ONLY") {
+            FunctionFilter::Only(names)
+        } else if let Some(names) = parse_name_list("C2RUST_INSTRUMENT_SKIP") {
+            FunctionFilter::Skip(names)
+        } else {
+            FunctionFilter::AllowAll
+        }
+    })
+}
+
 #[derive(Default)]
 pub struct Instrumenter {
     mir_locs: Mutex<IndexSet<MirLoc>>,
@@ -64,6 +104,10 @@ impl Instrumenter {
     /// Instrument memory operations in-place in the function `body`.
     pub fn instrument_fn<'tcx>(&self, tcx: TyCtxt<'tcx>, body: &mut Body<'tcx>, body_did: DefId) {
         let function_name = tcx.item_name(body_did);
+        if !function_filter().allows(function_name.as_str()) {
+            debug!("Skipping instrumentation of filtered function {}", function_name);
+            return;
+        }
         debug!("Instrumenting function {}", function_name);
 
         self.add_fn(body_did, tcx);
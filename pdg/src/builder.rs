@@ -11,11 +11,22 @@ use std::io::{self, BufReader};
 use std::iter;
 use std::path::Path;
 
-pub fn read_event_log(path: &Path) -> io::Result<Vec<Event>> {
+/// Open an event log and stream its events one at a time, without loading
+/// the whole file into memory. Each event is decoded from the reader lazily
+/// as the returned iterator is advanced, so callers that only need to scan
+/// the log (e.g. to merge several logs, or detect the first violation) can
+/// avoid the multi-gigabyte allocations that a full [`read_event_log`]
+/// collect would require on long-running traces.
+pub fn read_event_log_iter(path: &Path) -> io::Result<impl Iterator<Item = Event>> {
     let file = File::open(path)?;
     let mut reader = BufReader::new(file);
-    let events = iter::from_fn(|| bincode::deserialize_from(&mut reader).ok()).collect::<Vec<_>>();
-    Ok(events)
+    Ok(iter::from_fn(move || {
+        bincode::deserialize_from(&mut reader).ok()
+    }))
+}
+
+pub fn read_event_log(path: &Path) -> io::Result<Vec<Event>> {
+    Ok(read_event_log_iter(path)?.collect())
 }
 
 pub fn read_metadata(path: &Path) -> eyre::Result<Metadata> {
@@ -37,6 +37,7 @@ pub enum ToPrint {
     LatestAssignments,
     WritePermissions,
     Metadata,
+    AliasViolations,
 }
 
 impl Display for ToPrint {
@@ -52,8 +53,14 @@ pub struct Pdg {
 }
 
 impl Pdg {
-    pub fn new(metadata_path: &Path, event_log_path: &Path) -> eyre::Result<Self> {
-        let events = read_event_log(event_log_path)?;
+    pub fn new(metadata_path: &Path, event_log_paths: &[PathBuf]) -> eyre::Result<Self> {
+        let events = event_log_paths
+            .iter()
+            .map(|path| read_event_log(path))
+            .collect::<Result<Vec<_>, _>>()?
+            .into_iter()
+            .flatten()
+            .collect::<Vec<_>>();
         let metadata = read_metadata(metadata_path)?;
         let mut graphs = construct_pdg(&events, &metadata);
         add_info(&mut graphs);
@@ -122,6 +129,15 @@ impl Display for PdgRepr<'_> {
                         .collect::<Vec<_>>();
                     writeln!(f, "nodes_that_need_write = {needs_write:?}")?;
                 }
+                if should_print(ToPrint::AliasViolations) {
+                    for (node_id, node) in graph.aliasing_violations() {
+                        writeln!(
+                            f,
+                            "aliasing violation at {node_id}: fn {} {:?}[{}]",
+                            node.function, node.block, node.statement_idx
+                        )?;
+                    }
+                }
                 writeln!(f)?;
             }
         }
@@ -145,9 +161,13 @@ impl Display for PdgRepr<'_> {
 #[derive(Debug, Parser)]
 #[clap(author, version, about, long_about = None)]
 pub struct Args {
-    /// Path to an event log from a run of an instrumented program.
-    #[clap(long, value_parser)]
-    event_log: PathBuf,
+    /// Path(s) to an event log from a run of an instrumented program. If
+    /// more than one path is given (e.g. from several runs against the
+    /// same instrumented binary), their events are concatenated into a
+    /// single consolidated graph; this assumes all the given logs were
+    /// produced with the same `--metadata`.
+    #[clap(long, value_parser, num_args = 1..)]
+    event_log: Vec<PathBuf>,
 
     /// Path to the instrumented program's metadata generated at compile/instrumentation time.
     #[clap(long, value_parser)]
@@ -355,7 +375,7 @@ mod tests {
         ensure!(status.success(), eyre!("{cmd:?} failed: {status}"));
         drop(guard);
 
-        let pdg = Pdg::new(&metadata_path, &event_log_path)?;
+        let pdg = Pdg::new(&metadata_path, std::slice::from_ref(&event_log_path))?;
         pdg.graphs.assert_all_tests();
         let repr = pdg.repr(to_print);
         Ok(repr.to_string())
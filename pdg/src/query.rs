@@ -13,7 +13,7 @@
 
 use linked_hash_set::LinkedHashSet;
 
-use crate::graph::{Graph, NodeId, NodeKind};
+use crate::graph::{Graph, Node, NodeId, NodeKind};
 
 impl Graph {
     /// Query an object [`Graph`] to determine which of its [`Node`]s (returned as [`NodeId`]s)
@@ -50,4 +50,29 @@ impl Graph {
         }
         needs_write.into_iter()
     }
+
+    /// Find [`Node`]s that perform a write ([`Graph::needs_write_permission`])
+    /// at a point where the [`NodeInfo::unique`] analysis could not prove the
+    /// pointer was exclusively owned. Such a node indicates a place where
+    /// the instrumented run actually observed (or at least could not rule
+    /// out) aliasing across a write, i.e. a violation of the aliasing model
+    /// that later rewrites into safe references would assume.
+    ///
+    /// Each returned `(NodeId, &Node)` pair carries everything needed for
+    /// source attribution: [`Node::function`], [`Node::block`], and
+    /// [`Node::statement_idx`] identify exactly where in the original MIR
+    /// the violation was observed.
+    ///
+    /// [`NodeInfo::unique`]: crate::info::NodeInfo::unique
+    pub fn aliasing_violations(&self) -> impl Iterator<Item = (NodeId, &Node)> {
+        self.needs_write_permission().filter_map(move |node_id| {
+            let node = &self.nodes[node_id];
+            let is_unique = node.info.as_ref().map(|info| info.unique).unwrap_or(false);
+            if is_unique {
+                None
+            } else {
+                Some((node_id, node))
+            }
+        })
+    }
 }